@@ -1,3 +1,8 @@
 fn main() {
     println!("cargo:rerun-if-changed=examples/");
+    // for the tests compiling grammars with `cc`, which needs to know the target outside of build scripts
+    println!(
+        "cargo:rustc-env=TARGET={}",
+        std::env::var("TARGET").expect("TARGET is set by Cargo")
+    );
 }
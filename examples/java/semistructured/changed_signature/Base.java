@@ -0,0 +1,8 @@
+class Account {
+    int balance;
+
+    void deposit(int amount) {
+        check(amount);
+        balance += amount;
+    }
+}
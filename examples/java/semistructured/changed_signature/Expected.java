@@ -0,0 +1,9 @@
+class Account {
+    int balance;
+
+    void deposit(long amount) {
+        check(amount);
+        balance += amount;
+        log(amount);
+    }
+}
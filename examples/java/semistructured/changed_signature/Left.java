@@ -0,0 +1,8 @@
+class Account {
+    int balance;
+
+    void deposit(long amount) {
+        check(amount);
+        balance += amount;
+    }
+}
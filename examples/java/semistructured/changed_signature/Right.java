@@ -0,0 +1,9 @@
+class Account {
+    int balance;
+
+    void deposit(int amount) {
+        check(amount);
+        balance += amount;
+        log(amount);
+    }
+}
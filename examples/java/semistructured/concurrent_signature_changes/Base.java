@@ -0,0 +1,3 @@
+class A {
+    void f(int a){g(0);}
+}
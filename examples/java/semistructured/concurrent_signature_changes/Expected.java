@@ -0,0 +1,9 @@
+class A {
+<<<<<<< LEFT
+    void f(long a){g(1);}
+||||||| BASE
+    void f(int a){g(0);}
+=======
+    void f(short a){g(2);}
+>>>>>>> RIGHT
+}
@@ -0,0 +1,3 @@
+class A {
+    void f(short a){g(2);}
+}
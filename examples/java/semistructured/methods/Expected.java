@@ -0,0 +1,15 @@
+class Teste {
+  int soma(){
+    return 10+10;
+  }
+<<<<<<< LEFT
+  int subtracao(){
+    return 20-10;
+  }
+||||||| BASE
+=======
+  int subtracao(){
+    return 30-20;
+  }
+>>>>>>> RIGHT
+}
@@ -0,0 +1,11 @@
+class Teste {
+  int diminuir(){
+    return 30-20;
+  }
+  int soma(){
+    return 10+10;
+  }
+  int subtracao(){
+    return 20-10;
+  }
+}
@@ -0,0 +1,17 @@
+class Main {
+    int sum(int a, int b) {
+        int result = b + a;
+        log(result);
+        return result;
+    }
+
+    void other() {
+<<<<<<< LEFT
+        System.out.println("hello left");
+||||||| BASE
+        System.out.println("hello");
+=======
+        System.out.println("hello right");
+>>>>>>> RIGHT
+    }
+}
@@ -0,0 +1,11 @@
+class Main {
+    int total(int a, int b) {
+        int result = b + a;
+        log(result);
+        return result;
+    }
+
+    void other() {
+        System.out.println("hello right");
+    }
+}
@@ -0,0 +1,23 @@
+class Main {
+<<<<<<< LEFT
+    int sum(int a, int b) {
+||||||| BASE
+    int total(int a, int b) {
+=======
+    int add(int a, int b) {
+>>>>>>> RIGHT
+        int result = a + b;
+        log(result);
+        return result;
+    }
+
+    void other() {
+<<<<<<< LEFT
+        System.out.println("hello left");
+||||||| BASE
+        System.out.println("hello");
+=======
+        System.out.println("hello right");
+>>>>>>> RIGHT
+    }
+}
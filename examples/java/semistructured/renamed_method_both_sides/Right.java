@@ -0,0 +1,11 @@
+class Main {
+    int add(int a, int b) {
+        int result = a + b;
+        log(result);
+        return result;
+    }
+
+    void other() {
+        System.out.println("hello right");
+    }
+}
@@ -0,0 +1,7 @@
+class Teste {
+  void conta(){
+    for(int i = 1; i < 11; i++){
+      System.out.println(i);
+    }
+  }
+}
@@ -0,0 +1,10 @@
+class Main {
+    int sum(int a, int b) {
+        int result = compute(a, b);
+        return result;
+    }
+
+    void other() {
+        System.out.println("hello");
+    }
+}
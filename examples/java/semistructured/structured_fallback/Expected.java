@@ -0,0 +1,16 @@
+class Main {
+    int sum(int a, int b) {
+        int result = compute(a + 1, b * 2);
+        return result;
+    }
+
+    void other() {
+<<<<<<< LEFT
+        System.out.println("hello left");
+||||||| BASE
+        System.out.println("hello");
+=======
+        System.out.println("hello right");
+>>>>>>> RIGHT
+    }
+}
@@ -0,0 +1,11 @@
+class A {
+<<<<<<< LEFT
+    public void f() {
+||||||| BASE
+    void f() {
+=======
+    private void f() {
+>>>>>>> RIGHT
+        x();
+    }
+}
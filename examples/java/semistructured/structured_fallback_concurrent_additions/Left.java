@@ -0,0 +1,5 @@
+class A {
+    public void f() {
+        x();
+    }
+}
@@ -0,0 +1,5 @@
+class A {
+    private void f() {
+        x();
+    }
+}
@@ -0,0 +1,7 @@
+class Main {
+    int sum(int a, int b) {
+        log(a);
+        int result = compute(a, b);
+        return result;
+    }
+}
@@ -0,0 +1,13 @@
+class Main {
+<<<<<<< LEFT
+    long sum(int a, int b) {
+||||||| BASE
+    int sum(int a, int b) {
+=======
+    short sum(int a, int b) {
+>>>>>>> RIGHT
+        log(a);
+        int result = compute(a + 1, b * 2);
+        return result;
+    }
+}
@@ -0,0 +1,7 @@
+class Main {
+    long sum(int a, int b) {
+        log(a);
+        int result = compute(a + 1, b);
+        return result;
+    }
+}
@@ -0,0 +1,7 @@
+class Main {
+    short sum(int a, int b) {
+        log(a);
+        int result = compute(a, b * 2);
+        return result;
+    }
+}
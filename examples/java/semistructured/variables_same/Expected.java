@@ -1,5 +1,9 @@
 class Bird {
     String species;
+<<<<<<< LEFT
     int weight;
-    double wingspan;
+||||||| BASE
+=======
+    double weight;
+>>>>>>> RIGHT
 }
@@ -0,0 +1,11 @@
+import java.io.File;
+<<<<<<< LEFT
+import java.util.List;
+||||||| BASE
+=======
+import java.awt.List;
+>>>>>>> RIGHT
+import java.util.Map;
+
+class Main {
+}
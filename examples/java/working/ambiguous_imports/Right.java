@@ -0,0 +1,6 @@
+import java.io.File;
+import java.awt.List;
+import java.util.Map;
+
+class Main {
+}
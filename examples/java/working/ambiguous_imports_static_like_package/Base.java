@@ -0,0 +1,5 @@
+import java.io.File;
+import java.util.Map;
+
+class Main {
+}
@@ -0,0 +1,6 @@
+import java.io.File;
+import staticutils.List;
+import java.util.Map;
+
+class Main {
+}
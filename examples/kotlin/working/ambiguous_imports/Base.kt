@@ -0,0 +1,6 @@
+package com.example.foo
+
+import com.example.foo.core.Config
+
+object Obj {
+}
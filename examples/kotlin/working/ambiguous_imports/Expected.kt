@@ -0,0 +1,13 @@
+package com.example.foo
+
+import com.example.foo.core.Config
+<<<<<<< LEFT
+import com.example.foo.core.Logger
+||||||| BASE
+=======
+import com.example.foo.util.Logger
+>>>>>>> RIGHT
+import com.example.foo.util.Helper
+
+object Obj {
+}
@@ -0,0 +1,7 @@
+package com.example.foo
+
+import com.example.foo.core.Config
+import com.example.foo.core.Logger
+
+object Obj {
+}
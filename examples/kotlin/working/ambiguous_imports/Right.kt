@@ -0,0 +1,8 @@
+package com.example.foo
+
+import com.example.foo.core.Config
+import com.example.foo.util.Helper
+import com.example.foo.util.Logger
+
+object Obj {
+}
@@ -0,0 +1,12 @@
+import scala.math.max
+
+class Calculator {
+  val precision: Int = 2
+  def add(a: Int, b: Int): Int = {
+    a + b
+  }
+
+  def sub(a: Int, b: Int): Int = {
+    a - b
+  }
+}
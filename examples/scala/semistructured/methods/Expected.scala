@@ -0,0 +1,13 @@
+import scala.math.max
+
+class Calculator {
+  val precision: Int = 3
+  def add(a: Int, b: Int = 0): Int = {
+    val result = a + b
+    result
+  }
+
+  def sub(a: Int, b: Int): Int = {
+    max(a - b, 0)
+  }
+}
@@ -0,0 +1,8 @@
+pragma solidity ^0.8.0;
+
+contract Counter {
+    uint public count;
+    function increment() public {
+        count += 1;
+    }
+}
@@ -0,0 +1,14 @@
+pragma solidity ^0.8.0;
+
+contract Counter {
+    uint public count;
+    function decrement() public {
+        count -= 1;
+    }
+    function reset() public {
+        count = 0;
+    }
+    function increment() public {
+        count += 2;
+    }
+}
@@ -0,0 +1,11 @@
+pragma solidity ^0.8.0;
+
+contract Counter {
+    uint public count;
+    function decrement() public {
+        count -= 1;
+    }
+    function increment() public {
+        count += 1;
+    }
+}
@@ -0,0 +1,228 @@
+use std::{fmt::Display, path::Path, time::Duration};
+
+use clap::ValueEnum;
+
+use crate::{
+    scenario::{Scenario, find_scenarios},
+    strategies::{MergeOutcome, Strategy},
+};
+
+/// How to print the results of a comparison
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// an aligned table, followed by a summary for each strategy
+    #[default]
+    Table,
+    /// comma-separated values, with a header line
+    Csv,
+}
+
+/// Which of the expected outputs of a scenario a merge reproduces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expectation {
+    Expected,
+    ExpectedIdeally,
+    ExpectedCurrently,
+    None,
+}
+
+impl Display for Expectation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Expected => write!(f, "Expected"),
+            Self::ExpectedIdeally => write!(f, "ExpectedIdeally"),
+            Self::ExpectedCurrently => write!(f, "ExpectedCurrently"),
+            Self::None => write!(f, "-"),
+        }
+    }
+}
+
+/// The result of merging a scenario with a strategy
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// The path of the scenario, relative to the compared directory
+    pub scenario: String,
+    pub strategy: Strategy,
+    pub outcome: MergeOutcome,
+    pub expectation: Expectation,
+}
+
+/// Merges all the scenarios found in a directory with all the strategies
+pub fn compare_strategies(
+    dir: &Path,
+    language: Option<&'static str>,
+    merge_command: Option<&str>,
+    timeout: Duration,
+) -> Result<Vec<Comparison>, String> {
+    let strategies = Strategy::all(merge_command);
+    let mut comparisons = Vec::new();
+    for scenario_dir in find_scenarios(dir)? {
+        let scenario = Scenario::load(&scenario_dir)?;
+        let name = scenario_dir.strip_prefix(dir).unwrap_or(&scenario_dir);
+        let name = if name.as_os_str().is_empty() {
+            scenario_dir.display().to_string()
+        } else {
+            name.display().to_string()
+        };
+        let expected_outputs = [
+            (Expectation::Expected, scenario.expected("Expected")),
+            (
+                Expectation::ExpectedIdeally,
+                scenario.expected("ExpectedIdeally"),
+            ),
+            (
+                Expectation::ExpectedCurrently,
+                scenario.expected("ExpectedCurrently"),
+            ),
+        ];
+        for strategy in &strategies {
+            let outcome = strategy.merge(
+                &scenario.base,
+                &scenario.left,
+                &scenario.right,
+                &scenario.base_path(),
+                scenario.language.or(language),
+                merge_command,
+                timeout,
+            );
+            let expectation = expected_outputs
+                .iter()
+                .find(|(_, expected)| expected.as_ref() == Some(&outcome.contents))
+                .map_or(Expectation::None, |(expectation, _)| *expectation);
+            comparisons.push(Comparison {
+                scenario: name.clone(),
+                strategy: *strategy,
+                outcome,
+                expectation,
+            });
+        }
+    }
+    Ok(comparisons)
+}
+
+/// Prints the comparisons in the given format
+pub fn print_comparisons(comparisons: &[Comparison], format: OutputFormat) {
+    let header = [
+        "scenario",
+        "strategy",
+        "conflicts",
+        "mass",
+        "time_ms",
+        "matches",
+    ]
+    .map(String::from);
+    let rows: Vec<[String; 6]> = comparisons
+        .iter()
+        .map(|comparison| {
+            [
+                comparison.scenario.clone(),
+                comparison.strategy.to_string(),
+                comparison.outcome.conflict_count.to_string(),
+                comparison.outcome.conflict_mass.to_string(),
+                format!("{:.1}", comparison.outcome.duration.as_secs_f64() * 1000.),
+                comparison.expectation.to_string(),
+            ]
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Csv => {
+            for row in std::iter::once(&header).chain(&rows) {
+                let fields: Vec<_> = row.iter().map(|field| csv_field(field)).collect();
+                println!("{}", fields.join(","));
+            }
+        }
+        OutputFormat::Table => {
+            let mut widths = header.clone().map(|column| column.len());
+            for row in &rows {
+                for (width, field) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(field.len());
+                }
+            }
+            for row in std::iter::once(&header).chain(&rows) {
+                let fields: Vec<_> = row
+                    .iter()
+                    .zip(widths)
+                    .map(|(field, width)| format!("{field:width$}"))
+                    .collect();
+                println!("{}", fields.join("  ").trim_end());
+            }
+            println!();
+            print_summary(comparisons);
+        }
+    }
+}
+
+/// Prints, for each strategy, how many scenarios it merged as expected, along with its
+/// total number of conflicts and running time
+fn print_summary(comparisons: &[Comparison]) {
+    let mut strategies: Vec<Strategy> = Vec::new();
+    for comparison in comparisons {
+        if !strategies.contains(&comparison.strategy) {
+            strategies.push(comparison.strategy);
+        }
+    }
+    for strategy in strategies {
+        let results: Vec<_> = comparisons
+            .iter()
+            .filter(|comparison| comparison.strategy == strategy)
+            .collect();
+        let as_expected = results
+            .iter()
+            .filter(|comparison| comparison.expectation == Expectation::Expected)
+            .count();
+        let conflicts: usize = results
+            .iter()
+            .map(|comparison| comparison.outcome.conflict_count)
+            .sum();
+        let time_ms: f64 = results
+            .iter()
+            .map(|comparison| comparison.outcome.duration.as_secs_f64() * 1000.)
+            .sum();
+        println!(
+            "{strategy}: {as_expected}/{} as expected, {conflicts} conflicts, {time_ms:.1} ms",
+            results.len()
+        );
+    }
+}
+
+/// Quotes a CSV field if needed
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::DEFAULT_TIMEOUT;
+
+    #[test]
+    fn compare_strategies_on_example() {
+        let comparisons = compare_strategies(
+            Path::new("../examples/java/working/demo"),
+            None,
+            None,
+            DEFAULT_TIMEOUT,
+        )
+        .expect("failed to compare strategies");
+        let strategies = Strategy::all(None);
+        assert_eq!(comparisons.len(), strategies.len());
+        let structured = comparisons
+            .iter()
+            .find(|comparison| comparison.strategy == Strategy::Structured)
+            .unwrap();
+        assert_eq!(structured.expectation, Expectation::Expected);
+        assert_eq!(structured.outcome.conflict_count, 0);
+    }
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_field("java/working/demo"), "java/working/demo");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,428 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs::{self, File},
+    io::Read,
+    path::Path,
+};
+
+use mergiraf::{
+    attempts::AttemptsCache,
+    git::{read_content_from_commit, read_content_from_commits},
+    lang_profile::LangProfile,
+    newline::normalize_to_lf,
+};
+use zip::ZipArchive;
+
+use crate::{
+    mine::git,
+    strategies::{DEFAULT_TIMEOUT, Strategy},
+};
+
+/// The revisions of a merged file, to be exported as a test case
+#[derive(Debug, Clone)]
+pub struct Revisions {
+    /// The name of the merged file, which determines its language
+    pub file_name: String,
+    pub base: String,
+    pub left: String,
+    pub right: String,
+    /// The resolution of the merge, if known
+    pub resolution: Option<String>,
+}
+
+impl Revisions {
+    /// Reads the revisions of a file merged in a merge commit (its two parents and their
+    /// merge base), along with the resolution committed in the merge commit
+    pub fn from_merge_commit(
+        repo_dir: &Path,
+        merge_commit: &str,
+        path: &Path,
+    ) -> Result<Self, String> {
+        let commits = git(
+            repo_dir,
+            &["rev-list", "--parents", "-n", "1", merge_commit],
+        )?;
+        let [merge_commit, left_commit, right_commit] =
+            commits.split_whitespace().collect::<Vec<_>>()[..]
+        else {
+            return Err(format!(
+                "{merge_commit} is not a merge commit with two parents"
+            ));
+        };
+        let revisions = Self::from_commits(repo_dir, left_commit, right_commit, path)?;
+        let resolution = read_content_from_commit(repo_dir, merge_commit, path)
+            .ok_or_else(|| format!("{} is missing from {merge_commit}", path.display()))?;
+        Ok(Self {
+            resolution: Some(resolution),
+            ..revisions
+        })
+    }
+
+    /// Reads the revisions of a file at both ends of a commit range such as `main...feature`
+    /// and in their merge base, which is the merge `git merge` would do. Its resolution is unknown.
+    pub fn from_commit_range(repo_dir: &Path, range: &str, path: &Path) -> Result<Self, String> {
+        let (left_commit, right_commit) = range
+            .split_once("...")
+            .or_else(|| range.split_once(".."))
+            .ok_or_else(|| format!("Invalid commit range {range}, expected LEFT...RIGHT"))?;
+        // as in git, an omitted end of the range stands for `HEAD`
+        let or_head = |commit| {
+            if str::is_empty(commit) {
+                "HEAD"
+            } else {
+                commit
+            }
+        };
+        Self::from_commits(repo_dir, or_head(left_commit), or_head(right_commit), path)
+    }
+
+    /// Reads the revisions of a file in two commits and their merge base
+    fn from_commits(
+        repo_dir: &Path,
+        left_commit: &str,
+        right_commit: &str,
+        path: &Path,
+    ) -> Result<Self, String> {
+        let base_commit = git(repo_dir, &["merge-base", left_commit, right_commit])?;
+        let (base, left, right) = read_content_from_commits(
+            repo_dir,
+            (base_commit.trim(), left_commit, right_commit),
+            path,
+        )
+        .ok_or_else(|| {
+            format!(
+                "{} is missing from {left_commit}, {right_commit} or their merge base",
+                path.display()
+            )
+        })?;
+        let file_name = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .ok_or_else(|| format!("Invalid file name: {}", path.display()))?;
+        Ok(Self {
+            file_name: file_name.to_owned(),
+            base,
+            left,
+            right,
+            resolution: None,
+        })
+    }
+
+    /// Reads the revisions stored in a merge attempt
+    pub fn from_attempt(attempts_cache: &AttemptsCache, attempt_id: &str) -> Result<Self, String> {
+        let (file_name, base, left, right) = attempts_cache.read_revisions(attempt_id)?;
+        Ok(Self {
+            file_name,
+            base,
+            left,
+            right,
+            resolution: None,
+        })
+    }
+
+    /// Reads the revisions stored in an archive created by `mergiraf report`. The merge
+    /// output it contains is ignored, since it is the one being reported as faulty.
+    pub fn from_report(archive: &Path) -> Result<Self, String> {
+        let read_error = |err: &dyn std::fmt::Display| {
+            format!("Could not read archive {}: {err}", archive.display())
+        };
+        let file = File::open(archive).map_err(|err| read_error(&err))?;
+        let mut zip = ZipArchive::new(file).map_err(|err| read_error(&err))?;
+
+        // the files of the archive are stored in a directory named after the archive
+        let mut files = HashMap::new();
+        for index in 0..zip.len() {
+            let mut entry = zip.by_index(index).map_err(|err| read_error(&err))?;
+            if entry.is_dir() {
+                continue;
+            }
+            let name = entry
+                .name()
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_owned();
+            let mut contents = String::new();
+            entry
+                .read_to_string(&mut contents)
+                .map_err(|err| read_error(&err))?;
+            files.insert(name, contents);
+        }
+
+        let mut take = |name: &str| {
+            files
+                .remove(name)
+                .ok_or_else(|| format!("No {name} file in archive {}", archive.display()))
+        };
+        let file_name = take("filename.txt")?;
+        // same as in `mergiraf report`
+        let extension = file_name
+            .rsplit_once('.')
+            .map_or("no_ext", |(_stem, ext)| ext);
+        Ok(Self {
+            base: take(&format!("Base.{extension}"))?,
+            left: take(&format!("Left.{extension}"))?,
+            right: take(&format!("Right.{extension}"))?,
+            file_name,
+            resolution: None,
+        })
+    }
+}
+
+/// Writes the revisions as a test case in a new directory, such as `examples/java/working/my_case`.
+///
+/// The expected output is the resolution of the merge if known, or the current output of
+/// Mergiraf otherwise. If the directory is located in a `failing` directory, the current
+/// output is written to `ExpectedCurrently` and the resolution to `ExpectedIdeally` instead.
+/// A `language` file is written if a language is supplied.
+pub fn export(
+    revisions: Revisions,
+    dest: &Path,
+    language: Option<&'static str>,
+) -> Result<(), String> {
+    if dest.exists() {
+        return Err(format!("{} already exists", dest.display()));
+    }
+    let fname_base = Path::new(&revisions.file_name);
+    LangProfile::find_by_filename_or_name(fname_base, language)?;
+    let suffix = fname_base
+        .extension()
+        .and_then(OsStr::to_str)
+        .map_or_else(String::new, |ext| format!(".{ext}"));
+
+    let (base, left, right) = (
+        normalize_to_lf(revisions.base),
+        normalize_to_lf(revisions.left),
+        normalize_to_lf(revisions.right),
+    );
+    let resolution = revisions
+        .resolution
+        .map(|resolution| normalize_to_lf(resolution).into_owned());
+    let current = Strategy::Structured
+        .merge(
+            &base,
+            &left,
+            &right,
+            fname_base,
+            language,
+            None,
+            DEFAULT_TIMEOUT,
+        )
+        .contents;
+
+    let failing = dest
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|name| name == "failing");
+    let expected_outputs = if failing {
+        let ideal = match resolution {
+            Some(resolution) if resolution == current => {
+                return Err(
+                    "Mergiraf already reproduces the resolution of this merge, export it as a working test case instead"
+                        .to_owned(),
+                );
+            }
+            Some(resolution) => resolution,
+            None => {
+                eprintln!(
+                    "mgf_dev: the resolution of this merge is unknown, edit ExpectedIdeally{suffix} to provide it"
+                );
+                current.clone()
+            }
+        };
+        vec![("ExpectedCurrently", current), ("ExpectedIdeally", ideal)]
+    } else {
+        let expected = match resolution {
+            Some(resolution) => {
+                if resolution != current {
+                    eprintln!(
+                        "mgf_dev: Mergiraf does not reproduce the resolution of this merge, consider exporting it as a failing test case"
+                    );
+                }
+                resolution
+            }
+            None => current,
+        };
+        vec![("Expected", expected)]
+    };
+
+    fs::create_dir_all(dest)
+        .map_err(|err| format!("Could not create {}: {err}", dest.display()))?;
+    let write = |file_name: &str, contents: &str| {
+        let path = dest.join(file_name);
+        fs::write(&path, contents)
+            .map_err(|err| format!("Could not write {}: {err}", path.display()))
+    };
+    write(&format!("Base{suffix}"), &base)?;
+    write(&format!("Left{suffix}"), &left)?;
+    write(&format!("Right{suffix}"), &right)?;
+    for (name, contents) in &expected_outputs {
+        write(&format!("{name}{suffix}"), contents)?;
+    }
+    if let Some(language) = language {
+        write("language", &format!("{language}\n"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+    use crate::scenario::Scenario;
+
+    const BASE: &str = "class A {\n    void f() {\n    }\n\n    void g() {\n    }\n}\n";
+    const LEFT: &str =
+        "class A {\n    void f() {\n        f1();\n    }\n\n    void g() {\n    }\n}\n";
+    const RIGHT: &str =
+        "class A {\n    void f() {\n    }\n\n    void g() {\n        g1();\n    }\n}\n";
+    const MERGED: &str = "class A {\n    void f() {\n        f1();\n    }\n\n    void g() {\n        g1();\n    }\n}\n";
+
+    fn revisions(resolution: Option<&str>) -> Revisions {
+        Revisions {
+            file_name: "A.java".to_owned(),
+            base: BASE.to_owned(),
+            left: LEFT.to_owned(),
+            right: RIGHT.to_owned(),
+            resolution: resolution.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn export_working_test_case() {
+        let examples = tempfile::tempdir().expect("failed to create the temp dir");
+        let dest = examples.path().join("java/working/my_case");
+        export(revisions(None), &dest, None).expect("failed to export the test case");
+
+        let scenario = Scenario::load(&dest).expect("failed to load the exported test case");
+        assert_eq!(scenario.suffix, ".java");
+        assert_eq!(scenario.left, LEFT);
+        assert_eq!(scenario.expected("Expected").as_deref(), Some(MERGED));
+        assert_eq!(scenario.expected("ExpectedIdeally"), None);
+        assert_eq!(scenario.language, None);
+
+        // existing test cases are not overwritten
+        assert!(export(revisions(None), &dest, None).is_err());
+    }
+
+    #[test]
+    fn export_failing_test_case() {
+        let examples = tempfile::tempdir().expect("failed to create the temp dir");
+        let ideal = MERGED.replace("f1();", "f2();");
+        let dest = examples.path().join("java/failing/my_case");
+        export(revisions(Some(&ideal)), &dest, Some("Java"))
+            .expect("failed to export the test case");
+
+        let scenario = Scenario::load(&dest).expect("failed to load the exported test case");
+        assert_eq!(
+            scenario.expected("ExpectedCurrently").as_deref(),
+            Some(MERGED)
+        );
+        assert_eq!(scenario.expected("ExpectedIdeally"), Some(ideal));
+        assert_eq!(scenario.expected("Expected"), None);
+        assert_eq!(scenario.language, Some("Java"));
+
+        // the resolution is already reproduced
+        let dest = examples.path().join("java/failing/reproduced");
+        assert!(export(revisions(Some(MERGED)), &dest, None).is_err());
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn revisions_from_attempt() {
+        let cache_dir = tempfile::tempdir().expect("failed to create the temp dir");
+        let attempt_dir = cache_dir.path().join("A.java_abcd1234");
+        fs::create_dir(&attempt_dir).unwrap();
+        for (name, contents) in [("Base", BASE), ("Left", LEFT), ("Right", RIGHT)] {
+            fs::write(attempt_dir.join(format!("{name}.java")), contents).unwrap();
+        }
+        let cache = AttemptsCache::new(Some(cache_dir.path()), None).unwrap();
+
+        let revisions =
+            Revisions::from_attempt(&cache, "A.java_abcd1234").expect("failed to read the attempt");
+        assert_eq!(revisions.file_name, "A.java");
+        assert_eq!(revisions.right, RIGHT);
+        assert_eq!(revisions.resolution, None);
+    }
+
+    #[test]
+    fn revisions_from_report() {
+        let dir = tempfile::tempdir().expect("failed to create the temp dir");
+        let archive = dir.path().join("mergiraf_report_abcd1234.zip");
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        for (name, contents) in [
+            ("Base.java", BASE),
+            ("Left.java", LEFT),
+            ("Right.java", RIGHT),
+            ("Result.java", MERGED),
+            ("filename.txt", "A.java"),
+        ] {
+            zip.start_file(
+                format!("mergiraf_report_abcd1234/{name}"),
+                SimpleFileOptions::default(),
+            )
+            .unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let revisions = Revisions::from_report(&archive).expect("failed to read the archive");
+        assert_eq!(revisions.file_name, "A.java");
+        assert_eq!(revisions.base, BASE);
+        assert_eq!(revisions.resolution, None);
+    }
+
+    #[test]
+    fn revisions_from_merge_commit() {
+        let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
+        let repo = repo_dir.path();
+        let run = |args: &[&str]| {
+            let mut all_args = vec!["-c", "user.name=Test", "-c", "user.email=test@example.com"];
+            all_args.extend(args);
+            git(repo, &all_args).expect("git command failed");
+        };
+        let commit = |contents: &str, message: &str| {
+            fs::write(repo.join("A.java"), contents).unwrap();
+            run(&["add", "A.java"]);
+            run(&["commit", "-q", "-m", message]);
+        };
+
+        run(&["init", "-q", "-b", "main"]);
+        commit(BASE, "base");
+        run(&["checkout", "-q", "-b", "feature"]);
+        commit(LEFT, "left");
+        run(&["checkout", "-q", "main"]);
+        commit(RIGHT, "right");
+        run(&["merge", "-q", "--no-edit", "feature"]);
+
+        let revisions = Revisions::from_merge_commit(repo, "HEAD", Path::new("A.java"))
+            .expect("failed to read the merge commit");
+        // the first parent (`main`) is the left revision
+        assert_eq!(revisions.base, BASE);
+        assert_eq!(revisions.left, RIGHT);
+        assert_eq!(revisions.right, LEFT);
+        assert_eq!(revisions.resolution.as_deref(), Some(MERGED));
+
+        assert!(Revisions::from_merge_commit(repo, "HEAD~1", Path::new("A.java")).is_err());
+
+        // the same merge, from the branches before it
+        let revisions = Revisions::from_commit_range(repo, "HEAD~1...feature", Path::new("A.java"))
+            .expect("failed to read the commit range");
+        assert_eq!(revisions.base, BASE);
+        assert_eq!(revisions.left, RIGHT);
+        assert_eq!(revisions.right, LEFT);
+        assert_eq!(revisions.resolution, None);
+        assert_eq!(
+            Revisions::from_commit_range(repo, "..feature", Path::new("A.java"))
+                .expect("failed to read the commit range")
+                .left,
+            MERGED
+        );
+
+        assert!(Revisions::from_commit_range(repo, "feature", Path::new("A.java")).is_err());
+    }
+}
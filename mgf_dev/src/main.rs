@@ -1,19 +1,34 @@
+mod compare_strategies;
+mod export;
+mod mine;
+mod scenario;
+mod strategies;
+
 use std::{
     borrow::Cow,
     fs,
     path::{Path, PathBuf},
     process::exit,
+    time::Duration,
 };
 
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Parser, Subcommand};
 use mergiraf::{
     ast::AstNode,
+    attempts::AttemptsCache,
     lang_profile::LangProfile,
     // XXX: move the uses to lib to avoid making these public?
     newline::normalize_to_lf,
 };
 use typed_arena::Arena;
 
+use crate::{
+    compare_strategies::{OutputFormat, compare_strategies, print_comparisons},
+    export::{Revisions, export},
+    mine::{mine, print_replays},
+    strategies::DEFAULT_TIMEOUT,
+};
+
 /// Dev helper for contributing to Mergiraf
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -44,6 +59,74 @@ enum Command {
         #[arg(short, long)]
         commutative: bool,
     },
+    /// Merge test cases with all strategies (line-based, fast, structured and each
+    /// semistructured algorithm), reporting their conflicts, running time and whether
+    /// they reproduce the expected output
+    CompareStrategies {
+        /// Directory containing the test cases, such as `examples/java`. Each directory
+        /// with a `Base` file is a test case.
+        dir: PathBuf,
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+        /// Command used by the `external` semistructured algorithm, which is skipped if not provided
+        #[arg(long)]
+        merge_command: Option<String>,
+        /// Maximum number of milliseconds a structured merge can take, after which the
+        /// line-based merge is kept instead. Set to 0 to disable this limit.
+        #[arg(long, default_value_t = DEFAULT_TIMEOUT.as_millis() as u64)]
+        timeout: u64,
+    },
+    /// Replay the merges of files modified on both sides of the merge commits of a repository
+    /// with all strategies, reporting how often they reproduce the committed resolution
+    Mine {
+        /// Path to the git repository
+        repo: PathBuf,
+        /// Revision from which merge commits are looked up
+        #[arg(long, default_value = "HEAD")]
+        rev: String,
+        /// Maximum number of merge commits to replay, starting from the most recent ones
+        #[arg(long)]
+        max_merges: Option<usize>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+        /// Command used by the `external` semistructured algorithm, which is skipped if not provided
+        #[arg(long)]
+        merge_command: Option<String>,
+        /// Maximum number of milliseconds a structured merge can take, after which the
+        /// line-based merge is kept instead. Set to 0 to disable this limit.
+        #[arg(long, default_value_t = DEFAULT_TIMEOUT.as_millis() as u64)]
+        timeout: u64,
+    },
+    /// Export a merge scenario as a test case, with the resolution of the merge (if known)
+    /// or the current output of Mergiraf as expected output
+    #[command(group(ArgGroup::new("source").required(true)))]
+    Export {
+        /// Directory of the test case to create, such as `examples/java/working/my_case`.
+        /// Test cases in a `failing` directory get `ExpectedCurrently` and `ExpectedIdeally`
+        /// files instead of `Expected`.
+        dest: PathBuf,
+        /// Merge commit from which to export a file, as listed by `mine`
+        #[arg(long, group = "source", requires = "path")]
+        merge_commit: Option<String>,
+        /// Range of commits `LEFT...RIGHT` to merge, exporting a file from both ends of the
+        /// range and their merge base
+        #[arg(long, group = "source", requires = "path")]
+        commit_range: Option<String>,
+        /// Path of the file to export from the merge commit or the commit range
+        #[arg(long, conflicts_with_all = ["attempt", "report"])]
+        path: Option<PathBuf>,
+        /// Path to the git repository containing the merge commit or the commit range
+        #[arg(long, default_value = ".")]
+        repo: PathBuf,
+        /// Id of a merge attempt, as provided by `mergiraf merge`
+        #[arg(long, group = "source")]
+        attempt: Option<String>,
+        /// Archive created by `mergiraf report`
+        #[arg(long, group = "source")]
+        report: Option<PathBuf>,
+    },
 }
 
 fn main() {
@@ -74,13 +157,24 @@ fn real_main(args: &CliArgs) -> Result<i32, String> {
         Ok(contents)
     };
 
+    let language_name = || {
+        args.language
+            .as_deref()
+            .map(|name| {
+                LangProfile::find_by_name(name)
+                    .map(|lang_profile| lang_profile.name)
+                    .ok_or_else(|| format!("Unknown language: {name}"))
+            })
+            .transpose()
+    };
+
     let exit_code = match &args.command {
         Command::Parse { path } => {
             let lang_profile = lang_profile(path)?;
 
             let contents = contents(path)?;
 
-            let tree = AstNode::parse(&contents, lang_profile, &arena, &ref_arena)
+            let tree = AstNode::parse(&contents, lang_profile, &arena, &ref_arena, None)
                 .map_err(|err| format!("File has parse errors: {err}"))?;
 
             print!("{}", tree.ascii_tree());
@@ -95,12 +189,12 @@ fn real_main(args: &CliArgs) -> Result<i32, String> {
 
             let contents_first = contents(first)?;
 
-            let tree_first = AstNode::parse(&contents_first, lang_profile, &arena, &ref_arena)
+            let tree_first = AstNode::parse(&contents_first, lang_profile, &arena, &ref_arena, None)
                 .map_err(|err| format!("File has parse errors: {err}"))?;
 
             let contents_second = contents(second)?;
 
-            let tree_second = AstNode::parse(&contents_second, lang_profile, &arena, &ref_arena)
+            let tree_second = AstNode::parse(&contents_second, lang_profile, &arena, &ref_arena, None)
                 .map_err(|err| format!("File has parse errors: {err}"))?;
 
             let first_root = tree_first;
@@ -114,6 +208,63 @@ fn real_main(args: &CliArgs) -> Result<i32, String> {
                 1
             }
         }
+        Command::CompareStrategies {
+            dir,
+            format,
+            merge_command,
+            timeout,
+        } => {
+            let language = language_name()?;
+            let comparisons = compare_strategies(
+                dir,
+                language,
+                merge_command.as_deref(),
+                Duration::from_millis(*timeout),
+            )?;
+            print_comparisons(&comparisons, *format);
+            0
+        }
+        Command::Mine {
+            repo,
+            rev,
+            max_merges,
+            format,
+            merge_command,
+            timeout,
+        } => {
+            let replays = mine(
+                repo,
+                rev,
+                *max_merges,
+                merge_command.as_deref(),
+                Duration::from_millis(*timeout),
+            )?;
+            print_replays(&replays, *format);
+            0
+        }
+        Command::Export {
+            dest,
+            merge_commit,
+            commit_range,
+            path,
+            repo,
+            attempt,
+            report,
+        } => {
+            let revisions = if let (Some(merge_commit), Some(path)) = (merge_commit, path) {
+                Revisions::from_merge_commit(repo, merge_commit, path)?
+            } else if let (Some(commit_range), Some(path)) = (commit_range, path) {
+                Revisions::from_commit_range(repo, commit_range, path)?
+            } else if let Some(attempt) = attempt {
+                Revisions::from_attempt(&AttemptsCache::new(None, None)?, attempt)?
+            } else if let Some(report) = report {
+                Revisions::from_report(report)?
+            } else {
+                unreachable!("a source is required by the argument parser")
+            };
+            export(revisions, dest, language_name()?)?;
+            0
+        }
     };
     Ok(exit_code)
 }
@@ -194,6 +345,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn compare_strategies_as_csv() {
+        assert_eq!(
+            real_main(&CliArgs::parse_from([
+                "mgf_dev",
+                "compare-strategies",
+                "--format=csv",
+                "../examples/java/working/demo",
+            ])),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn timeout_flag() {
+        let timeout = |args: &[&str]| {
+            let CliArgs {
+                command: Command::Mine { timeout, .. },
+                ..
+            } = CliArgs::parse_from(["mgf_dev", "mine"].iter().chain(args).chain(&["repo"]))
+            else {
+                unreachable!("`mgf_dev mine` should invoke the `Mine` subcommand")
+            };
+            timeout
+        };
+        assert_eq!(timeout(&[]), 10_000);
+        assert_eq!(timeout(&["--timeout=500"]), 500);
+        assert_eq!(timeout(&["--timeout=0"]), 0);
+    }
+
     #[test]
     fn set_language() {
         let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
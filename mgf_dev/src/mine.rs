@@ -0,0 +1,293 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use mergiraf::{
+    git::{read_content_from_commit, read_content_from_commits},
+    lang_profile::LangProfile,
+    newline::normalize_to_lf,
+};
+
+use crate::{
+    compare_strategies::{OutputFormat, csv_field},
+    strategies::{MergeOutcome, Strategy},
+};
+
+/// How the output of a strategy compares to the resolution committed in the repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The committed resolution was reproduced exactly
+    Reproduced,
+    /// The merge has conflicts
+    Conflict,
+    /// The merge has no conflicts, but differs from the committed resolution
+    Differs,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Reproduced => write!(f, "reproduced"),
+            Self::Conflict => write!(f, "conflict"),
+            Self::Differs => write!(f, "differs"),
+        }
+    }
+}
+
+/// The merge of a file, replayed with a strategy
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// The merge commit in which the file was merged
+    pub merge_commit: String,
+    /// The path of the file in the repository
+    pub path: PathBuf,
+    pub strategy: Strategy,
+    pub outcome: MergeOutcome,
+    pub verdict: Verdict,
+}
+
+/// Replays the merges of files modified on both sides of the merge commits reachable from
+/// `rev` (at most `max_merges` of them, most recent first), with all strategies.
+/// Only files in a supported language are considered.
+pub fn mine(
+    repo_dir: &Path,
+    rev: &str,
+    max_merges: Option<usize>,
+    merge_command: Option<&str>,
+    timeout: Duration,
+) -> Result<Vec<Replay>, String> {
+    let strategies = Strategy::all(merge_command);
+    let mut rev_list_args = vec![
+        "rev-list".to_owned(),
+        "--merges".to_owned(),
+        "--parents".to_owned(),
+    ];
+    if let Some(max_merges) = max_merges {
+        rev_list_args.push(format!("--max-count={max_merges}"));
+    }
+    rev_list_args.push(rev.to_owned());
+    let merges = git(repo_dir, &rev_list_args)?;
+
+    let mut replays = Vec::new();
+    for line in merges.lines() {
+        // octopus merges are skipped
+        let [merge_commit, left_commit, right_commit] = line.split(' ').collect::<Vec<_>>()[..]
+        else {
+            continue;
+        };
+        let Ok(base_commit) = git(repo_dir, &["merge-base", left_commit, right_commit]) else {
+            continue;
+        };
+        let base_commit = base_commit.trim();
+
+        for path in files_modified_on_both_sides(repo_dir, base_commit, left_commit, right_commit)?
+        {
+            if LangProfile::find_by_filename_or_name(&path, None).is_err() {
+                continue;
+            }
+            let (Some((base, left, right)), Some(resolution)) = (
+                read_content_from_commits(
+                    repo_dir,
+                    (base_commit, left_commit, right_commit),
+                    &path,
+                ),
+                read_content_from_commit(repo_dir, merge_commit, &path),
+            ) else {
+                continue;
+            };
+            let (base, left, right) = (
+                normalize_to_lf(base),
+                normalize_to_lf(left),
+                normalize_to_lf(right),
+            );
+            let resolution = normalize_to_lf(resolution);
+
+            for strategy in &strategies {
+                let outcome =
+                    strategy.merge(&base, &left, &right, &path, None, merge_command, timeout);
+                let verdict = if outcome.contents == resolution {
+                    Verdict::Reproduced
+                } else if outcome.conflict_count > 0 {
+                    Verdict::Conflict
+                } else {
+                    Verdict::Differs
+                };
+                replays.push(Replay {
+                    merge_commit: merge_commit.to_owned(),
+                    path: path.clone(),
+                    strategy: *strategy,
+                    outcome,
+                    verdict,
+                });
+            }
+        }
+    }
+    Ok(replays)
+}
+
+/// The files modified (and not added, deleted or renamed) on both sides of a merge
+fn files_modified_on_both_sides(
+    repo_dir: &Path,
+    base: &str,
+    left: &str,
+    right: &str,
+) -> Result<Vec<PathBuf>, String> {
+    let modified = |side: &str| {
+        git(
+            repo_dir,
+            &[
+                "diff",
+                "--name-only",
+                "-z",
+                "--no-renames",
+                "--diff-filter=M",
+                base,
+                side,
+            ],
+        )
+    };
+    // the paths are separated by NUL characters, as git would otherwise quote unusual ones
+    let modified_on_left = modified(left)?;
+    let modified_on_right = modified(right)?;
+    let modified_on_right: HashSet<&str> = modified_on_right.split('\0').collect();
+    Ok(modified_on_left
+        .split('\0')
+        .filter(|path| !path.is_empty() && modified_on_right.contains(path))
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Runs a git command in a repository, returning its output
+pub fn git(repo_dir: &Path, args: &[impl AsRef<str>]) -> Result<String, String> {
+    let args: Vec<_> = args.iter().map(AsRef::as_ref).collect();
+    let output = Command::new("git")
+        .args(&args)
+        .current_dir(repo_dir)
+        .output()
+        .map_err(|err| format!("could not run git: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(|err| err.to_string())
+}
+
+/// Prints the replays in the given format. As a table, only a summary for each strategy
+/// is printed, followed by the merges which differ silently from their resolution.
+pub fn print_replays(replays: &[Replay], format: OutputFormat) {
+    match format {
+        OutputFormat::Csv => {
+            println!("merge_commit,path,strategy,verdict,conflicts,mass,time_ms");
+            for replay in replays {
+                println!(
+                    "{},{},{},{},{},{},{:.1}",
+                    replay.merge_commit,
+                    csv_field(&replay.path.display().to_string()),
+                    replay.strategy,
+                    replay.verdict,
+                    replay.outcome.conflict_count,
+                    replay.outcome.conflict_mass,
+                    replay.outcome.duration.as_secs_f64() * 1000.
+                );
+            }
+        }
+        OutputFormat::Table => {
+            let mut strategies: Vec<Strategy> = Vec::new();
+            for replay in replays {
+                if !strategies.contains(&replay.strategy) {
+                    strategies.push(replay.strategy);
+                }
+            }
+            for strategy in strategies {
+                let count = |verdict| {
+                    replays
+                        .iter()
+                        .filter(|replay| replay.strategy == strategy && replay.verdict == verdict)
+                        .count()
+                };
+                println!(
+                    "{strategy}: {} reproduced, {} with conflicts, {} differing silently",
+                    count(Verdict::Reproduced),
+                    count(Verdict::Conflict),
+                    count(Verdict::Differs)
+                );
+            }
+
+            let silent_differences: Vec<_> = replays
+                .iter()
+                .filter(|replay| replay.verdict == Verdict::Differs)
+                .collect();
+            if !silent_differences.is_empty() {
+                println!("\nmerges differing silently from their resolution:");
+                for replay in silent_differences {
+                    println!(
+                        "{} {} ({})",
+                        replay.merge_commit,
+                        replay.path.display(),
+                        replay.strategy
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::strategies::DEFAULT_TIMEOUT;
+
+    #[test]
+    fn replay_merge_commits() {
+        let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
+        let repo = repo_dir.path();
+        let run = |args: &[&str]| {
+            let mut all_args = vec!["-c", "user.name=Test", "-c", "user.email=test@example.com"];
+            all_args.extend(args);
+            git(repo, &all_args).expect("git command failed");
+        };
+        // git quotes non-ASCII paths, unless they are separated by NUL characters
+        let path = "Ä.java";
+        let commit = |contents: &str, message: &str| {
+            fs::write(repo.join(path), contents).unwrap();
+            run(&["add", path]);
+            run(&["commit", "-q", "-m", message]);
+        };
+
+        run(&["init", "-q", "-b", "main"]);
+        commit(
+            "class A {\n    void f() {\n    }\n\n    void g() {\n    }\n}\n",
+            "base",
+        );
+        run(&["checkout", "-q", "-b", "feature"]);
+        commit(
+            "class A {\n    void f() {\n        f1();\n    }\n\n    void g() {\n    }\n}\n",
+            "left",
+        );
+        run(&["checkout", "-q", "main"]);
+        commit(
+            "class A {\n    void f() {\n    }\n\n    void g() {\n        g1();\n    }\n}\n",
+            "right",
+        );
+        run(&["merge", "-q", "--no-edit", "feature"]);
+
+        let replays =
+            mine(repo, "HEAD", None, None, DEFAULT_TIMEOUT).expect("failed to mine the repository");
+        assert_eq!(replays.len(), Strategy::all(None).len());
+        assert!(replays.iter().all(|replay| replay.path == Path::new(path)));
+        assert!(
+            replays
+                .iter()
+                .all(|replay| replay.verdict == Verdict::Reproduced)
+        );
+    }
+}
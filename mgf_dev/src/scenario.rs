@@ -0,0 +1,93 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use mergiraf::{lang_profile::LangProfile, newline::normalize_to_lf};
+
+/// A merge scenario, laid out like the test cases in `examples/`: a directory with `Base`,
+/// `Left` and `Right` revisions sharing the same suffix, optional expected outputs and an
+/// optional `language` file overriding the language detection.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    /// The directory the scenario was read from
+    pub dir: PathBuf,
+    /// The suffix of the revision files, including the period (if any)
+    pub suffix: String,
+    pub base: String,
+    pub left: String,
+    pub right: String,
+    /// The name of the language to use, if overridden
+    pub language: Option<&'static str>,
+}
+
+impl Scenario {
+    /// Reads a scenario from a directory
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let suffix = detect_suffix(dir)?
+            .ok_or_else(|| format!("No Base file found in {}", dir.display()))?;
+        let read = |revision: &str| -> Result<String, String> {
+            let path = dir.join(format!("{revision}{suffix}"));
+            let contents = fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read {}: {err}", path.display()))?;
+            Ok(normalize_to_lf(contents).into_owned())
+        };
+        let language = match fs::read_to_string(dir.join("language")) {
+            Ok(contents) => {
+                let name = contents.trim();
+                let lang_profile = LangProfile::find_by_name(name)
+                    .ok_or_else(|| format!("Invalid language in {}: '{name}'", dir.display()))?;
+                Some(lang_profile.name)
+            }
+            Err(_) => None,
+        };
+        Ok(Self {
+            dir: dir.to_owned(),
+            base: read("Base")?,
+            left: read("Left")?,
+            right: read("Right")?,
+            suffix,
+            language,
+        })
+    }
+
+    /// The path of the base revision, which determines the language of the scenario
+    pub fn base_path(&self) -> PathBuf {
+        self.dir.join(format!("Base{}", self.suffix))
+    }
+
+    /// Reads one of the expected outputs of the scenario (such as `Expected` or
+    /// `ExpectedIdeally`), if it exists
+    pub fn expected(&self, name: &str) -> Option<String> {
+        fs::read_to_string(self.dir.join(format!("{name}{}", self.suffix))).ok()
+    }
+}
+
+/// Detects the suffix (including period) of the `Base` file of a directory, if there is one
+fn detect_suffix(dir: &Path) -> Result<Option<String>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|err| format!("Could not list {}: {err}", dir.display()))?;
+    Ok(entries.filter_map(Result::ok).find_map(|entry| {
+        let file_name = entry.file_name().into_string().ok()?;
+        file_name.strip_prefix("Base").map(String::from)
+    }))
+}
+
+/// Recursively finds the directories containing merge scenarios, in alphabetical order
+pub fn find_scenarios(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut scenarios = Vec::new();
+    if detect_suffix(dir)?.is_some() {
+        scenarios.push(dir.to_owned());
+    }
+    let mut subdirs: Vec<_> = fs::read_dir(dir)
+        .map_err(|err| format!("Could not list {}: {err}", dir.display()))?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    subdirs.sort();
+    for subdir in subdirs {
+        scenarios.extend(find_scenarios(&subdir)?);
+    }
+    Ok(scenarios)
+}
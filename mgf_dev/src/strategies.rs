@@ -0,0 +1,126 @@
+use std::{
+    fmt::Display,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use mergiraf::{
+    TextualMergeStrategy,
+    line_based::line_based_merge,
+    line_merge_and_structured_resolution,
+    settings::{DisplaySettings, SemistructuredSettings},
+};
+
+/// The timeout of structured merges, the same as in `mergiraf merge`
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A way of merging three revisions of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Git-style line-based merge
+    LineBased,
+    /// Line-based merge followed by a structured resolution of its conflicts, like `mergiraf merge --fast`
+    Fast,
+    /// Like [`Strategy::Fast`], falling back on a fully structured merge if conflicts remain
+    Structured,
+    /// Like [`Strategy::Structured`], in semistructured mode with the given algorithm
+    Semistructured(TextualMergeStrategy),
+}
+
+/// The result of merging a scenario with a strategy
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    pub contents: String,
+    pub conflict_count: usize,
+    pub conflict_mass: usize,
+    pub duration: Duration,
+}
+
+impl Strategy {
+    /// All the strategies. The external semistructured algorithm is only included
+    /// if a command is supplied for it.
+    pub fn all(merge_command: Option<&str>) -> Vec<Self> {
+        let semistructured = TextualMergeStrategy::value_variants()
+            .iter()
+            .filter(|strategy| {
+                merge_command.is_some() || **strategy != TextualMergeStrategy::External
+            })
+            .map(|strategy| Self::Semistructured(*strategy));
+        [Self::LineBased, Self::Fast, Self::Structured]
+            .into_iter()
+            .chain(semistructured)
+            .collect()
+    }
+
+    /// Merges three revisions of a file, whose language is determined by the name of the
+    /// base revision or the supplied language name. Structured merges which take longer
+    /// than the timeout fall back on the line-based merge, unless it is zero.
+    #[allow(clippy::too_many_arguments)]
+    pub fn merge(
+        self,
+        base: &str,
+        left: &str,
+        right: &str,
+        fname_base: &Path,
+        language: Option<&str>,
+        merge_command: Option<&str>,
+        timeout: Duration,
+    ) -> MergeOutcome {
+        let settings = DisplaySettings {
+            compact: Some(false),
+            ..Default::default()
+        };
+        let semistructured = match self {
+            Self::Semistructured(strategy) => Some(SemistructuredSettings {
+                merge_command: merge_command.map(Arc::from),
+                ..SemistructuredSettings::new(strategy)
+            }),
+            _ => None,
+        };
+
+        let start = Instant::now();
+        let result = match self {
+            Self::LineBased => line_based_merge(base, left, right, &settings),
+            Self::Fast | Self::Structured | Self::Semistructured(_) => {
+                line_merge_and_structured_resolution(
+                    base,
+                    left,
+                    right,
+                    fname_base,
+                    settings,
+                    self != Self::Fast,
+                    None,
+                    None,
+                    timeout,
+                    language,
+                    None,
+                    semistructured,
+                )
+            }
+        };
+        MergeOutcome {
+            contents: result.contents,
+            conflict_count: result.conflict_count,
+            conflict_mass: result.conflict_mass,
+            duration: start.elapsed(),
+        }
+    }
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LineBased => write!(f, "line_based"),
+            Self::Fast => write!(f, "fast"),
+            Self::Structured => write!(f, "structured"),
+            Self::Semistructured(strategy) => {
+                let name = strategy
+                    .to_possible_value()
+                    .expect("no strategy is skipped");
+                write!(f, "semistructured:{}", name.get_name())
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@ use std::{
     hash::{Hash, Hasher},
     iter::zip,
     ops::Range,
+    sync::OnceLock,
 };
 #[cfg(test)]
 use std::{ops::Index, slice::SliceIndex};
@@ -13,9 +14,9 @@ use std::{ops::Index, slice::SliceIndex};
 use either::Either;
 use itertools::Itertools;
 use nu_ansi_term::Color;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use tree_sitter::{
-    Parser, Point, Query, QueryCursor, Range as TSRange, StreamingIterator, Tree, TreeCursor,
+    InputEdit, Parser, Point, QueryCursor, Range as TSRange, StreamingIterator, Tree, TreeCursor,
 };
 use typed_arena::Arena;
 
@@ -23,6 +24,7 @@ use crate::{
     debug,
     lang_profile::{CommutativeParent, LangProfile, ParentType},
     signature::{Signature, SignatureDefinition},
+    truncation_policy::TruncationPolicy,
 };
 
 /// A node in a syntax tree.
@@ -60,11 +62,19 @@ pub struct AstNode<'a> {
     parent: UnsafeCell<Option<&'a Self>>,
     /// The commutative merging settings associated with this node.
     commutative_parent: Option<&'a CommutativeParent>,
+    /// Whether this node is a truncation point (only in semistructured mode)
+    truncation_point: bool,
+    /// Whether this node was turned into a leaf because it is a truncation point
+    /// (depending on the truncation policy, not all of them are truncated)
+    truncated: bool,
     /// As the DFS of a child is a subslice of the DFS of its parent, we compute the entire DFS of
     /// the root once and slice all child DFS into this slice.
     /// This is computed right after construction and then never written to again.
     /// On nodes that have been truncated (which is rare) this will be `None`.
     dfs: UnsafeCell<Option<&'a [&'a Self]>>,
+    /// The tree-sitter tree this node was parsed from, kept on the roots of parses so that
+    /// the structure of truncated nodes can be recovered without parsing the file again.
+    ts_tree: OnceLock<Tree>,
     /// The language this node was parsed from
     pub lang_profile: &'a LangProfile,
 }
@@ -76,10 +86,17 @@ impl<'a> AstNode<'a> {
         lang_profile: &'a LangProfile,
         arena: &'a Arena<Self>,
         ref_arena: &'a Arena<&'a Self>,
-        semistructured: bool,
+        truncation_policy: Option<TruncationPolicy>,
     ) -> Result<&'a Self, String> {
         let mut next_node_id = 1;
-        let root = Self::parse_root(source, None, lang_profile, arena, &mut next_node_id, semistructured)?;
+        let root = Self::parse_root(
+            source,
+            None,
+            lang_profile,
+            arena,
+            &mut next_node_id,
+            truncation_policy,
+        )?;
         root.internal_precompute_root_dfs(ref_arena);
         Ok(root)
     }
@@ -93,7 +110,7 @@ impl<'a> AstNode<'a> {
         lang_profile: &'a LangProfile,
         arena: &'a Arena<Self>,
         next_node_id: &mut usize,
-        semistructured: bool,
+        truncation_policy: Option<TruncationPolicy>,
     ) -> Result<&'a Self, String> {
         let mut parser = Parser::new();
         parser
@@ -107,15 +124,21 @@ impl<'a> AstNode<'a> {
         let tree = parser
             .parse(source, None)
             .expect("Parsing source code failed");
-        let node_id_to_injection_lang = Self::locate_injections(&tree, source, lang_profile);
+        let node_id_to_injection_lang =
+            Self::locate_injections(&tree.root_node(), source, lang_profile);
         let node_id_to_commutative_parent =
-            Self::locate_commutative_parents_by_query(&tree, source, lang_profile);
+            Self::locate_commutative_parents_by_query(&tree.root_node(), source, lang_profile);
+        let truncation_point_ids = if truncation_policy.is_some() {
+            Self::locate_truncation_points_by_query(&tree, source, lang_profile)
+        } else {
+            FxHashSet::default()
+        };
         let range_for_root = if let Some(range) = range {
             range.start_byte..range.end_byte
         } else {
             0..source.len()
         };
-        Self::internal_new(
+        let root = Self::internal_new(
             &mut tree.walk(),
             source,
             lang_profile,
@@ -123,15 +146,76 @@ impl<'a> AstNode<'a> {
             next_node_id,
             &node_id_to_injection_lang,
             &node_id_to_commutative_parent,
+            &truncation_point_ids,
             Some(range_for_root),
-            semistructured,
+            truncation_policy,
+        )?;
+        let _ = root.ts_tree.set(tree);
+        Ok(root)
+    }
+
+    /// Parses the supplied source, obtained from the source of this root by replacing the
+    /// given range, and returns the node of the supplied kind spanning exactly the replacement
+    /// (without truncation). The tree-sitter tree kept from the parse of this root is edited
+    /// and reused, so that only the replaced region is re-parsed and converted to nodes.
+    pub(crate) fn parse_replaced_range<'b>(
+        &self,
+        replaced: Range<usize>,
+        source: &'b str,
+        grammar_name: &str,
+        arena: &'b Arena<AstNode<'b>>,
+        ref_arena: &'b Arena<&'b AstNode<'b>>,
+    ) -> Option<&'b AstNode<'b>>
+    where
+        'a: 'b,
+    {
+        let point_at = |source: &str, offset: usize| {
+            let before = &source[..offset];
+            let row = before.matches('\n').count();
+            let column = offset - before.rfind('\n').map_or(0, |newline| newline + 1);
+            Point::new(row, column)
+        };
+        let new_end = source.len() - (self.source.len() - replaced.end);
+        let mut old_tree = self.ts_tree.get()?.clone();
+        old_tree.edit(&InputEdit {
+            start_byte: replaced.start,
+            old_end_byte: replaced.end,
+            new_end_byte: new_end,
+            start_position: point_at(self.source, replaced.start),
+            old_end_position: point_at(self.source, replaced.end),
+            new_end_position: point_at(source, new_end),
+        });
+        let mut parser = Parser::new();
+        parser.set_language(&self.lang_profile.language).ok()?;
+        let tree = parser.parse(source, Some(&old_tree))?;
+        let node = (tree.root_node())
+            .descendant_for_byte_range(replaced.start, new_end)
+            .into_iter()
+            .flat_map(|node| std::iter::successors(Some(node), tree_sitter::Node::parent))
+            .take_while(|node| node.byte_range() == (replaced.start..new_end))
+            .find(|node| node.grammar_name() == grammar_name)?;
+
+        let reparsed = AstNode::internal_new(
+            &mut node.walk(),
+            source,
+            self.lang_profile,
+            arena,
+            &mut 1,
+            &AstNode::locate_injections(&node, source, self.lang_profile),
+            &AstNode::locate_commutative_parents_by_query(&node, source, self.lang_profile),
+            &FxHashSet::default(),
+            None,
+            None,
         )
+        .ok()?;
+        reparsed.internal_precompute_root_dfs(ref_arena);
+        Some(reparsed)
     }
 
-    /// Locate all nodes which are marked as commutative via a tree-sitter query.
+    /// Locate all nodes of a subtree which are marked as commutative via a tree-sitter query.
     /// This returns a map from node ids to the their commutative parent definition.
     fn locate_commutative_parents_by_query<'b>(
-        tree: &Tree,
+        subtree: &tree_sitter::Node,
         source: &'a str,
         lang_profile: &'b LangProfile,
     ) -> FxHashMap<usize, &'b CommutativeParent> {
@@ -140,13 +224,14 @@ impl<'a> AstNode<'a> {
         for commutative_parent in &lang_profile.commutative_parents {
             if let ParentType::ByQuery(query_str) = commutative_parent.parent_type() {
                 // Execute this query over the tree
-                let query = Query::new(&lang_profile.language, query_str)
+                let query = lang_profile
+                    .query(query_str)
                     .expect("Invalid commutative parent query");
                 let commutative_capture_index = query
                     .capture_index_for_name("commutative")
                     .expect("Commutative parent query without a '@commutative' capture");
                 let mut cursor = QueryCursor::new();
-                let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+                let matches = cursor.matches(&query, *subtree, source.as_bytes());
                 // For each match, mark the captured node(s) as commutative
                 matches.for_each(|m| {
                     node_id_to_commutative_parent.extend(
@@ -159,17 +244,46 @@ impl<'a> AstNode<'a> {
         node_id_to_commutative_parent
     }
 
-    /// Locate nodes which need re-parsing in a different language
-    fn locate_injections(
+    /// Locate all nodes which are marked as truncation points via a tree-sitter query.
+    /// This returns the set of their node ids.
+    fn locate_truncation_points_by_query(
         tree: &Tree,
         source: &'a str,
+        lang_profile: &LangProfile,
+    ) -> FxHashSet<usize> {
+        let mut truncation_point_ids = FxHashSet::default();
+        for query_str in &lang_profile.truncation_queries {
+            let query = lang_profile
+                .query(query_str)
+                .expect("Invalid truncation query");
+            let truncated_capture_index = query
+                .capture_index_for_name("truncated")
+                .expect("Truncation query without a '@truncated' capture");
+            let mut cursor = QueryCursor::new();
+            let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+            matches.for_each(|m| {
+                truncation_point_ids.extend(
+                    m.nodes_for_capture_index(truncated_capture_index)
+                        .map(|node| node.id()),
+                );
+            });
+        }
+        truncation_point_ids
+    }
+
+    /// Locate nodes of a subtree which need re-parsing in a different language
+    fn locate_injections(
+        subtree: &tree_sitter::Node,
+        source: &'a str,
         lang_profile: &'a LangProfile,
     ) -> FxHashMap<usize, &'static LangProfile> {
         let Some(query_str) = lang_profile.injections else {
             return FxHashMap::default();
         };
         let mut node_id_to_injection_lang = FxHashMap::default();
-        let query = Query::new(&lang_profile.language, query_str).expect("Invalid injection query");
+        let query = lang_profile
+            .query(query_str)
+            .expect("Invalid injection query");
         let content_capture_index = query
             .capture_index_for_name("injection.content")
             .expect("Injection query without an injection.content capture");
@@ -177,7 +291,7 @@ impl<'a> AstNode<'a> {
         // or statically defined as a property (fixed by the injection query), in which case the capture below won't be defined.
         let language_capture_index = query.capture_index_for_name("injection.language");
         let mut cursor = QueryCursor::new();
-        let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        let matches = cursor.matches(&query, *subtree, source.as_bytes());
         matches.for_each(|m| {
             let pattern_properties = query.property_settings(m.pattern_index);
             // first, check if the language is statically defined in this clause of the query as a property
@@ -218,14 +332,19 @@ impl<'a> AstNode<'a> {
         next_node_id: &mut usize,
         node_id_to_injection_lang: &FxHashMap<usize, &'static LangProfile>,
         node_id_to_commutative_parent: &FxHashMap<usize, &'a CommutativeParent>,
+        truncation_point_ids: &FxHashSet<usize>,
         range_for_root: Option<Range<usize>>,
-        semistructured: bool,
+        truncation_policy: Option<TruncationPolicy>,
     ) -> Result<&'a Self, String> {
         let field_name = cursor.field_name();
         let node = cursor.node();
         let grammar_name = node.grammar_name();
 
-        if semistructured && lang_profile.truncation_node_kinds.contains(grammar_name) {
+        let truncation_point = truncation_policy.is_some()
+            && (lang_profile.truncation_node_kinds.contains(grammar_name)
+                || truncation_point_ids.contains(&node.id()));
+        if truncation_point && truncation_policy.is_some_and(|policy| policy.should_truncate(&node))
+        {
             let range = node.byte_range();
             let local_source = &global_source[range.start..range.end];
 
@@ -247,7 +366,10 @@ impl<'a> AstNode<'a> {
                 descendant_count: 1,
                 parent: UnsafeCell::new(None),
                 commutative_parent: None,
+                truncation_point: true,
+                truncated: true,
                 dfs: UnsafeCell::new(None),
+                ts_tree: OnceLock::new(),
                 lang_profile,
             });
             *next_node_id += 1;
@@ -272,7 +394,7 @@ impl<'a> AstNode<'a> {
                 injection_lang,
                 arena,
                 next_node_id,
-                semistructured,
+                truncation_policy,
             ) {
                 children.push(injected_root);
                 last_child_end = injected_root.byte_range.end;
@@ -288,8 +410,9 @@ impl<'a> AstNode<'a> {
                     next_node_id,
                     node_id_to_injection_lang,
                     node_id_to_commutative_parent,
+                    truncation_point_ids,
                     None,
-                    semistructured,
+                    truncation_policy,
                 )?;
                 children.push(child);
                 if let Some(field_name) = cursor.field_name() {
@@ -377,7 +500,10 @@ impl<'a> AstNode<'a> {
                     // `@virtual_line@` isn't an actual grammar type, so it cannot be present in
                     // the grammar and thus can't have a commutative parent defined
                     commutative_parent: None,
+                    truncation_point: false,
+                    truncated: false,
                     dfs: UnsafeCell::new(None),
+                    ts_tree: OnceLock::new(),
                     lang_profile,
                 }));
                 *next_node_id += 1;
@@ -424,7 +550,10 @@ impl<'a> AstNode<'a> {
             descendant_count,
             parent: UnsafeCell::new(None),
             commutative_parent,
+            truncation_point,
+            truncated: false,
             dfs: UnsafeCell::new(None),
+            ts_tree: OnceLock::new(),
             lang_profile,
         });
         *next_node_id += 1;
@@ -694,6 +823,7 @@ impl<'a> AstNode<'a> {
                 byte_range: node.byte_range.clone(),
                 parent: UnsafeCell::new(None),
                 dfs: UnsafeCell::new(None),
+                ts_tree: OnceLock::new(),
                 ..*node
             });
             result.internal_set_parent_on_children();
@@ -946,46 +1076,153 @@ impl<'a> AstNode<'a> {
         conflict_in_self() || conflict_in_children()
     }
 
-    /// Extracts a signature for this node if we have a signature definition
-    /// for this type of nodes in the language profile.
-    pub(crate) fn signature(&'a self) -> Option<Signature<'a, 'a>> {
-        let definition = self.signature_definition()?;
+    /// Whether this node was turned into a leaf when parsing in semistructured mode
+    pub(crate) fn is_truncated(&self) -> bool {
+        self.truncated
+    }
 
-        if self.children.is_empty() && self.lang_profile.truncation_node_kinds.contains(self.grammar_name) {
-            let global_source = self.root().source;
-            let mut parser = Parser::new();
+    /// Whether this node is a truncation point, in semistructured mode.
+    /// Depending on the truncation policy, it might not have been truncated.
+    pub(crate) fn is_truncation_point(&self) -> bool {
+        self.truncation_point
+    }
 
-            if parser.set_language(&self.lang_profile.language).is_err() {
-                debug!("[AST DEBUG] Failed to set language for re-parsing.");
-                return None;
+    /// Recovers the tree-sitter node corresponding to this (truncated) node, and applies the
+    /// supplied function to it and the source of the file. The tree-sitter tree is kept from
+    /// the parse of the file, and only re-parsed for trees built otherwise (such as by [`Self::truncate`]).
+    fn with_ts_node<R>(&'a self, f: impl FnOnce(&tree_sitter::Node, &'a str) -> R) -> Option<R> {
+        let global_source = self.root().source;
+        let tree = match self.ancestors().find_map(|node| node.ts_tree.get()) {
+            Some(tree) => tree,
+            None => {
+                let root = self.root();
+                let mut parser = Parser::new();
+                if parser.set_language(&root.lang_profile.language).is_err() {
+                    debug!("[AST DEBUG] Failed to set language for re-parsing.");
+                    return None;
+                }
+                let _ = root.ts_tree.set(parser.parse(global_source, None)?);
+                root.ts_tree.get()?
             }
+        };
+        let root = tree.root_node();
 
-            if let Some(tree) = parser.parse(global_source, None) {
-                let root = tree.root_node();
+        let Some(target) = root.descendant_for_byte_range(self.byte_range.start, self.byte_range.end) else {
+            debug!("[AST DEBUG] Could not find a descendant for the given byte range.");
+            return None;
+        };
 
-                let Some(target) = root.descendant_for_byte_range(self.byte_range.start, self.byte_range.end) else {
-                    debug!("[AST DEBUG] Could not find a descendant for the given byte range.");
-                    return None;
-                };
+        if target.start_byte() != self.byte_range.start || target.end_byte() != self.byte_range.end {
+            debug!("[AST DEBUG] Found node does not match the exact byte range.");
+            return None;
+        }
 
-                if target.start_byte() != self.byte_range.start || target.end_byte() != self.byte_range.end {
-                    debug!("[AST DEBUG] Found node does not match the exact byte range.");
-                    return None;
-                }
+        Some(f(&target, global_source))
+    }
+
+    /// Extracts a signature for this node if we have a signature definition
+    /// for this type of nodes in the language profile.
+    pub(crate) fn signature(&'a self) -> Option<Signature<'a, 'a>> {
+        let definition = self.signature_definition()?;
+
+        if self.is_truncated() {
+            return self.with_ts_node(|target, global_source| {
+                let temp_sig = definition.extract_signature_from_ts_node(target, global_source);
 
-                let temp_sig = definition.extract_signature_from_ts_node(&target, global_source);
-                
                 debug!("[AST DEBUG] Extracted Signature (with descendant_for_byte_range): {}", temp_sig);
 
                 let static_sig = temp_sig.to_static();
-                return Some(unsafe { std::mem::transmute(static_sig) });
-            }
-          
-            return None;
+                unsafe {
+                    std::mem::transmute::<Signature<'static, 'static>, Signature<'a, 'a>>(
+                        static_sig,
+                    )
+                }
+            });
         }
 
         Some(definition.extract_signature_from_original_node(self))
     }
+
+    /// Splits the source of this node into tokens, which are the sources of its leaves
+    /// and the whitespace between them. The tokens concatenate back to the source of the node.
+    ///
+    /// The leaves of truncated nodes and atomic nodes are recovered from the tree-sitter tree.
+    pub(crate) fn source_tokens(&'a self) -> Option<Vec<&'a str>> {
+        let start = self.byte_range.start;
+        let mut tokens = Vec::new();
+        let mut last_end = start;
+        let mut push_leaf = |global_source: &'a str, range: Range<usize>| {
+            if range.start < last_end || range.is_empty() {
+                return;
+            }
+            if range.start > last_end {
+                tokens.push(&global_source[last_end..range.start]);
+            }
+            tokens.push(&global_source[range.clone()]);
+            last_end = range.end;
+        };
+
+        let global_source = self.root().source;
+        let recovered = self.is_leaf()
+            && self
+                .with_ts_node(|target, global_source| {
+                    let mut cursor = target.walk();
+                    let mut descended = true;
+                    // iterative pre-order traversal which stays within the target node
+                    loop {
+                        if descended && cursor.goto_first_child() {
+                            continue;
+                        }
+                        let node = cursor.node();
+                        if node.child_count() == 0 {
+                            push_leaf(global_source, node.byte_range());
+                        }
+                        if cursor.goto_next_sibling() {
+                            descended = true;
+                        } else if cursor.goto_parent() {
+                            descended = false;
+                        } else {
+                            break;
+                        }
+                    }
+                })
+                .is_some();
+        if !recovered {
+            if self.is_truncated() {
+                return None;
+            }
+            for leaf in self.dfs().filter(|node| node.is_leaf()) {
+                push_leaf(global_source, leaf.byte_range.clone());
+            }
+        }
+
+        let end = self.byte_range.end;
+        if last_end < end {
+            tokens.push(&global_source[last_end..end]);
+        }
+        Some(tokens)
+    }
+
+    /// The field names and grammar names of the named children of this node, as parsed
+    /// without truncation. Truncated nodes are re-parsed to recover them.
+    pub(crate) fn untruncated_child_kinds(&'a self) -> Option<Vec<(Option<&'static str>, &'static str)>> {
+        self.with_ts_node(|target, _| {
+            let mut cursor = target.walk();
+            let mut kinds = Vec::new();
+            if cursor.goto_first_child() {
+                loop {
+                    let child = cursor.node();
+                    if child.is_named() {
+                        kinds.push((cursor.field_name(), child.grammar_name()));
+                    }
+                    if !cursor.goto_next_sibling() {
+                        break;
+                    }
+                }
+            }
+            kinds
+        })
+    }
 }
 
 /// We pre-compute hash values for all nodes,
@@ -1201,6 +1438,7 @@ mod tests {
             parent: UnsafeCell::new(None),
             commutative_parent: node_2.commutative_parent,
             dfs: UnsafeCell::new(None),
+            ts_tree: OnceLock::new(),
             children: node_2.children.to_owned(),
             field_to_children: FxHashMap::default(),
             byte_range: node_2.byte_range.to_owned(),
@@ -1352,6 +1590,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tokens_of_truncated_nodes() {
+        let ctx = ctx();
+        let tree = ctx.parse_java_semistructured("class A {\n  void f(int x) { g(x); }\n}\n");
+        let method = tree[0][2][1];
+        assert!(method.is_truncated());
+
+        // the tree-sitter tree is kept from the parse, instead of parsing the file again
+        assert!(tree.ts_tree.get().is_some());
+        let tokens = method.source_tokens().expect("the tokens should be recovered");
+        assert_eq!(tokens.concat(), method.source);
+        assert_eq!(tokens[..5], ["void", " ", "f", "(", "int"]);
+
+        // trees built without parsing re-parse the file once
+        let arena = Arena::new();
+        let truncated = tree.truncate(|_| false, &arena);
+        let truncated_method = truncated[0][2][1];
+        assert!(truncated.ts_tree.get().is_none());
+        assert_eq!(truncated_method.source_tokens(), Some(tokens));
+        assert!(truncated.ts_tree.get().is_some());
+    }
+
     #[test]
     fn leading_source() {
         let ctx = ctx();
@@ -1744,4 +2004,69 @@ A list:
         // the other list isn't captured, so it's not associated to any commutative parent
         assert!(second_list.commutative_parent_definition().is_none());
     }
+
+    #[test]
+    fn truncation_via_query() {
+        let ctx = ctx();
+        let python = ctx.parse_python_semistructured(
+            "x = foo(1)\n__all__ = ['foo']\nif True:\n    y = bar(2)\n",
+        );
+
+        // module-level assignments are captured by the truncation query
+        let x_statement = python[0];
+        assert_eq!(x_statement.grammar_name, "expression_statement");
+        assert!(x_statement.is_truncated());
+        assert!(x_statement.children.is_empty());
+        // except for `__all__`, which needs to be parsed for it to be commutative
+        let all_statement = python[1];
+        assert!(!all_statement.is_truncated());
+        assert!(all_statement[0][2].commutative_parent_definition().is_some());
+        // statements in nested blocks aren't captured
+        let y_statement = python[2][3][0];
+        assert_eq!(y_statement.grammar_name, "expression_statement");
+        assert!(!y_statement.is_truncated());
+        assert!(!y_statement.children.is_empty());
+    }
+
+    #[test]
+    fn parse_replaced_range() {
+        let ctx = ctx();
+        let source = "class A {\n    void f() {\n        a();\n    }\n\n    void g() {}\n}\n";
+        let root = ctx.parse_java(source);
+        let method = root[0][2][1];
+        assert_eq!(method.grammar_name, "method_declaration");
+
+        let replacement = "void f() {\n        a();\n        b();\n    }";
+        let replaced = format!(
+            "{}{replacement}{}",
+            &source[..method.byte_range.start],
+            &source[method.byte_range.end..]
+        );
+        let arena = Arena::new();
+        let ref_arena = Arena::new();
+        let reparsed = root
+            .parse_replaced_range(
+                method.byte_range.clone(),
+                &replaced,
+                method.grammar_name,
+                &arena,
+                &ref_arena,
+            )
+            .expect("the replacement should parse back to a method");
+        assert_eq!(reparsed.source, replacement);
+        assert!(reparsed.isomorphic_to(ctx.parse_java(&replaced)[0][2][1]));
+
+        // the replacement doesn't parse to a single method
+        let replaced = replaced.replace("void f()", "void f() {} void h()");
+        assert!(
+            root.parse_replaced_range(
+                method.byte_range.clone(),
+                &replaced,
+                method.grammar_name,
+                &arena,
+                &ref_arena,
+            )
+            .is_none()
+        );
+    }
 }
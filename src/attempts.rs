@@ -165,6 +165,26 @@ impl AttemptsCache {
         })
     }
 
+    /// Reads the name of the merged file and the contents of its base, left and right
+    /// revisions from an attempt
+    pub fn read_revisions(
+        &self,
+        attempt_id: &str,
+    ) -> Result<(String, String, String, String), String> {
+        let attempt = self.parse_attempt_id(attempt_id)?;
+        let read = |revision| {
+            let path = attempt.path(revision);
+            fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read {}: {err}", path.display()))
+        };
+        Ok((
+            attempt.file_name.to_owned(),
+            read("Base")?,
+            read("Left")?,
+            read("Right")?,
+        ))
+    }
+
     /// Reviews an attempt by showing the diff between the line-based merge and Mergiraf's
     pub fn review_merge(&self, attempt_id: &str) -> Result<(), String> {
         let attempt = self.parse_attempt_id(attempt_id)?;
@@ -262,6 +282,15 @@ mod tests {
 
         assert_eq!(attempt, parsed_attempt);
 
+        let (file_name, base, left, right) = cache
+            .read_revisions(&attempt_id)
+            .expect("Could not read the revisions of the attempt");
+        assert_eq!(file_name, "MyFile.java");
+        assert_eq!(
+            (base.as_str(), left.as_str(), right.as_str()),
+            ("hello base", "hello left", "bye right")
+        );
+
         attempt
             .write_best_merge_id("some_merge_method")
             .expect("Failed to write best merge id in attempt");
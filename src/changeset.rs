@@ -175,6 +175,7 @@ impl<'a> ChangeSet<'a> {
     }
 
     ///For debug purposes
+    #[allow(dead_code)]
     pub fn iter_successors(&self) -> impl Iterator<Item = (&PCSNode<'a>, &PCS<'a>)> {
         self.successors.iter().flat_map(|(node, set)| {
             set.iter().map(move |pcs| (node, pcs))
@@ -201,6 +201,16 @@ impl<'a> ClassMapping<'a> {
         self.exact_matchings.get(leader).is_some_and(|n| *n >= 2)
     }
 
+    /// Was this node truncated in any of the revisions where it is present?
+    /// With size-dependent truncation policies, it might not have been truncated in all of them.
+    pub fn is_truncated_in_any_revision(&self, leader: &Leader<'a>) -> bool {
+        leader.as_representative().node.is_truncated()
+            || self
+                .internal_representatives(leader)
+                .values()
+                .any(|rev_node| rev_node.node.is_truncated())
+    }
+
     /// Maps a node from some revision to its class representative
     pub fn map_to_leader(&self, rev_node: RevNode<'a>) -> Leader<'a> {
         self.map.get(&rev_node).copied().unwrap_or(Leader(rev_node))
@@ -295,6 +305,149 @@ impl<'a> ClassMapping<'a> {
         })
     }
 
+    /// Matches the truncated nodes of the base revision which were left unmatched in
+    /// the supplied revision (generally because their contents were modified there)
+    /// to unmatched nodes with the same signature and parent in that revision,
+    /// so that their contents can be merged textually.
+    ///
+    /// A truncated node can also be matched to a truncation point which was not truncated
+    /// (and conversely), as nodes are truncated depending on their size in each revision.
+    pub fn unify_modified_truncated_nodes(&mut self, rev: Revision) {
+        let unmatched_base: Vec<_> = self
+            .get_unmatched_nodes(Revision::Base, rev)
+            .filter(|rev_node| rev_node.node.is_truncation_point())
+            .collect();
+        let mut side_sig_map: FxHashMap<Signature, Vec<RevNode>> = FxHashMap::default();
+        for rev_node in self.get_unmatched_nodes(rev, Revision::Base) {
+            if rev_node.node.is_truncation_point()
+                && let Some(signature) = rev_node.node.signature()
+            {
+                side_sig_map.entry(signature).or_default().push(rev_node);
+            }
+        }
+
+        for base_node in unmatched_base {
+            let Some(signature) = base_node.node.signature() else {
+                continue;
+            };
+            let Some(candidates) = side_sig_map.get_mut(&signature) else {
+                continue;
+            };
+            let same_parent = |candidate: &RevNode<'a>| match (base_node.node.parent(), candidate.node.parent()) {
+                (Some(base_parent), Some(side_parent)) => {
+                    self.map_to_leader(RevNode::new(Revision::Base, base_parent))
+                        == self.map_to_leader(RevNode::new(rev, side_parent))
+                }
+                _ => false,
+            };
+            // nodes which were truncated in neither revision were not matched for a reason
+            let any_truncated =
+                |candidate: &RevNode<'a>| base_node.node.is_truncated() || candidate.node.is_truncated();
+            if let Some(idx) = candidates
+                .iter()
+                .position(|candidate| any_truncated(candidate) && same_parent(candidate))
+            {
+                let side_node = candidates.remove(idx);
+                debug!("[CM DEBUG] Unifying modified nodes {} and {}", base_node, side_node);
+                self.unify_nodes(base_node, side_node);
+            }
+        }
+    }
+
+    /// In semistructured mode, links unmatched truncated nodes from the base revision
+    /// to unmatched nodes in the supplied revision which look like versions of them with
+    /// a different signature but the same name (such as a method whose parameter types changed),
+    /// with the same parent and similar enough sources. If there are multiple candidates, the one
+    /// with the most similar source is picked. Their contents can then be merged textually, so that
+    /// concurrent changes to the signature of a node conflict instead of keeping both versions.
+    ///
+    /// This should be run after [`Self::unify_modified_truncated_nodes`], so that nodes
+    /// which kept their signature get matched first.
+    pub fn unify_retyped_truncated_nodes(&mut self, rev: Revision) {
+        self.unify_similar_truncated_nodes(
+            rev,
+            "retyped",
+            RETYPE_SIMILARITY_THRESHOLD,
+            |base_sig, candidate_sig| {
+                base_sig != candidate_sig
+                    && base_sig.name().is_some()
+                    && base_sig.name() == candidate_sig.name()
+            },
+        );
+    }
+
+    /// In semistructured mode, links unmatched truncated nodes from the base revision
+    /// to unmatched nodes in the supplied revision which look like renamed versions of them:
+    /// their signatures only differ by their name, they have the same parent and their
+    /// sources are similar enough. Their contents can then be merged textually, which also
+    /// surfaces any conflicting renames.
+    ///
+    /// This should be run after [`Self::unify_modified_truncated_nodes`], so that nodes
+    /// which kept their signature get matched first.
+    pub fn unify_renamed_truncated_nodes(&mut self, rev: Revision) {
+        self.unify_similar_truncated_nodes(
+            rev,
+            "renamed",
+            RENAME_SIMILARITY_THRESHOLD,
+            |base_sig, candidate_sig| base_sig.differs_only_by_name(candidate_sig),
+        );
+    }
+
+    /// Links unmatched truncation points of the base revision to the most similar unmatched
+    /// truncation point of the supplied revision which has the same grammar name and parent,
+    /// whose signature is accepted by the supplied predicate and whose source is at least
+    /// as similar as the threshold.
+    fn unify_similar_truncated_nodes(
+        &mut self,
+        rev: Revision,
+        description: &str,
+        similarity_threshold: f32,
+        accept_signatures: impl Fn(&Signature<'a, 'a>, &Signature<'a, 'a>) -> bool,
+    ) {
+        let unmatched_base: Vec<_> = self
+            .get_unmatched_nodes(Revision::Base, rev)
+            .filter(|rev_node| rev_node.node.is_truncation_point())
+            .collect();
+        let mut candidates: Vec<_> = self
+            .get_unmatched_nodes(rev, Revision::Base)
+            .filter(|rev_node| rev_node.node.is_truncation_point())
+            .filter_map(|rev_node| Some((rev_node, rev_node.node.signature()?)))
+            .collect();
+
+        for base_node in unmatched_base {
+            let Some(signature) = base_node.node.signature() else {
+                continue;
+            };
+            let Some(base_parent) = base_node.node.parent() else {
+                continue;
+            };
+            let base_parent_leader = self.map_to_leader(RevNode::new(Revision::Base, base_parent));
+            let best_candidate = candidates
+                .iter()
+                .enumerate()
+                .filter(|(_, (candidate, candidate_signature))| {
+                    candidate.node.grammar_name == base_node.node.grammar_name
+                        && (base_node.node.is_truncated() || candidate.node.is_truncated())
+                        && candidate.node.parent().is_some_and(|parent| {
+                            self.map_to_leader(RevNode::new(rev, parent)) == base_parent_leader
+                        })
+                        && accept_signatures(&signature, candidate_signature)
+                })
+                .map(|(idx, (candidate, _))| {
+                    (idx, source_similarity(base_node.node.source, candidate.node.source))
+                })
+                .filter(|(_, similarity)| *similarity >= similarity_threshold)
+                .max_by(|(_, a), (_, b)| a.total_cmp(b));
+            if let Some((idx, similarity)) = best_candidate {
+                let (side_node, _) = candidates.remove(idx);
+                debug!(
+                    "[CM DEBUG] Unifying {description} nodes {base_node} and {side_node} (similarity {similarity})"
+                );
+                self.unify_nodes(base_node, side_node);
+            }
+        }
+    }
+
     pub fn unify_concurrent_additions(&mut self) {
         debug!("[CM DEBUG] Start Concurrent Additions Unification");
         // Encontra todos os nós em left e right que não têm correspondência em base.
@@ -534,6 +687,37 @@ impl Display for RevisionNESet {
     }
 }
 
+/// The minimum similarity between the sources of two truncated nodes
+/// for them to be considered as renamed versions of each other
+const RENAME_SIMILARITY_THRESHOLD: f32 = 0.7;
+
+/// The minimum similarity between the sources of two truncated nodes with the same name
+/// for them to be considered as versions of each other with a different signature.
+/// It is lower than for renamings, as keeping the name is already a strong hint.
+const RETYPE_SIMILARITY_THRESHOLD: f32 = 0.5;
+
+/// Dice similarity between the sets of words (identifiers, keywords and literals)
+/// appearing in two sources
+fn source_similarity(first: &str, second: &str) -> f32 {
+    fn words(source: &str) -> Vec<&str> {
+        source
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|word| !word.is_empty())
+            .sorted()
+            .collect()
+    }
+    let (first_words, second_words) = (words(first), words(second));
+    if first_words.is_empty() && second_words.is_empty() {
+        return 1.0;
+    }
+    let common = first_words
+        .iter()
+        .merge_join_by(second_words.iter(), |a, b| a.cmp(b))
+        .filter(|pair| pair.is_both())
+        .count();
+    2.0_f32 * (common as f32) / ((first_words.len() + second_words.len()) as f32)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test_utils::ctx;
@@ -645,4 +829,126 @@ mod tests {
         assert_eq!(class_mapping.map_to_leader(hey_right), expected_hey_leader);
         assert!(class_mapping.revision_set(&expected_hey_leader).is_full());
     }
+
+    /// Matches the nodes of two revisions which are not truncation points, as the matchers
+    /// would if the truncated nodes were all modified
+    fn match_containers<'a>(base: &'a AstNode<'a>, side: &'a AstNode<'a>) -> Matching<'a> {
+        let containers = |root: &'a AstNode<'a>| {
+            root.dfs()
+                .filter(|node| !node.is_truncation_point() && !node.ancestors().any(AstNode::is_truncation_point))
+                .filter(|node| !node.is_leaf())
+                .collect::<Vec<_>>()
+        };
+        let mut matching = Matching::new();
+        for (base_node, side_node) in containers(base).into_iter().zip(containers(side)) {
+            assert_eq!(base_node.grammar_name, side_node.grammar_name);
+            matching.add(base_node, side_node);
+        }
+        matching
+    }
+
+    fn methods<'a>(root: &'a AstNode<'a>) -> Vec<&'a AstNode<'a>> {
+        root.dfs()
+            .filter(|node| node.grammar_name == "method_declaration")
+            .collect()
+    }
+
+    #[test]
+    fn modified_truncated_nodes_are_matched() {
+        let ctx = ctx();
+
+        let base = ctx.parse_java_semistructured("class A {\n  void f() { a(); }\n  void g() { b(); }\n}\n");
+        let left = ctx.parse_java_semistructured("class A {\n  void f() { c(); }\n  void g() { d(); }\n}\n");
+
+        let mut class_mapping = ClassMapping::new();
+        class_mapping.add_matching(&match_containers(base, left), Revision::Base, Revision::Left, false);
+        class_mapping.populate_from_ast(Revision::Base, base);
+        class_mapping.populate_from_ast(Revision::Left, left);
+
+        let (base_methods, left_methods) = (methods(base), methods(left));
+        assert!(base_methods.iter().all(|method| method.is_truncated()));
+        for (base_method, left_method) in base_methods.iter().zip(&left_methods) {
+            assert_ne!(
+                class_mapping.map_to_leader(RevNode::new(Revision::Base, base_method)),
+                class_mapping.map_to_leader(RevNode::new(Revision::Left, left_method))
+            );
+        }
+
+        class_mapping.unify_modified_truncated_nodes(Revision::Left);
+
+        // the methods are matched by signature
+        for (base_method, left_method) in base_methods.iter().zip(&left_methods) {
+            assert_eq!(
+                class_mapping.map_to_leader(RevNode::new(Revision::Base, base_method)),
+                class_mapping.map_to_leader(RevNode::new(Revision::Left, left_method))
+            );
+        }
+    }
+
+    #[test]
+    fn modified_truncated_nodes_need_the_same_signature_and_parent() {
+        let ctx = ctx();
+
+        let base = ctx.parse_java_semistructured(
+            "class A {\n  void f() { a(); }\n  void g() { b(); }\n}\nclass B {\n}\n",
+        );
+        let left = ctx.parse_java_semistructured(
+            "class A {\n  void f(int x) { a(); }\n}\nclass B {\n  void g() { c(); }\n}\n",
+        );
+
+        let mut class_mapping = ClassMapping::new();
+        class_mapping.add_matching(&match_containers(base, left), Revision::Base, Revision::Left, false);
+        class_mapping.populate_from_ast(Revision::Base, base);
+        class_mapping.populate_from_ast(Revision::Left, left);
+
+        class_mapping.unify_modified_truncated_nodes(Revision::Left);
+
+        // `f` changed its signature and `g` moved to another class
+        for base_method in methods(base) {
+            let leader = class_mapping.map_to_leader(RevNode::new(Revision::Base, base_method));
+            assert!(!class_mapping.revision_set(&leader).contains(Revision::Left));
+        }
+    }
+
+    #[test]
+    fn retyped_truncated_nodes_need_similar_sources() {
+        let ctx = ctx();
+
+        let base = ctx.parse_java_semistructured(
+            "class A {\n  void f(int x) { total += x; log(x); }\n  void g(int x) { a(); }\n}\n",
+        );
+        let left = ctx.parse_java_semistructured(
+            "class A {\n  void f(long x) { total += x; log(x); }\n  void g(String s) { System.out.println(s); }\n}\n",
+        );
+
+        let mut class_mapping = ClassMapping::new();
+        class_mapping.add_matching(&match_containers(base, left), Revision::Base, Revision::Left, false);
+        class_mapping.populate_from_ast(Revision::Base, base);
+        class_mapping.populate_from_ast(Revision::Left, left);
+
+        class_mapping.unify_modified_truncated_nodes(Revision::Left);
+        class_mapping.unify_retyped_truncated_nodes(Revision::Left);
+
+        // only `f` kept enough of its source to be matched
+        let (base_methods, left_methods) = (methods(base), methods(left));
+        let leader_of = |rev, method| class_mapping.map_to_leader(RevNode::new(rev, method));
+        assert_eq!(
+            leader_of(Revision::Base, base_methods[0]),
+            leader_of(Revision::Left, left_methods[0])
+        );
+        assert_ne!(
+            leader_of(Revision::Base, base_methods[1]),
+            leader_of(Revision::Left, left_methods[1])
+        );
+    }
+
+    #[test]
+    fn similarity_of_sources() {
+        let base = "int total(int a, int b) {\n    return a + b;\n}";
+        let renamed = "int sum(int a, int b) {\n    return a + b;\n}";
+        let other = "void log(String message) {\n    System.out.println(message);\n}";
+        assert_eq!(source_similarity(base, base), 1.0);
+        assert!(source_similarity(base, renamed) >= RENAME_SIMILARITY_THRESHOLD);
+        assert!(source_similarity(base, other) < RENAME_SIMILARITY_THRESHOLD);
+    }
 }
@@ -0,0 +1,432 @@
+//! Repository-level configuration of the merge settings, read from a `.mergiraf.toml` file
+//! at the root of the repository:
+//!
+//! ```toml
+//! # defaults for all files
+//! compact = true
+//! timeout = 5000
+//!
+//! # per-language overrides, taking precedence over the defaults
+//! [languages.Java]
+//! semistructured = "diff3"
+//! truncation = "lines:3"
+//! reference-check = "warn"
+//!
+//! # language profiles defined or extended for this repository (see [`crate::custom_langs`])
+//! [profiles.Starlark]
+//! grammar = "Python"
+//! extensions = ["bzl", "star"]
+//! file-names = ["BUILD", "WORKSPACE"]
+//!
+//! # per-path overrides, relative to the repository root, taking precedence over the
+//! # language overrides (later patterns taking precedence over earlier ones)
+//! [files."src/generated/**"]
+//! fast = true
+//! ```
+//!
+//! Settings supplied on the command line take precedence over the configuration file.
+//! `mergiraf solve` only solves the conflicts of an existing merge, so it ignores the
+//! `timeout` and `fast` settings.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use clap::ValueEnum;
+use glob::{MatchOptions, Pattern};
+use toml::{Table, Value};
+
+use crate::{
+    TextualMergeStrategy, TruncationPolicy,
+    custom_langs::{self, parse_profile},
+    lang_profile::LangProfile,
+    settings::ReferenceCheck,
+};
+
+/// Name of the configuration file, looked up at the root of the repository
+pub const CONFIG_FILE_NAME: &str = ".mergiraf.toml";
+
+/// Merge settings which can be set in the configuration file as well as on the command line.
+/// Unset settings fall back on a configuration with lower precedence, or on the default
+/// behaviour of Mergiraf.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// Display compact conflicts, breaking down lines
+    pub compact: Option<bool>,
+    /// Length of conflict markers
+    pub conflict_marker_size: Option<usize>,
+    /// Unstructured merge algorithm used in semistructured merge
+    pub semistructured: Option<TextualMergeStrategy>,
+    /// Which nodes to truncate in semistructured merge
+    pub truncation: Option<TruncationPolicy>,
+    /// Whether to check for elements added on one side which reference elements modified
+    /// on the other side, in semistructured merge
+    pub reference_check: Option<ReferenceCheck>,
+    /// Maximum number of milliseconds to try doing the merging for (ignored by `mergiraf solve`)
+    pub timeout: Option<u64>,
+    /// Only solve the conflicts of the line-based merge, without a full structured merge
+    /// (ignored by `mergiraf solve`)
+    pub fast: Option<bool>,
+}
+
+impl MergeOptions {
+    /// Fills the settings unset in `self` with those of `fallback`
+    #[must_use]
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            compact: self.compact.or(fallback.compact),
+            conflict_marker_size: self.conflict_marker_size.or(fallback.conflict_marker_size),
+            semistructured: self.semistructured.or(fallback.semistructured),
+            truncation: self.truncation.or(fallback.truncation),
+            reference_check: self.reference_check.or(fallback.reference_check),
+            timeout: self.timeout.or(fallback.timeout),
+            fast: self.fast.or(fallback.fast),
+        }
+    }
+
+    /// Reads the settings from a table of the configuration file, rejecting the keys
+    /// which are not settings, except for those in `sections`
+    fn from_table(table: &Table, sections: &[&str], context: &str) -> Result<Self, String> {
+        let mut options = Self::default();
+        for (key, value) in table {
+            let invalid = |expected: &str| {
+                format!("Invalid value for `{key}` in {context}: expected {expected}, got {value}")
+            };
+            let positive_integer = || {
+                value
+                    .as_integer()
+                    .and_then(|integer| u64::try_from(integer).ok())
+                    .ok_or_else(|| invalid("a positive integer"))
+            };
+            match key.as_str() {
+                "compact" => {
+                    options.compact = Some(value.as_bool().ok_or_else(|| invalid("a boolean"))?)
+                }
+                "conflict-marker-size" => {
+                    let size = positive_integer()?;
+                    options.conflict_marker_size =
+                        Some(usize::try_from(size).map_err(|_| invalid("a smaller integer"))?);
+                }
+                "semistructured" => {
+                    let name = value.as_str().ok_or_else(|| invalid("a string"))?;
+                    // the external algorithm needs a command, which can only be supplied
+                    // on the command line
+                    let strategy = TextualMergeStrategy::from_str(name, false)
+                        .ok()
+                        .filter(|strategy| *strategy != TextualMergeStrategy::External)
+                        .ok_or_else(|| invalid("the name of an algorithm other than `external`"))?;
+                    options.semistructured = Some(strategy);
+                }
+                "truncation" => {
+                    let policy = value.as_str().ok_or_else(|| invalid("a string"))?;
+                    options.truncation = Some(
+                        TruncationPolicy::from_str(policy)
+                            .map_err(|_| invalid("`all`, `lines:N` or `nodes:N`"))?,
+                    );
+                }
+                "reference-check" => {
+                    let mode = value.as_str().ok_or_else(|| invalid("a string"))?;
+                    options.reference_check = Some(
+                        ReferenceCheck::from_str(mode, false)
+                            .map_err(|_| invalid("`off`, `warn` or `conflict`"))?,
+                    );
+                }
+                "timeout" => options.timeout = Some(positive_integer()?),
+                "fast" => options.fast = Some(value.as_bool().ok_or_else(|| invalid("a boolean"))?),
+                _ if sections.contains(&key.as_str()) => {}
+                _ => return Err(format!("Unknown setting `{key}` in {context}")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// The contents of a configuration file
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// The directory containing the configuration file, to which the file patterns are relative
+    root: PathBuf,
+    defaults: MergeOptions,
+    /// Overrides indexed by language name
+    languages: Vec<(&'static str, MergeOptions)>,
+    /// Overrides by file pattern, in the order of the file
+    files: Vec<(Pattern, MergeOptions)>,
+    /// Language profiles defined or extended in the file
+    profiles: Vec<LangProfile>,
+}
+
+impl Config {
+    /// Looks up the configuration file at the root of the repository containing `dir`,
+    /// which is the closest ancestor containing a `.git` directory or file
+    pub fn discover(dir: &Path) -> Result<Option<Self>, String> {
+        let Some(root) = dir.ancestors().find(|dir| dir.join(".git").exists()) else {
+            return Ok(None);
+        };
+        let path = root.join(CONFIG_FILE_NAME);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| format!("Could not read {}: {err}", path.display()))?;
+        Self::parse(&contents, root)
+            .map(Some)
+            .map_err(|err| format!("Invalid configuration file {}: {err}", path.display()))
+    }
+
+    /// Parses the contents of a configuration file located in the `root` directory
+    pub fn parse(contents: &str, root: &Path) -> Result<Self, String> {
+        let table: Table = contents.parse().map_err(|err| format!("{err}"))?;
+        let defaults =
+            MergeOptions::from_table(&table, &["languages", "files", "profiles"], "the defaults")?;
+
+        let section = |name: &str| match table.get(name) {
+            None => Ok(None),
+            Some(Value::Table(section)) => Ok(Some(section)),
+            Some(_) => Err(format!("`{name}` should be a table")),
+        };
+        let overrides = |name: &str, value: &Value| match value {
+            Value::Table(table) => MergeOptions::from_table(table, &[], &format!("`{name}`")),
+            _ => Err(format!("`{name}` should be a table")),
+        };
+
+        let mut profiles = Vec::new();
+        for (name, value) in section("profiles")?.into_iter().flatten() {
+            let Value::Table(definition) = value else {
+                return Err(format!("`{name}` should be a table"));
+            };
+            profiles.push(parse_profile(name, definition)?);
+        }
+
+        let mut languages = Vec::new();
+        for (name, value) in section("languages")?.into_iter().flatten() {
+            let language_name = profiles
+                .iter()
+                .find(|lang_profile| lang_profile.name.eq_ignore_ascii_case(name))
+                .map(|lang_profile| lang_profile.name)
+                .or_else(|| LangProfile::find_by_name(name).map(|lang_profile| lang_profile.name))
+                .ok_or_else(|| format!("Unknown language `{name}`"))?;
+            languages.push((language_name, overrides(name, value)?));
+        }
+
+        let mut files = Vec::new();
+        for (pattern, value) in section("files")?.into_iter().flatten() {
+            let glob = Pattern::new(pattern)
+                .map_err(|err| format!("Invalid file pattern `{pattern}`: {err}"))?;
+            files.push((glob, overrides(pattern, value)?));
+        }
+
+        Ok(Self {
+            root: root.to_owned(),
+            defaults,
+            languages,
+            files,
+            profiles,
+        })
+    }
+
+    /// Registers the language profiles defined in the file, so that they are used
+    /// to detect the language of files and to merge them
+    pub fn register_profiles(&self) {
+        for lang_profile in &self.profiles {
+            custom_langs::register(lang_profile.clone());
+        }
+    }
+
+    /// The settings applying to a file in the given language. Relative paths are interpreted
+    /// relative to the root of the repository.
+    pub fn options_for(&self, path: &Path, language: Option<&str>) -> MergeOptions {
+        let path = path.strip_prefix(&self.root).unwrap_or(path);
+        let match_options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        let file_options = self
+            .files
+            .iter()
+            .rev()
+            .filter(|(pattern, _)| pattern.matches_path_with(path, match_options))
+            .fold(MergeOptions::default(), |options, (_, overrides)| {
+                options.or(*overrides)
+            });
+        let language_options = self
+            .languages
+            .iter()
+            .find(|(name, _)| Some(*name) == language)
+            .map(|(_, options)| *options)
+            .unwrap_or_default();
+        file_options.or(language_options).or(self.defaults)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+compact = true
+timeout = 5000
+
+[languages.java]
+semistructured = "diff3"
+truncation = "lines:3"
+timeout = 2000
+
+[files."src/generated/**"]
+fast = true
+conflict-marker-size = 9
+reference-check = "conflict"
+
+[files."src/generated/*.java"]
+fast = false
+"#;
+
+    #[test]
+    fn precedence() {
+        let config = Config::parse(CONFIG, Path::new("/repo")).expect("invalid configuration");
+
+        assert_eq!(
+            config.options_for(Path::new("README.md"), None),
+            MergeOptions {
+                compact: Some(true),
+                timeout: Some(5000),
+                ..Default::default()
+            }
+        );
+        // languages are identified by the name of their profile
+        assert_eq!(
+            config.options_for(Path::new("src/Main.java"), Some("Java")),
+            MergeOptions {
+                compact: Some(true),
+                semistructured: Some(TextualMergeStrategy::Diff3),
+                truncation: Some(TruncationPolicy::MinLines(3)),
+                timeout: Some(2000),
+                ..Default::default()
+            }
+        );
+        // later patterns take precedence
+        assert_eq!(
+            config.options_for(Path::new("/repo/src/generated/Gen.java"), Some("Java")),
+            MergeOptions {
+                compact: Some(true),
+                conflict_marker_size: Some(9),
+                semistructured: Some(TextualMergeStrategy::Diff3),
+                truncation: Some(TruncationPolicy::MinLines(3)),
+                reference_check: Some(ReferenceCheck::Conflict),
+                timeout: Some(2000),
+                fast: Some(false),
+            }
+        );
+        // `*` does not match path separators
+        assert_eq!(
+            config
+                .options_for(Path::new("src/generated/sub/Gen.java"), Some("Java"))
+                .fast,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn command_line_takes_precedence() {
+        let config = Config::parse(CONFIG, Path::new("/repo")).expect("invalid configuration");
+        let cli = MergeOptions {
+            timeout: Some(100),
+            ..Default::default()
+        };
+        let options = cli.or(config.options_for(Path::new("README.md"), None));
+        assert_eq!(options.timeout, Some(100));
+        assert_eq!(options.compact, Some(true));
+    }
+
+    #[test]
+    fn invalid_configurations() {
+        let root = Path::new("/repo");
+        let error = |contents| Config::parse(contents, root).unwrap_err();
+        assert_eq!(
+            error("compakt = true"),
+            "Unknown setting `compakt` in the defaults"
+        );
+        assert_eq!(
+            error("timeout = -1"),
+            "Invalid value for `timeout` in the defaults: expected a positive integer, got -1"
+        );
+        assert_eq!(
+            error("[languages.klingon]\ncompact = true"),
+            "Unknown language `klingon`"
+        );
+        assert_eq!(
+            error("[files.\"*.rs\"]\nsemistructured = \"magic\""),
+            "Invalid value for `semistructured` in `*.rs`: expected the name of an algorithm other than `external`, got \"magic\""
+        );
+        assert_eq!(
+            error("truncation = \"lines\""),
+            "Invalid value for `truncation` in the defaults: expected `all`, `lines:N` or `nodes:N`, got \"lines\""
+        );
+        assert_eq!(
+            error("reference-check = true"),
+            "Invalid value for `reference-check` in the defaults: expected a string, got true"
+        );
+        assert!(error("compact = ").contains("TOML parse error"));
+    }
+
+    #[test]
+    fn profiles() {
+        let config = Config::parse(
+            r#"
+[profiles.Registry]
+grammar = "json"
+extensions = ["reg"]
+
+[languages.registry]
+compact = true
+"#,
+            Path::new("/repo"),
+        )
+        .expect("invalid configuration");
+        assert_eq!(config.profiles.len(), 1);
+        assert_eq!(config.profiles[0].extensions, ["reg"]);
+        // languages can refer to the profiles defined in the file
+        assert_eq!(
+            config
+                .options_for(Path::new("a.reg"), Some("Registry"))
+                .compact,
+            Some(true)
+        );
+
+        assert_eq!(
+            Config::parse(
+                "[profiles.Registry]
+extensions = [\"reg\"]",
+                Path::new("/repo")
+            )
+            .unwrap_err(),
+            "Unknown language `Registry`: a `grammar` is required to define a new one"
+        );
+    }
+
+    #[test]
+    fn discover() {
+        let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
+        let repo = repo_dir.path();
+        let subdir = repo.join("src");
+        fs::create_dir_all(&subdir).unwrap();
+
+        // not in a repository
+        assert!(Config::discover(&subdir).unwrap().is_none());
+
+        fs::create_dir(repo.join(".git")).unwrap();
+        assert!(Config::discover(&subdir).unwrap().is_none());
+
+        fs::write(repo.join(CONFIG_FILE_NAME), CONFIG).unwrap();
+        let config = Config::discover(&subdir)
+            .expect("invalid configuration")
+            .expect("no configuration found");
+        assert_eq!(
+            config
+                .options_for(&repo.join("src/generated/lib.rs"), None)
+                .fast,
+            Some(true)
+        );
+    }
+}
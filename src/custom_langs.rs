@@ -0,0 +1,596 @@
+//! Language profiles defined or extended at runtime, such as in the `profiles` section of the
+//! configuration file (see [`crate::config`]):
+//!
+//! ```toml
+//! # extends the built-in profile for Python
+//! [profiles.Python]
+//! commutative-parents = [
+//!     { query = '(assignment left: (identifier) @name (#eq? @name "REGISTRY") right: (list) @commutative)', left-delim = "[", separator = ", ", right-delim = "]" },
+//! ]
+//!
+//! # defines a new profile, reusing the tree-sitter grammar of a supported language
+//! [profiles.Registry]
+//! grammar = "Python"
+//! extensions = ["reg"]
+//! commutative-parents = [{ type = "list", left-delim = "[", separator = ", ", right-delim = "]" }]
+//! signatures = [{ type = "pair", paths = [["key"]] }]
+//!
+//! # defines a new profile, with a grammar loaded from a shared library (see [`crate::dynamic_grammar`])
+//! [profiles.Dsl]
+//! grammar = { library = "libtree-sitter-dsl.so", symbol = "tree_sitter_dsl" }
+//! extensions = ["dsl"]
+//! ```
+
+use std::sync::RwLock;
+
+use rustc_hash::FxHashSet;
+use toml::{Table, Value};
+use tree_sitter::{Language, Query};
+
+use crate::{
+    dynamic_grammar,
+    lang_profile::{CommutativeParent, LangProfile, ParentType},
+    signature::{PathStep, SignatureDefinition, signature},
+    tree_matcher::{MatcherOverrides, TreeMatcherOverrides},
+};
+
+/// The profiles registered at runtime, most recent first
+static CUSTOM_LANGUAGES: RwLock<Vec<&'static LangProfile>> = RwLock::new(Vec::new());
+
+/// Registers a language profile. It takes precedence over the supported languages
+/// and replaces any profile previously registered with the same name.
+pub fn register(lang_profile: LangProfile) {
+    let lang_profile: &'static LangProfile = Box::leak(Box::new(lang_profile));
+    let mut languages = CUSTOM_LANGUAGES
+        .write()
+        .expect("the lock on custom languages is poisoned");
+    languages.retain(|registered| registered.name != lang_profile.name);
+    languages.insert(0, lang_profile);
+}
+
+/// The profiles registered at runtime, most recent first
+pub(crate) fn custom_languages() -> Vec<&'static LangProfile> {
+    CUSTOM_LANGUAGES
+        .read()
+        .expect("the lock on custom languages is poisoned")
+        .clone()
+}
+
+/// Parses the definition of a language profile from a table of the configuration file.
+///
+/// If the table has a `grammar` key, it defines a new profile using the tree-sitter grammar of
+/// the supported language with that name, or the one loaded from a library. Otherwise, it extends the profile of the existing
+/// language called `name`, with its own settings taking precedence.
+pub(crate) fn parse_profile(name: &str, table: &Table) -> Result<LangProfile, String> {
+    let context = format!("profile `{name}`");
+    let mut lang_profile = match table.get("grammar") {
+        Some(grammar) => LangProfile {
+            name: leak(name),
+            alternate_names: &[],
+            extensions: Vec::new(),
+            file_names: Vec::new(),
+            language: parse_grammar(name, grammar, &context)?,
+            atomic_nodes: Vec::new(),
+            commutative_parents: Vec::new(),
+            signatures: Vec::new(),
+            injections: None,
+            truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: Vec::new(),
+            matcher_overrides: MatcherOverrides::default(),
+        },
+        None => LangProfile::find_by_name(name)
+            .ok_or_else(|| {
+                format!("Unknown language `{name}`: a `grammar` is required to define a new one")
+            })?
+            .clone(),
+    };
+    let language = lang_profile.language.clone();
+
+    for (key, value) in table {
+        let setting_context = || format!("`{key}` of {context}");
+        match key.as_str() {
+            "grammar" => {}
+            "extensions" => lang_profile
+                .extensions
+                .extend(strings(value, &setting_context())?),
+            "file-names" => lang_profile
+                .file_names
+                .extend(strings(value, &setting_context())?),
+            "atomic-nodes" => {
+                let context = setting_context();
+                for node_type in strings(value, &context)? {
+                    check_node_type(&language, node_type, false, &context)?;
+                    lang_profile.atomic_nodes.push(node_type);
+                }
+            }
+            "commutative-parents" => {
+                let context = setting_context();
+                let commutative_parents = tables(value, &context)?
+                    .map(|table| parse_commutative_parent(&language, table, &context))
+                    .collect::<Result<Vec<_>, _>>()?;
+                lang_profile
+                    .commutative_parents
+                    .splice(0..0, commutative_parents);
+            }
+            "signatures" => {
+                let context = setting_context();
+                let signatures = tables(value, &context)?
+                    .map(|table| parse_signature(&language, table, &context))
+                    .collect::<Result<Vec<_>, _>>()?;
+                lang_profile.signatures.splice(0..0, signatures);
+            }
+            "truncation-node-kinds" => {
+                let context = setting_context();
+                for node_type in strings(value, &context)? {
+                    check_node_type(&language, node_type, true, &context)?;
+                    lang_profile.truncation_node_kinds.insert(node_type);
+                }
+            }
+            "truncation-queries" => {
+                let context = setting_context();
+                for query in strings(value, &context)? {
+                    check_query(&language, query, "truncated", &context)?;
+                    lang_profile.truncation_queries.push(query);
+                }
+            }
+            "primary-matcher" => {
+                let overrides = parse_matcher_overrides(value, &setting_context())?;
+                let matcher_overrides = &mut lang_profile.matcher_overrides;
+                matcher_overrides.primary = overrides.or(matcher_overrides.primary);
+            }
+            "auxiliary-matcher" => {
+                let overrides = parse_matcher_overrides(value, &setting_context())?;
+                let matcher_overrides = &mut lang_profile.matcher_overrides;
+                matcher_overrides.auxiliary = overrides.or(matcher_overrides.auxiliary);
+            }
+            _ => return Err(format!("Unknown setting `{key}` in {context}")),
+        }
+    }
+    Ok(lang_profile)
+}
+
+/// Parses the grammar of a new profile: either the name of a supported language whose grammar
+/// to reuse, or a table designating a grammar `library` (see [`crate::dynamic_grammar`]) and
+/// optionally the `symbol` exposing the grammar in it
+fn parse_grammar(name: &str, grammar: &Value, context: &str) -> Result<Language, String> {
+    match grammar {
+        Value::String(grammar) => LangProfile::find_by_name(grammar)
+            .map(|grammar_profile| grammar_profile.language.clone())
+            .ok_or_else(|| format!("Unknown grammar `{grammar}` in {context}")),
+        Value::Table(grammar) => {
+            if let Some(key) = grammar
+                .keys()
+                .find(|key| !["library", "symbol"].contains(&key.as_str()))
+            {
+                return Err(format!(
+                    "Unknown setting `{key}` for the grammar of {context}"
+                ));
+            }
+            let library = grammar
+                .get("library")
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("The grammar of {context} needs a `library`"))?;
+            let symbol = match grammar.get("symbol") {
+                None => dynamic_grammar::default_symbol(name),
+                Some(Value::String(symbol)) => symbol.clone(),
+                Some(_) => {
+                    return Err(format!(
+                        "Invalid `symbol` for the grammar of {context}: expected a string"
+                    ));
+                }
+            };
+            let library_path = dynamic_grammar::find_grammar_library(library)?;
+            dynamic_grammar::load_grammar(&library_path, &symbol)
+        }
+        _ => Err(format!(
+            "Invalid `grammar` in {context}: expected the name of a language or a table"
+        )),
+    }
+}
+
+/// Parses a commutative parent, designated by a `type` or a `query`, with a `separator`, optional
+/// `left-delim` and `right-delim`, and optional `groups` of children types allowed to commute together
+fn parse_commutative_parent(
+    language: &Language,
+    table: &Table,
+    context: &str,
+) -> Result<CommutativeParent, String> {
+    let optional_string = |key: &str| {
+        table
+            .get(key)
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(leak)
+                    .ok_or_else(|| format!("Invalid `{key}` in {context}: expected a string"))
+            })
+            .transpose()
+    };
+    let parent_type = match (optional_string("type")?, optional_string("query")?) {
+        (Some(node_type), None) => {
+            check_node_type(language, node_type, true, context)?;
+            ParentType::ByGrammarName(node_type)
+        }
+        (None, Some(query)) => {
+            check_query(language, query, "commutative", context)?;
+            ParentType::ByQuery(query)
+        }
+        _ => {
+            return Err(format!(
+                "Commutative parents in {context} need either a `type` or a `query`"
+            ));
+        }
+    };
+    let separator = optional_string("separator")?
+        .ok_or_else(|| format!("Commutative parents in {context} need a `separator`"))?;
+    let commutative_parent = CommutativeParent::with_optional_delimiters(
+        parent_type,
+        optional_string("left-delim")?,
+        separator,
+        optional_string("right-delim")?,
+    );
+
+    for key in table.keys() {
+        if ![
+            "type",
+            "query",
+            "separator",
+            "left-delim",
+            "right-delim",
+            "groups",
+        ]
+        .contains(&key.as_str())
+        {
+            return Err(format!(
+                "Unknown setting `{key}` for a commutative parent in {context}"
+            ));
+        }
+    }
+    let Some(groups) = table.get("groups") else {
+        return Ok(commutative_parent);
+    };
+    let groups = groups
+        .as_array()
+        .ok_or_else(|| format!("Invalid `groups` in {context}: expected an array"))?
+        .iter()
+        .map(|group| strings(group, context))
+        .collect::<Result<Vec<_>, _>>()?;
+    for node_type in groups.iter().flatten() {
+        check_node_type(language, node_type, false, context)?;
+    }
+    let groups: Vec<&[&'static str]> = groups.iter().map(Vec::as_slice).collect();
+    Ok(commutative_parent.restricted_to_groups(&groups))
+}
+
+/// Parses a signature definition, made of the `type` of nodes it applies to and the `paths`
+/// to their descendants forming the signature. The steps of a path are either field names,
+/// or tables like `{ child-type = "identifier" }` selecting children by type.
+fn parse_signature(
+    language: &Language,
+    table: &Table,
+    context: &str,
+) -> Result<SignatureDefinition, String> {
+    let node_type = table
+        .get("type")
+        .and_then(Value::as_str)
+        .map(leak)
+        .ok_or_else(|| format!("Signatures in {context} need a `type`"))?;
+    check_node_type(language, node_type, true, context)?;
+    let paths = table
+        .get("paths")
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("Signatures in {context} need an array of `paths`"))?;
+    let invalid_path = || {
+        format!(
+            "Invalid path in the signature of `{node_type}` in {context}: expected an array of field names or `{{ child-type = \"...\" }}` tables"
+        )
+    };
+    let paths = paths
+        .iter()
+        .map(|path| {
+            path.as_array()
+                .ok_or_else(invalid_path)?
+                .iter()
+                .map(|step| match step {
+                    Value::String(field) => Ok(PathStep::Field(leak(field))),
+                    Value::Table(step) if step.len() == 1 => step
+                        .get("child-type")
+                        .and_then(Value::as_str)
+                        .map(|child_type| PathStep::ChildType(leak(child_type)))
+                        .ok_or_else(invalid_path),
+                    _ => Err(invalid_path()),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(signature(node_type, paths))
+}
+
+/// Parses overrides of the parameters of a tree matcher: `min-height`, `sim-threshold`,
+/// `use-rted` and `max-recovery-size`
+fn parse_matcher_overrides(value: &Value, context: &str) -> Result<TreeMatcherOverrides, String> {
+    let table = value
+        .as_table()
+        .ok_or_else(|| format!("Invalid {context}: expected a table"))?;
+    let mut overrides = TreeMatcherOverrides::default();
+    for (key, value) in table {
+        let invalid = |expected: &str| {
+            format!("Invalid value for `{key}` in {context}: expected {expected}, got {value}")
+        };
+        let positive_integer = || {
+            value
+                .as_integer()
+                .and_then(|integer| i32::try_from(integer).ok())
+                .filter(|integer| *integer >= 0)
+                .ok_or_else(|| invalid("a positive integer"))
+        };
+        match key.as_str() {
+            "min-height" => overrides.min_height = Some(positive_integer()?),
+            "sim-threshold" => {
+                let threshold = value
+                    .as_float()
+                    .or_else(|| value.as_integer().map(|integer| integer as f64))
+                    .filter(|threshold| (0.0..=1.0).contains(threshold))
+                    .ok_or_else(|| invalid("a number between 0 and 1"))?;
+                overrides.sim_threshold = Some(threshold as f32);
+            }
+            "use-rted" => {
+                overrides.use_rted = Some(value.as_bool().ok_or_else(|| invalid("a boolean"))?);
+            }
+            "max-recovery-size" => overrides.max_recovery_size = Some(positive_integer()?),
+            _ => return Err(format!("Unknown setting `{key}` in {context}")),
+        }
+    }
+    Ok(overrides)
+}
+
+/// Checks that a node type exists in a grammar, as a named node if `named` is set
+fn check_node_type(
+    language: &Language,
+    node_type: &str,
+    named: bool,
+    context: &str,
+) -> Result<(), String> {
+    let exists = language.id_for_node_kind(node_type, true) != 0
+        || (!named && language.id_for_node_kind(node_type, false) != 0);
+    if exists {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown node type `{node_type}` in {context} for this grammar"
+        ))
+    }
+}
+
+/// Checks that a query is valid and has the supplied capture
+fn check_query(
+    language: &Language,
+    query: &str,
+    capture: &str,
+    context: &str,
+) -> Result<(), String> {
+    let query =
+        Query::new(language, query).map_err(|err| format!("Invalid query in {context}: {err}"))?;
+    if query.capture_index_for_name(capture).is_some() {
+        Ok(())
+    } else {
+        Err(format!("Query without a `@{capture}` capture in {context}"))
+    }
+}
+
+/// Reads an array of strings
+fn strings(value: &Value, context: &str) -> Result<Vec<&'static str>, String> {
+    value
+        .as_array()
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|value| value.as_str().map(leak))
+                .collect()
+        })
+        .ok_or_else(|| format!("Invalid {context}: expected an array of strings"))
+}
+
+/// Reads an array of tables
+fn tables<'a>(value: &'a Value, context: &str) -> Result<impl Iterator<Item = &'a Table>, String> {
+    let values = value
+        .as_array()
+        .filter(|values| values.iter().all(Value::is_table))
+        .ok_or_else(|| format!("Invalid {context}: expected an array of tables"))?;
+    Ok(values.iter().filter_map(Value::as_table))
+}
+
+fn leak(s: &str) -> &'static str {
+    s.to_owned().leak()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{ast::AstNode, test_utils::ctx};
+
+    fn parse(name: &str, definition: &str) -> Result<LangProfile, String> {
+        parse_profile(name, &definition.parse().expect("invalid TOML"))
+    }
+
+    #[test]
+    fn define_profile() {
+        let lang_profile = parse(
+            "Registry",
+            r#"
+grammar = "json"
+extensions = ["reg"]
+atomic-nodes = ["string"]
+commutative-parents = [{ type = "array", left-delim = "[", separator = ", ", right-delim = "]" }]
+signatures = [{ type = "pair", paths = [["key"]] }]
+truncation-node-kinds = ["object"]
+"#,
+        )
+        .expect("invalid profile");
+
+        assert_eq!(lang_profile.name, "Registry");
+        assert_eq!(lang_profile.extensions, ["reg"]);
+        assert!(lang_profile.is_atomic_node_type("string"));
+        assert!(
+            lang_profile
+                .get_commutative_parent_by_grammar_name("array")
+                .is_some()
+        );
+        assert!(
+            lang_profile
+                .find_signature_definition_by_grammar_name("pair")
+                .is_some()
+        );
+        assert!(lang_profile.truncation_node_kinds.contains("object"));
+        // nothing is inherited from the profile of the grammar
+        assert!(
+            lang_profile
+                .get_commutative_parent_by_grammar_name("object")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn extend_profile() {
+        let lang_profile = parse(
+            "JSON",
+            r#"
+extensions = ["jsonc"]
+commutative-parents = [{ type = "array", left-delim = "[", separator = ", ", right-delim = "]" }]
+"#,
+        )
+        .expect("invalid profile");
+
+        assert_eq!(lang_profile.name, "JSON");
+        assert!(lang_profile.extensions.contains(&"json"));
+        assert!(lang_profile.extensions.contains(&"jsonc"));
+        assert!(
+            lang_profile
+                .get_commutative_parent_by_grammar_name("array")
+                .is_some()
+        );
+        assert!(
+            lang_profile
+                .get_commutative_parent_by_grammar_name("object")
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn matcher_overrides() {
+        let lang_profile = parse(
+            "Markdown",
+            r#"
+primary-matcher = { sim-threshold = 0.5, min-height = 2 }
+auxiliary-matcher = { use-rted = true }
+"#,
+        )
+        .expect("invalid profile");
+        assert_eq!(
+            lang_profile.matcher_overrides,
+            MatcherOverrides {
+                primary: TreeMatcherOverrides {
+                    min_height: Some(2),
+                    sim_threshold: Some(0.5),
+                    ..Default::default()
+                },
+                auxiliary: TreeMatcherOverrides {
+                    use_rted: Some(true),
+                    ..Default::default()
+                },
+            }
+        );
+
+        let error = |definition| parse("Markdown", definition).unwrap_err();
+        assert_eq!(
+            error("primary-matcher = { sim-threshold = 2 }"),
+            "Invalid value for `sim-threshold` in `primary-matcher` of profile `Markdown`: expected a number between 0 and 1, got 2"
+        );
+        assert_eq!(
+            error("auxiliary-matcher = { min-height = -1 }"),
+            "Invalid value for `min-height` in `auxiliary-matcher` of profile `Markdown`: expected a positive integer, got -1"
+        );
+        assert_eq!(
+            error("auxiliary-matcher = { threshold = 1 }"),
+            "Unknown setting `threshold` in `auxiliary-matcher` of profile `Markdown`"
+        );
+    }
+
+    #[test]
+    fn invalid_profiles() {
+        let error = |name, definition| parse(name, definition).unwrap_err();
+        assert_eq!(
+            error("Klingon", "extensions = [\"kl\"]"),
+            "Unknown language `Klingon`: a `grammar` is required to define a new one"
+        );
+        assert_eq!(
+            error("Registry", "grammar = \"klingon\""),
+            "Unknown grammar `klingon` in profile `Registry`"
+        );
+        assert_eq!(
+            error("Dsl", "grammar = { library = \"../libdsl.so\" }"),
+            "Invalid grammar library `../libdsl.so`: expected a file name, looked up in the directories of MERGIRAF_GRAMMAR_PATH"
+        );
+        assert_eq!(
+            error("Dsl", "grammar = { path = \"libdsl.so\" }"),
+            "Unknown setting `path` for the grammar of profile `Dsl`"
+        );
+        assert_eq!(
+            error("JSON", "atomic-nodes = [\"sentence\"]"),
+            "Unknown node type `sentence` in `atomic-nodes` of profile `JSON` for this grammar"
+        );
+        assert_eq!(
+            error("JSON", "commutative-parents = [{ type = \"array\" }]"),
+            "Commutative parents in `commutative-parents` of profile `JSON` need a `separator`"
+        );
+        assert_eq!(
+            error(
+                "JSON",
+                "commutative-parents = [{ query = \"(array) @list\", separator = \",\" }]"
+            ),
+            "Query without a `@commutative` capture in `commutative-parents` of profile `JSON`"
+        );
+        assert!(
+            error("JSON", "truncation-queries = [\"(array\"]")
+                .starts_with("Invalid query in `truncation-queries` of profile `JSON`")
+        );
+        assert_eq!(
+            error("JSON", "signatures = [{ type = \"pair\", paths = [[1]] }]"),
+            "Invalid path in the signature of `pair` in `signatures` of profile `JSON`: expected an array of field names or `{ child-type = \"...\" }` tables"
+        );
+        assert_eq!(
+            error("JSON", "injections = \"\""),
+            "Unknown setting `injections` in profile `JSON`"
+        );
+    }
+
+    #[test]
+    fn commutative_parent_by_query() {
+        let ctx = ctx();
+        let lang_profile = parse(
+            "JSON",
+            r#"
+commutative-parents = [
+    { query = '(pair key: (string (string_content) @key (#eq? @key "set")) value: (array) @commutative)', left-delim = "[", separator = ", ", right-delim = "]" },
+]
+"#,
+        )
+        .expect("invalid profile");
+        let lang_profile: &'static LangProfile = Box::leak(Box::new(lang_profile));
+
+        let tree = AstNode::parse(
+            "{\"set\": [1, 2], \"list\": [3, 4]}",
+            lang_profile,
+            &ctx.arena,
+            &ctx.ref_arena,
+            None,
+        )
+        .expect("syntax error in source");
+        let object = tree[0];
+        let set = object[1][2];
+        let list = object[3][2];
+        assert!(set.commutative_parent_definition().is_some());
+        assert!(list.commutative_parent_definition().is_none());
+    }
+}
@@ -0,0 +1,148 @@
+//! Loading of tree-sitter grammars compiled as shared libraries, for the languages which are not
+//! built into Mergiraf. Since loading a library runs its code, grammars are only looked up in the
+//! directories listed in the [`GRAMMAR_PATH_ENV_VAR`] environment variable, so that the
+//! configuration file of a repository can only select grammars which the user chose to trust.
+
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use libloading::{Library, Symbol};
+use tree_sitter::{LANGUAGE_VERSION, Language, MIN_COMPATIBLE_LANGUAGE_VERSION};
+use tree_sitter_language::LanguageFn;
+
+/// Environment variable listing the directories in which grammar libraries are looked up,
+/// separated like in the `PATH` variable
+/// ## Usage
+/// ```console
+/// MERGIRAF_GRAMMAR_PATH=~/.local/lib/tree-sitter mergiraf merge foo.dsl bar.dsl baz.dsl
+/// ```
+pub const GRAMMAR_PATH_ENV_VAR: &str = "MERGIRAF_GRAMMAR_PATH";
+
+/// Finds a grammar library by its file name in the directories of [`GRAMMAR_PATH_ENV_VAR`]
+pub fn find_grammar_library(file_name: &str) -> Result<PathBuf, String> {
+    find_in_directories(file_name, env::var_os(GRAMMAR_PATH_ENV_VAR))
+}
+
+fn find_in_directories(file_name: &str, directories: Option<OsString>) -> Result<PathBuf, String> {
+    if Path::new(file_name).file_name() != Some(file_name.as_ref()) {
+        return Err(format!(
+            "Invalid grammar library `{file_name}`: expected a file name, looked up in the directories of {GRAMMAR_PATH_ENV_VAR}"
+        ));
+    }
+    let directories = directories.ok_or_else(|| {
+        format!(
+            "Could not find the grammar library `{file_name}`: {GRAMMAR_PATH_ENV_VAR} is not set"
+        )
+    })?;
+    env::split_paths(&directories)
+        .map(|directory| directory.join(file_name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            format!(
+                "Could not find the grammar library `{file_name}` in the directories of {GRAMMAR_PATH_ENV_VAR}"
+            )
+        })
+}
+
+/// Loads a tree-sitter grammar from a shared library, exposing it with the supplied symbol
+/// (such as `tree_sitter_json`). The library is never unloaded.
+pub fn load_grammar(library_path: &Path, symbol: &str) -> Result<Language, String> {
+    // SAFETY: the library is trusted by the user, who placed it in a directory
+    // of the grammar path (or supplied it directly through the library API)
+    let library = unsafe { Library::new(library_path) }
+        .map_err(|err| format!("Could not load {}: {err}", library_path.display()))?;
+    let language = {
+        // SAFETY: the signature is that of the functions generated by the tree-sitter CLI
+        let language_fn: Symbol<unsafe extern "C" fn() -> *const ()> =
+            unsafe { library.get(symbol.as_bytes()) }.map_err(|err| {
+                format!(
+                    "Could not find the grammar `{symbol}` in {}: {err}",
+                    library_path.display()
+                )
+            })?;
+        // SAFETY: same as above
+        Language::new(unsafe { LanguageFn::from_raw(*language_fn) })
+    };
+    let abi_version = language.abi_version();
+    if !(MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION).contains(&abi_version) {
+        return Err(format!(
+            "Incompatible grammar in {}: its ABI version is {abi_version}, expected between {MIN_COMPATIBLE_LANGUAGE_VERSION} and {LANGUAGE_VERSION}",
+            library_path.display()
+        ));
+    }
+    // the grammar points to static data of the library, which must therefore stay loaded
+    std::mem::forget(library);
+    Ok(language)
+}
+
+/// The symbol exposing the grammar of a language in the libraries generated by the
+/// tree-sitter CLI, such as `tree_sitter_c_sharp` for `C-Sharp`
+pub fn default_symbol(language_name: &str) -> String {
+    let name: String = language_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("tree_sitter_{name}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn find_library() {
+        let dir = tempfile::tempdir().expect("failed to create the temp dir");
+        let other_dir = tempfile::tempdir().expect("failed to create the temp dir");
+        fs::write(dir.path().join("libdsl.so"), "").unwrap();
+        let directories = env::join_paths([other_dir.path(), dir.path()]).unwrap();
+
+        assert_eq!(
+            find_in_directories("libdsl.so", Some(directories.clone())),
+            Ok(dir.path().join("libdsl.so"))
+        );
+        assert_eq!(
+            find_in_directories("libother.so", Some(directories.clone())),
+            Err(format!(
+                "Could not find the grammar library `libother.so` in the directories of {GRAMMAR_PATH_ENV_VAR}"
+            ))
+        );
+        assert_eq!(
+            find_in_directories("libdsl.so", None),
+            Err(format!(
+                "Could not find the grammar library `libdsl.so`: {GRAMMAR_PATH_ENV_VAR} is not set"
+            ))
+        );
+        // libraries can't be looked up elsewhere
+        let path = dir.path().join("libdsl.so");
+        assert!(find_in_directories(path.to_str().unwrap(), Some(directories)).is_err());
+    }
+
+    #[test]
+    fn load_invalid_library() {
+        let dir = tempfile::tempdir().expect("failed to create the temp dir");
+        let path = dir.path().join("libdsl.so");
+        fs::write(&path, "not a library").unwrap();
+        assert!(
+            load_grammar(&path, "tree_sitter_dsl")
+                .unwrap_err()
+                .starts_with("Could not load")
+        );
+    }
+
+    #[test]
+    fn symbols() {
+        assert_eq!(default_symbol("json"), "tree_sitter_json");
+        assert_eq!(default_symbol("C-Sharp"), "tree_sitter_c_sharp");
+    }
+}
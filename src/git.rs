@@ -71,7 +71,8 @@ pub(crate) fn extract_all_revisions_from_git(
     }
 }
 
-fn read_content_from_commit(repo_dir: &Path, oid: &str, file_name: &Path) -> Option<String> {
+/// Reads the content of a file at a given commit
+pub fn read_content_from_commit(repo_dir: &Path, oid: &str, file_name: &Path) -> Option<String> {
     Command::new("git")
         .args(["show", &format!("{}:{}", oid, file_name.display())])
         .current_dir(repo_dir)
@@ -83,7 +84,7 @@ fn read_content_from_commit(repo_dir: &Path, oid: &str, file_name: &Path) -> Opt
 }
 
 /// Extracts the content of all revisions of a file from relevant commits by oid.
-pub(crate) fn read_content_from_commits(
+pub fn read_content_from_commits(
     repo_dir: &Path,
     oids: (&str, &str, &str),
     file_name: &Path,
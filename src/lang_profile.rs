@@ -1,10 +1,20 @@
-use std::{collections::HashSet, ffi::OsStr, fmt::Display, hash::Hash, path::Path};
+use std::{
+    collections::HashSet,
+    ffi::OsStr,
+    fmt::Display,
+    hash::Hash,
+    path::Path,
+    sync::{Arc, LazyLock, Mutex},
+};
 
 use itertools::Itertools;
-use tree_sitter::Language;
-use rustc_hash::FxHashSet;
+use tree_sitter::{Language, Query, QueryError};
+use rustc_hash::{FxHashMap, FxHashSet};
 
-use crate::{signature::SignatureDefinition, supported_langs::SUPPORTED_LANGUAGES};
+use crate::{
+    custom_langs::custom_languages, signature::SignatureDefinition,
+    supported_langs::SUPPORTED_LANGUAGES, tree_matcher::MatcherOverrides,
+};
 
 /// Language-dependent settings to influence how merging is done.
 /// All those settings are declarative (except for the tree-sitter parser, which is
@@ -33,6 +43,13 @@ pub struct LangProfile {
     /// A set of node kinds that should be treated as truncation points.
     ///The tree construction will stop at these nodes, treating their content as text.
     pub truncation_node_kinds: FxHashSet<&'static str>,
+    /// Tree-sitter queries locating truncation points which only apply in certain contexts,
+    /// such as statements at the top level of a module. The nodes to truncate are
+    /// designated by a `@truncated` capture.
+    pub truncation_queries: Vec<&'static str>,
+    /// Adjustments of the parameters of the tree matchers, for languages which are matched
+    /// better with other settings than the default ones
+    pub matcher_overrides: MatcherOverrides,
 }
 
 impl PartialEq for LangProfile {
@@ -56,10 +73,34 @@ impl Display for LangProfile {
 }
 
 impl LangProfile {
+    /// All the language profiles: those registered at runtime (see [`crate::custom_langs`]),
+    /// followed by the supported languages
+    fn all() -> impl Iterator<Item = &'static Self> {
+        custom_languages().into_iter().chain(SUPPORTED_LANGUAGES.iter())
+    }
+
+    /// Compile one of the queries of this profile (such as a truncation query).
+    /// Each query is only compiled once per profile, and shared by all the parses which follow.
+    pub(crate) fn query(&self, query: &'static str) -> Result<Arc<Query>, QueryError> {
+        /// The compiled queries, indexed by the name of their profile (which identifies it) and their source
+        type CompiledQueries = FxHashMap<(&'static str, &'static str), Arc<Query>>;
+        static QUERIES: LazyLock<Mutex<CompiledQueries>> = LazyLock::new(Mutex::default);
+
+        let mut queries = QUERIES
+            .lock()
+            .expect("the lock on compiled queries is poisoned");
+        if let Some(compiled) = queries.get(&(self.name, query)) {
+            return Ok(compiled.clone());
+        }
+        let compiled = Arc::new(Query::new(&self.language, query)?);
+        queries.insert((self.name, query), compiled.clone());
+        Ok(compiled)
+    }
+
     /// Load a profile by language name.
     /// Alternate names or extensions are also considered.
     pub fn find_by_name(name: &str) -> Option<&'static Self> {
-        SUPPORTED_LANGUAGES.iter().find(|lang_profile| {
+        Self::all().find(|lang_profile| {
             lang_profile.name.eq_ignore_ascii_case(name)
                 || (lang_profile.alternate_names.iter())
                     .chain(&lang_profile.extensions)
@@ -78,7 +119,7 @@ impl LangProfile {
             // https://github.com/Wilfred/difftastic/blob/master/src/parse/tree_sitter_parser.rs
             let extension = filename.extension()?;
             let name = filename.file_name()?;
-            SUPPORTED_LANGUAGES.iter().find(|lang_profile| {
+            LangProfile::all().find(|lang_profile| {
                 lang_profile
                     .extensions
                     .iter()
@@ -245,6 +286,22 @@ impl CommutativeParent {
         }
     }
 
+    /// Declares a commutative parent with optional delimiters
+    pub(crate) fn with_optional_delimiters(
+        parent_type: ParentType<'static>,
+        left_delim: Option<&'static str>,
+        separator: &'static str,
+        right_delim: Option<&'static str>,
+    ) -> Self {
+        Self {
+            parent_type,
+            separator,
+            left_delim,
+            right_delim,
+            children_groups: Vec::new(),
+        }
+    }
+
     /// Short-hand function to create a commutative parent with a left delimiter and separator
     pub(crate) fn with_left_delimiter(
         parent_type: &'static str,
@@ -314,7 +371,7 @@ impl CommutativeParent {
     }
 
     /// the type of the root node
-    pub(crate) fn parent_type(&self) -> &ParentType {
+    pub(crate) fn parent_type(&self) -> &ParentType<'static> {
         &self.parent_type
     }
 
@@ -407,6 +464,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn queries_are_compiled_once() {
+        let python = LangProfile::find_by_name("python").unwrap();
+        let query = python.truncation_queries[0];
+
+        let compiled = python.query(query).expect("invalid truncation query");
+        let recompiled = python.query(query).expect("invalid truncation query");
+        assert!(Arc::ptr_eq(&compiled, &recompiled));
+
+        assert!(python.query("(not_a_node_kind) @truncated").is_err());
+    }
+
     #[test]
     fn find_by_filename_or_name() {
         fn find(filename: &str, name: Option<&str>) -> Result<&'static LangProfile, String> {
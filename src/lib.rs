@@ -12,15 +12,26 @@
 //!
 //! ## Using as a library to build other programs
 //!
-//! Mergiraf is not designed to be used as a library so far, the Rust API is therefore not meant
-//! to be stable.
+//! Files can be merged with a [`Merger`], which is the entry point meant to stay stable:
+//!
+//! ```
+//! let merge = mergiraf::Merger::new()
+//!     .file_name("Cargo.toml")
+//!     .merge("[a]\nx = 1\n", "[a]\nx = 1\ny = 2\n", "[a]\nz = 3\nx = 1\n");
+//! assert_eq!(merge.conflict_count, 0);
+//! ```
+//!
+//! The rest of the Rust API is not meant to be stable.
 
 pub mod ast;
 pub mod attempts;
 pub mod bug_reporter;
 pub(crate) mod changeset;
 pub(crate) mod class_mapping;
-pub(crate) mod git;
+pub mod config;
+pub mod custom_langs;
+pub mod dynamic_grammar;
+pub mod git;
 pub mod lang_profile;
 pub mod line_based;
 pub(crate) mod matching;
@@ -29,6 +40,7 @@ pub(crate) mod merge_3dm;
 pub(crate) mod merge_result;
 pub(crate) mod merged_text;
 pub(crate) mod merged_tree;
+pub mod merger;
 pub(crate) mod multimap;
 pub mod newline;
 pub mod textual_merge;
@@ -36,6 +48,7 @@ pub mod textual_merge_strategy;
 pub(crate) mod parsed_merge;
 mod path_buf_ext;
 pub(crate) mod pcs;
+pub(crate) mod reference_check;
 pub mod settings;
 pub(crate) mod signature;
 mod solve;
@@ -45,6 +58,7 @@ pub mod supported_langs;
 pub(crate) mod test_utils;
 pub(crate) mod tree_builder;
 pub(crate) mod tree_matcher;
+pub mod truncation_policy;
 pub(crate) mod visualizer;
 
 use core::fmt::Write;
@@ -55,10 +69,11 @@ use lang_profile::LangProfile;
 use log::debug;
 
 pub use textual_merge_strategy::TextualMergeStrategy;
+pub use truncation_policy::TruncationPolicy;
 use merge_result::MergeResult;
 use parsed_merge::{PARSED_MERGE_DIFF2_DETECTED, ParsedMerge};
 use pcs::Revision;
-use settings::DisplaySettings;
+use settings::{ChunkLogFormat, DisplaySettings, SemistructuredSettings};
 use structured::structured_merge;
 use supported_langs::SUPPORTED_LANGUAGES;
 
@@ -72,6 +87,7 @@ pub use path_buf_ext::PathBufExt;
 pub const DISABLING_ENV_VAR: &str = "mergiraf";
 
 pub use merge::line_merge_and_structured_resolution;
+pub use merger::Merger;
 pub use solve::resolve_merge_cascading;
 
 /// Takes the result of an earlier merge process (likely line-based)
@@ -84,8 +100,8 @@ fn resolve_merge<'a>(
     settings: &DisplaySettings<'a>,
     lang_profile: &LangProfile,
     debug_dir: Option<&Path>,
-    print_chunks: bool,
-    semistructured: Option<TextualMergeStrategy>,
+    print_chunks: Option<ChunkLogFormat>,
+    semistructured: Option<SemistructuredSettings>,
 ) -> Result<MergeResult, String> {
     let start = Instant::now();
 
@@ -154,7 +170,7 @@ pub fn languages(gitattributes: bool) -> String {
 
 #[cfg(test)]
 mod test {
-    use crate::structured::ZDIFF3_DETECTED;
+    use crate::{settings::SemistructuredSettings, structured::ZDIFF3_DETECTED};
 
     use super::*;
 
@@ -173,7 +189,74 @@ mod test {
 ";
         let settings = DisplaySettings::default();
         let parsed = ParsedMerge::parse(contents, &settings).unwrap();
-        let result = resolve_merge(&parsed, &settings, LangProfile::rust(), None, false, None);
+        let result = resolve_merge(&parsed, &settings, LangProfile::rust(), None, None, None);
         assert_eq!(result, Err(ZDIFF3_DETECTED.to_string()));
     }
+
+    #[test]
+    fn semistructured_stats() {
+        let base = "class A {\n    int f() {\n        return 1;\n    }\n\n    int g() {\n        return 1;\n    }\n\n    int k() {\n        return 1;\n    }\n}\n";
+        let left = "class A {\n    int f() {\n        return 2;\n    }\n\n    int g() {\n        return 2;\n    }\n\n    int k() {\n        return 1;\n    }\n}\n";
+        let right = "class A {\n    int f() {\n        return 3;\n    }\n\n    int g() {\n        return 1;\n    }\n\n    int k() {\n        return 1;\n    }\n\n    int h() {\n        return 3;\n    }\n}\n";
+        let lang_profile = LangProfile::find_by_filename_or_name("A.java", None).unwrap();
+        let settings = DisplaySettings::default();
+
+        let merge = |semistructured| {
+            structured_merge(
+                base,
+                left,
+                right,
+                None,
+                &settings,
+                lang_profile,
+                None,
+                None,
+                semistructured,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(merge(None).semistructured_stats, None);
+        let stats = merge(Some(SemistructuredSettings::new(TextualMergeStrategy::Diff3)))
+            .semistructured_stats
+            .expect("semistructured merges have stats");
+        assert_eq!(stats.truncated_nodes(), 4);
+        assert_eq!(stats.merged_cleanly, 1);
+        assert_eq!(stats.merged_with_conflicts, 1);
+        assert_eq!(stats.one_sided, 1);
+        assert_eq!(stats.unchanged, 1);
+    }
+
+    #[test]
+    fn json_chunk_log() {
+        let base = "{\"a\": 1}";
+        let left = "{\"a\": 1, \"b\": 2}";
+        let right = "{\"a\": 1, \"c\": 3}";
+        let lang_profile = LangProfile::find_by_filename_or_name("a.json", None).unwrap();
+        let settings = DisplaySettings::default();
+
+        let merge = |print_chunks| {
+            structured_merge(
+                base,
+                left,
+                right,
+                None,
+                &settings,
+                lang_profile,
+                None,
+                print_chunks,
+                None,
+            )
+            .unwrap()
+        };
+
+        assert!(merge(None).chunk_log.is_empty());
+        assert!(merge(Some(ChunkLogFormat::Text)).chunk_log.is_empty());
+        let chunk_log = merge(Some(ChunkLogFormat::Json)).chunk_log;
+        assert!(!chunk_log.is_empty());
+        for chunk in chunk_log {
+            let chunk: serde_json::Value = serde_json::from_str(&chunk).unwrap();
+            assert_eq!(chunk["attempt"], "structured");
+        }
+    }
 }
@@ -3,23 +3,27 @@ use std::{
     env, fs,
     path::{Path, PathBuf},
     process::{Command, exit},
+    sync::Arc,
     time::Duration,
 };
 
 use clap::{ArgAction, Args, Parser, Subcommand};
-use log::warn;
+use log::{info, warn};
 use mergiraf::{
     DISABLING_ENV_VAR,
     PathBufExt,
     attempts::AttemptsCache,
     bug_reporter::report_bug,
+    config::{Config, MergeOptions},
+    lang_profile::LangProfile,
     languages,
-    line_merge_and_structured_resolution,
+    merger::Strategy,
+    Merger,
     // XXX: move the uses to lib to avoid making these public?
     newline::{imitate_cr_lf_from_input, normalize_to_lf},
     resolve_merge_cascading,
-    settings::DisplaySettings,
-    TextualMergeStrategy,
+    settings::{ChunkLogFormat, DisplaySettings, ReferenceCheck, SemistructuredSettings},
+    TextualMergeStrategy, TruncationPolicy,
 };
 
 /// Syntax-aware merge driver for Git.
@@ -55,12 +59,66 @@ struct MergeOrSolveArgs {
     /// Override automatic language detection.
     #[arg(short = 'L', long)]
     language: Option<String>,
-    /// Print a detailed, chunk-by-chunk log of the merge process
-    #[arg(long, default_value_t = false)]
-    print_chunks: bool,
+    /// Print a detailed, chunk-by-chunk log of the merge process, as text in the logs
+    /// or as JSON lines on the standard output
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_missing_value = "text",
+        num_args = 0..=1,
+        require_equals = true
+    )]
+    print_chunks: Option<ChunkLogFormat>,
     /// Chooses a unstructured merge algorithm to be used in semistructured merge
     #[arg(long, value_enum, value_name = "ALGORITHM")]
     semistructured: Option<TextualMergeStrategy>,
+    /// Which nodes to truncate in semistructured merge: `all` (the default), only those spanning
+    /// at least N lines (`lines:N`), or only those made of at least N syntax nodes (`nodes:N`)
+    #[arg(long, value_name = "POLICY")]
+    truncation: Option<TruncationPolicy>,
+    /// Check for elements added on one side which reference elements modified on the other side,
+    /// in semistructured merge [default: off]
+    #[arg(long, value_enum, value_name = "MODE")]
+    reference_check: Option<ReferenceCheck>,
+    /// Command merging truncated nodes with the `external` semistructured algorithm, run like a
+    /// Git merge driver: `%O`, `%A` and `%B` are replaced by files holding the base, left and right
+    /// revisions, the result is read from `%A` and an exit code between 1 and 128 signals conflicts.
+    /// Commands running for more than 10 seconds are killed
+    #[arg(long, value_name = "COMMAND", required_if_eq("semistructured", "external"))]
+    merge_command: Option<String>,
+    /// Print statistics about how truncated nodes were merged, and how long each phase took,
+    /// in semistructured merge
+    #[arg(long, default_value_t = false)]
+    print_stats: bool,
+}
+
+impl MergeOrSolveArgs {
+    /// The first flag among those which only apply to semistructured merge, if any is set
+    fn semistructured_flag(&self) -> Option<&'static str> {
+        [
+            ("--truncation", self.truncation.is_some()),
+            ("--reference-check", self.reference_check.is_some()),
+            ("--merge-command", self.merge_command.is_some()),
+            ("--print-stats", self.print_stats),
+        ]
+        .into_iter()
+        .find_map(|(flag, set)| set.then_some(flag))
+    }
+}
+
+/// Rejects the flags which only apply to semistructured merge, unless it is enabled,
+/// either on the command line or in the configuration file
+fn check_semistructured_flag(
+    flag: Option<&str>,
+    semistructured: Option<TextualMergeStrategy>,
+) -> Result<(), String> {
+    match flag {
+        Some(flag) if semistructured.is_none() => Err(format!(
+            "{flag} only applies to semistructured merge, which is enabled with --semistructured or the `semistructured` setting of the configuration file"
+        )),
+        _ => Ok(()),
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -75,8 +133,12 @@ enum CliCommand {
         right: PathBuf,
         /// Only attempt to merge the files by solving textual conflicts,
         /// without doing a full structured merge from the ground up.
-        #[arg(long)]
+        #[arg(long, overrides_with = "no_fast")]
         fast: bool,
+        /// Fall back on a full structured merge if textual conflicts remain,
+        /// even if the configuration file enables `fast`
+        #[arg(long, overrides_with = "fast")]
+        no_fast: bool,
         #[command(flatten)]
         merge_or_solve: MergeOrSolveArgs,
         /// Behave as a git merge driver: overwrite the left revision
@@ -105,7 +167,8 @@ enum CliCommand {
         #[arg(short, long)]
         timeout: Option<u64>,
     },
-    /// Solve the conflicts in a merged file
+    /// Solve the conflicts in a merged file.
+    /// The `timeout` and `fast` settings of the configuration file only apply to `mergiraf merge`.
     Solve {
         /// Path to the file containing merge conflicts
         conflicts: PathBuf,
@@ -166,6 +229,10 @@ fn main() {
     }
 }
 
+/// The JSON lines of the chunk log are written to the standard output, where they
+/// can't be mixed with a merged file
+const JSON_CHUNKS_ON_STDOUT: &str = "--print-chunks=json writes the chunks to the standard output";
+
 fn real_main(args: CliArgs) -> Result<i32, String> {
     let return_code = match args.command {
         CliCommand::Merge {
@@ -173,44 +240,68 @@ fn real_main(args: CliArgs) -> Result<i32, String> {
             left,
             right,
             fast,
+            no_fast,
             path_name,
             git,
             output,
             base_name,
             left_name,
             right_name,
-            merge_or_solve:
-                MergeOrSolveArgs {
-                    debug_dir,
+            merge_or_solve,
+            timeout,
+        } => {
+            let semistructured_flag = merge_or_solve.semistructured_flag();
+            let MergeOrSolveArgs {
+                debug_dir,
+                compact,
+                conflict_marker_size,
+                language,
+                print_chunks,
+                semistructured,
+                truncation,
+                reference_check,
+                merge_command,
+                print_stats,
+            } = merge_or_solve;
+            if print_chunks == Some(ChunkLogFormat::Json) && output.is_none() && !git {
+                return Err(format!("{JSON_CHUNKS_ON_STDOUT}, use -o/--output to write the merge to a file"));
+            }
+            let old_git_detected = base_name.as_deref().is_some_and(|n| n == "%S");
+
+            let (base, left, right) = (&*base, &*left, &*right);
+            let path_name = path_name.as_deref();
+            let base_name = base_name.as_deref();
+            let left_name = left_name.as_deref();
+            let right_name = right_name.as_deref();
+
+            let mergiraf_disabled = env::var(DISABLING_ENV_VAR).as_deref() == Ok("0");
+            let working_dir = env::current_dir().expect("Invalid current directory");
+            let MergeOptions {
+                compact,
+                conflict_marker_size,
+                semistructured,
+                truncation,
+                reference_check,
+                timeout,
+                fast,
+            } = merge_options(
+                MergeOptions {
                     compact,
                     conflict_marker_size,
-                    language,
-                    print_chunks,
                     semistructured,
+                    truncation,
+                    reference_check,
+                    timeout,
+                    fast: fast_option(fast, no_fast),
                 },
-            timeout,
-        } => {
-            let old_git_detected = base_name.as_deref().is_some_and(|n| n == "%S");
+                &working_dir.join(path_name.unwrap_or(base)),
+                language.as_deref(),
+                mergiraf_disabled,
+            );
+            check_semistructured_flag(semistructured_flag, semistructured)?;
+            let fast = fast.unwrap_or(false);
 
-            #[expect(unstable_name_collisions)]
-            let base = base.leak();
-            #[expect(unstable_name_collisions)]
-            let left = left.leak();
-            #[expect(unstable_name_collisions)]
-            let right = right.leak();
-
-            // NOTE: reborrow to turn `&mut Path` returned by `PathBuf::leak` into `&Path`
-            #[expect(unstable_name_collisions)]
-            let path_name = path_name.map(|s| &*s.leak());
-            #[expect(unstable_name_collisions)]
-            let debug_dir = debug_dir.map(|s| &*s.leak());
-
-            // same for `String::leak`
-            let base_name = base_name.map(|s| &*s.leak());
-            let left_name = left_name.map(|s| &*s.leak());
-            let right_name = right_name.map(|s| &*s.leak());
-
-            let settings: DisplaySettings<'static> = DisplaySettings {
+            let settings = DisplaySettings {
                 compact,
                 conflict_marker_size,
                 base_revision_name: match base_name {
@@ -231,47 +322,71 @@ fn real_main(args: CliArgs) -> Result<i32, String> {
                 ..Default::default()
             };
 
-            {
-                let mergiraf_disabled = env::var(DISABLING_ENV_VAR).as_deref() == Ok("0");
-
-                if mergiraf_disabled {
-                    return fallback_to_git_merge_file(base, left, right, git, &settings);
-                }
+            if mergiraf_disabled {
+                return fallback_to_git_merge_file(base, left, right, git, &settings);
             }
 
-            let fname_base = &*base;
+            let fname_base = base;
             let original_contents_base = read_file_to_string(fname_base)?;
             let contents_base = normalize_to_lf(original_contents_base);
-            let contents_base = contents_base.into_owned().leak();
 
             let fname_left = &left;
             let original_contents_left = read_file_to_string(fname_left)?;
             let contents_left = normalize_to_lf(&original_contents_left);
-            let contents_left = contents_left.into_owned().leak();
 
             let fname_right = &right;
             let original_contents_right = read_file_to_string(fname_right)?;
             let contents_right = normalize_to_lf(original_contents_right);
-            let contents_right = contents_right.into_owned().leak();
 
             let attempts_cache = AttemptsCache::new(None, None).ok();
 
             let fname_base = path_name.unwrap_or(fname_base);
 
-            let merge_result = line_merge_and_structured_resolution(
-                contents_base,
-                contents_left,
-                contents_right,
-                fname_base,
-                settings,
-                !fast,
-                attempts_cache.as_ref(),
-                debug_dir,
-                Duration::from_millis(timeout.unwrap_or(if fast { 5000 } else { 10000 })),
-                language.as_deref(),
-                print_chunks,
-                semistructured,
-            );
+            let semistructured = semistructured.map(|strategy| SemistructuredSettings {
+                strategy,
+                truncation_policy: truncation.unwrap_or_default(),
+                merge_command: merge_command.map(Arc::from),
+                reference_check: reference_check.unwrap_or_default(),
+            });
+            let mut merger = Merger::new()
+                .file_name(fname_base)
+                .settings(settings)
+                .strategy(if fast {
+                    Strategy::Fast
+                } else {
+                    Strategy::Structured
+                })
+                .timeout(Duration::from_millis(
+                    timeout.unwrap_or(if fast { 5000 } else { 10000 }),
+                ));
+            if let Some(language) = language {
+                merger = merger.language(language);
+            }
+            if let Some(semistructured) = semistructured {
+                merger = merger.semistructured(semistructured);
+            }
+            if let Some(attempts_cache) = &attempts_cache {
+                merger = merger.cache(attempts_cache);
+            }
+            if let Some(debug_dir) = debug_dir {
+                merger = merger.debug_dir(debug_dir);
+            }
+            if let Some(print_chunks) = print_chunks {
+                merger = merger.print_chunks(print_chunks);
+            }
+            let merge_result = merger.merge(&contents_base, &contents_left, &contents_right);
+            for chunk in &merge_result.chunk_log {
+                println!("{chunk}");
+            }
+            if print_stats {
+                match &merge_result.semistructured_stats {
+                    Some(stats) => info!("{stats}"),
+                    None => info!(
+                        "no semistructured merge in the {} output",
+                        merge_result.method
+                    ),
+                }
+            }
             if let Some(fname_out) = output {
                 write_string_to_file(&fname_out, &merge_result.contents)?;
             } else if git {
@@ -296,25 +411,33 @@ fn real_main(args: CliArgs) -> Result<i32, String> {
         }
         CliCommand::Solve {
             conflicts: fname_conflicts,
-            merge_or_solve:
-                MergeOrSolveArgs {
-                    debug_dir,
-                    compact,
-                    conflict_marker_size,
-                    language,
-                    print_chunks,
-                    semistructured,
-                },
+            merge_or_solve,
             keep,
             mut stdout,
             keep_backup,
         } => {
+            let semistructured_flag = merge_or_solve.semistructured_flag();
+            let MergeOrSolveArgs {
+                debug_dir,
+                compact,
+                conflict_marker_size,
+                language,
+                print_chunks,
+                semistructured,
+                truncation,
+                reference_check,
+                merge_command,
+                print_stats,
+            } = merge_or_solve;
             if keep {
                 warn!("-k/--keep is DEPRECATED, use -p/--stdout instead");
                 // since we only use `stdout` in the actual logic below,
                 // update its value with what of `--keep`
                 stdout = keep;
             }
+            if print_chunks == Some(ChunkLogFormat::Json) && stdout {
+                return Err(format!("{JSON_CHUNKS_ON_STDOUT}, so it can't be used with -p/--stdout"));
+            }
             // Check if user is using Jujutsu instead of Git, which can lead to issues.
             if let Ok(canonical_path) = fname_conflicts.canonicalize()
                 && let Some(conflict_dir) = canonical_path.parent()
@@ -336,6 +459,29 @@ fn real_main(args: CliArgs) -> Result<i32, String> {
                 );
             }
 
+            let working_dir = env::current_dir().expect("Invalid current directory");
+            let MergeOptions {
+                compact,
+                conflict_marker_size,
+                semistructured,
+                truncation,
+                reference_check,
+                ..
+            } = merge_options(
+                MergeOptions {
+                    compact,
+                    conflict_marker_size,
+                    semistructured,
+                    truncation,
+                    reference_check,
+                    ..Default::default()
+                },
+                &working_dir.join(&fname_conflicts),
+                language.as_deref(),
+                false,
+            );
+            check_semistructured_flag(semistructured_flag, semistructured)?;
+
             let settings = DisplaySettings {
                 compact,
                 // NOTE: the names will be recognized in `resolve_merge_cascading` (if possible)
@@ -348,8 +494,13 @@ fn real_main(args: CliArgs) -> Result<i32, String> {
 
             let original_conflict_contents = read_file_to_string(&fname_conflicts)?;
             let conflict_contents = normalize_to_lf(&original_conflict_contents);
-            let working_dir = env::current_dir().expect("Invalid current directory");
 
+            let semistructured = semistructured.map(|strategy| SemistructuredSettings {
+                strategy,
+                truncation_policy: truncation.unwrap_or_default(),
+                merge_command: merge_command.map(Arc::from),
+                reference_check: reference_check.unwrap_or_default(),
+            });
             let postprocessed = resolve_merge_cascading(
                 &conflict_contents,
                 &fname_conflicts,
@@ -362,6 +513,17 @@ fn real_main(args: CliArgs) -> Result<i32, String> {
             );
             match postprocessed {
                 Ok(merged) => {
+                    for chunk in &merged.chunk_log {
+                        println!("{chunk}");
+                    }
+                    if print_stats {
+                        match &merged.semistructured_stats {
+                            Some(stats) => info!("{stats}"),
+                            None => {
+                                info!("no semistructured merge in the {} output", merged.method)
+                            }
+                        }
+                    }
                     if stdout {
                         print!(
                             "{}",
@@ -402,6 +564,41 @@ fn real_main(args: CliArgs) -> Result<i32, String> {
     Ok(return_code)
 }
 
+/// The settings for merging a file: those supplied on the command line take precedence over
+/// the configuration file of the repository, which is ignored when Mergiraf is disabled.
+/// The language profiles defined in the configuration file are registered along the way.
+fn merge_options(
+    cli_options: MergeOptions,
+    path: &Path,
+    language: Option<&str>,
+    mergiraf_disabled: bool,
+) -> MergeOptions {
+    if mergiraf_disabled {
+        return cli_options;
+    }
+    let config = match path.parent().map(Config::discover) {
+        Some(Ok(config)) => config,
+        Some(Err(err)) => {
+            warn!("Mergiraf: ignoring configuration file: {err}");
+            None
+        }
+        None => None,
+    };
+    let Some(config) = config else {
+        return cli_options;
+    };
+    config.register_profiles();
+    let language = LangProfile::find_by_filename_or_name(path, language)
+        .ok()
+        .map(|lang_profile| lang_profile.name);
+    cli_options.or(config.options_for(path, language))
+}
+
+/// The `fast` setting set by the `--fast` and `--no-fast` flags, if any
+fn fast_option(fast: bool, no_fast: bool) -> Option<bool> {
+    if fast { Some(true) } else { no_fast.then_some(false) }
+}
+
 fn read_file_to_string(path: &Path) -> Result<String, String> {
     fs::read_to_string(path).map_err(|err| format!("Could not read {}: {err}", path.display()))
 }
@@ -492,6 +689,279 @@ mod test {
         assert_eq!(compact, Some(true));
     }
 
+    #[test]
+    fn truncation_flag() {
+        let CliCommand::Merge {
+            merge_or_solve: MergeOrSolveArgs { truncation, .. },
+            ..
+        } = CliArgs::parse_from(["mergiraf", "merge", "foo.c", "bar.c", "baz.c"]).command
+        else {
+            unreachable!("`mergiraf merge` should invoke the `Merge` subcommand")
+        };
+        assert_eq!(truncation, None);
+
+        let CliCommand::Merge {
+            merge_or_solve: MergeOrSolveArgs { truncation, .. },
+            ..
+        } = CliArgs::parse_from([
+            "mergiraf",
+            "merge",
+            "--semistructured=diff3",
+            "--truncation=lines:10",
+            "foo.c",
+            "bar.c",
+            "baz.c",
+        ])
+        .command
+        else {
+            unreachable!("`mergiraf merge` should invoke the `Merge` subcommand")
+        };
+        assert_eq!(truncation, Some(TruncationPolicy::MinLines(10)));
+    }
+
+    #[test]
+    fn reference_check_flag() {
+        let CliCommand::Merge {
+            merge_or_solve: MergeOrSolveArgs {
+                reference_check, ..
+            },
+            ..
+        } = CliArgs::parse_from([
+            "mergiraf",
+            "merge",
+            "--semistructured=diff3",
+            "--reference-check=warn",
+            "foo.c",
+            "bar.c",
+            "baz.c",
+        ])
+        .command
+        else {
+            unreachable!("`mergiraf merge` should invoke the `Merge` subcommand")
+        };
+        assert_eq!(reference_check, Some(ReferenceCheck::Warn));
+    }
+
+    #[test]
+    fn print_chunks_flag() {
+        let print_chunks = |args: &[&str]| {
+            let CliCommand::Merge {
+                merge_or_solve: MergeOrSolveArgs { print_chunks, .. },
+                ..
+            } = CliArgs::parse_from(
+                ["mergiraf", "merge"]
+                    .iter()
+                    .chain(args)
+                    .chain(&["foo.c", "bar.c", "baz.c"]),
+            )
+            .command
+            else {
+                unreachable!("`mergiraf merge` should invoke the `Merge` subcommand")
+            };
+            print_chunks
+        };
+        assert_eq!(print_chunks(&[]), None);
+        assert_eq!(print_chunks(&["--print-chunks"]), Some(ChunkLogFormat::Text));
+        assert_eq!(
+            print_chunks(&["--print-chunks=json"]),
+            Some(ChunkLogFormat::Json)
+        );
+    }
+
+    #[test]
+    fn merge_command_flag() {
+        let CliCommand::Merge {
+            merge_or_solve: MergeOrSolveArgs { merge_command, .. },
+            ..
+        } = CliArgs::parse_from([
+            "mergiraf",
+            "merge",
+            "--semistructured=external",
+            "--merge-command=my-merger %O %A %B",
+            "foo.c",
+            "bar.c",
+            "baz.c",
+        ])
+        .command
+        else {
+            unreachable!("`mergiraf merge` should invoke the `Merge` subcommand")
+        };
+        assert_eq!(merge_command.as_deref(), Some("my-merger %O %A %B"));
+
+        // required by the external algorithm
+        assert!(
+            CliArgs::try_parse_from([
+                "mergiraf",
+                "merge",
+                "--semistructured=external",
+                "foo.c",
+                "bar.c",
+                "baz.c"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn print_stats_flag() {
+        let CliCommand::Merge {
+            merge_or_solve: MergeOrSolveArgs { print_stats, .. },
+            ..
+        } = CliArgs::parse_from([
+            "mergiraf",
+            "merge",
+            "--semistructured=diff3",
+            "--print-stats",
+            "foo.c",
+            "bar.c",
+            "baz.c",
+        ])
+        .command
+        else {
+            unreachable!("`mergiraf merge` should invoke the `Merge` subcommand")
+        };
+        assert!(print_stats);
+    }
+
+    #[test]
+    fn semistructured_flags() {
+        let flag = |args: &[&str]| {
+            let CliCommand::Merge { merge_or_solve, .. } = CliArgs::parse_from(
+                ["mergiraf", "merge"]
+                    .iter()
+                    .chain(args)
+                    .chain(&["foo.c", "bar.c", "baz.c"]),
+            )
+            .command
+            else {
+                unreachable!("`mergiraf merge` should invoke the `Merge` subcommand")
+            };
+            merge_or_solve.semistructured_flag()
+        };
+        assert_eq!(flag(&["--semistructured=diff3"]), None);
+        assert_eq!(flag(&["--truncation=nodes:10"]), Some("--truncation"));
+        assert_eq!(
+            flag(&["--reference-check=conflict"]),
+            Some("--reference-check")
+        );
+        assert_eq!(flag(&["--print-stats"]), Some("--print-stats"));
+
+        // only make sense in semistructured mode
+        assert!(check_semistructured_flag(Some("--truncation"), None).is_err());
+        assert!(
+            check_semistructured_flag(Some("--truncation"), Some(TextualMergeStrategy::Diff3))
+                .is_ok()
+        );
+        assert!(check_semistructured_flag(None, None).is_ok());
+    }
+
+    #[test]
+    fn semistructured_flags_with_configuration_file() {
+        let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
+        let repo_path = repo_dir.path();
+        fs::create_dir(repo_path.join(".git")).unwrap();
+        let base = "class A {\n    void f() {\n        g(0);\n    }\n}\n";
+        let left = "class A {\n    void f() {\n        g(1);\n    }\n}\n";
+        for (name, contents) in [("Base.java", base), ("Left.java", left), ("Right.java", base)] {
+            fs::write(repo_path.join(name), contents).unwrap();
+        }
+        let path = |name| repo_path.join(name).to_str().unwrap().to_owned();
+        let merge = || {
+            real_main(CliArgs::parse_from([
+                "mergiraf".to_owned(),
+                "merge".to_owned(),
+                path("Base.java"),
+                path("Left.java"),
+                path("Right.java"),
+                "--truncation=lines:1".to_owned(),
+                "-o".to_owned(),
+                path("Merged.java"),
+            ]))
+        };
+
+        // semistructured merge is neither enabled on the command line nor in the configuration
+        assert!(merge().is_err());
+
+        // the configuration file enables it
+        fs::write(
+            repo_path.join(mergiraf::config::CONFIG_FILE_NAME),
+            "semistructured = \"diff3\"\n",
+        )
+        .unwrap();
+        assert_eq!(merge(), Ok(0));
+        assert_eq!(fs::read_to_string(path("Merged.java")).unwrap(), left);
+    }
+
+    #[test]
+    fn configuration_file_precedence() {
+        let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
+        let repo_path = repo_dir.path();
+        fs::create_dir(repo_path.join(".git")).unwrap();
+        fs::write(
+            repo_path.join(mergiraf::config::CONFIG_FILE_NAME),
+            "compact = true\ntimeout = 100\n",
+        )
+        .unwrap();
+        let path = repo_path.join("file.json");
+        let cli_options = MergeOptions {
+            timeout: Some(200),
+            ..Default::default()
+        };
+
+        // command-line flags take precedence over the configuration file
+        let options = merge_options(cli_options, &path, None, false);
+        assert_eq!(options.timeout, Some(200));
+        assert_eq!(options.compact, Some(true));
+
+        // the configuration file is ignored when Mergiraf is disabled with `mergiraf=0`
+        let options = merge_options(cli_options, &path, None, true);
+        assert_eq!(options, cli_options);
+    }
+
+    #[test]
+    fn no_fast_flag() {
+        let fast = |args: &[&str]| {
+            let CliCommand::Merge { fast, no_fast, .. } = CliArgs::parse_from(
+                ["mergiraf", "merge"]
+                    .iter()
+                    .chain(args)
+                    .chain(&["foo.c", "bar.c", "baz.c"]),
+            )
+            .command
+            else {
+                unreachable!("`mergiraf merge` should invoke the `Merge` subcommand")
+            };
+            fast_option(fast, no_fast)
+        };
+        assert_eq!(fast(&[]), None);
+        assert_eq!(fast(&["--fast"]), Some(true));
+        assert_eq!(fast(&["--no-fast"]), Some(false));
+        // the last flag wins
+        assert_eq!(fast(&["--fast", "--no-fast"]), Some(false));
+        assert_eq!(fast(&["--no-fast", "--fast"]), Some(true));
+
+        // `--no-fast` overrides `fast = true` in the configuration file
+        let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
+        let repo_path = repo_dir.path();
+        fs::create_dir(repo_path.join(".git")).unwrap();
+        fs::write(repo_path.join(mergiraf::config::CONFIG_FILE_NAME), "fast = true\n").unwrap();
+        let path = repo_path.join("file.json");
+        let options = |fast| {
+            merge_options(
+                MergeOptions {
+                    fast,
+                    ..Default::default()
+                },
+                &path,
+                None,
+                false,
+            )
+            .fast
+        };
+        assert_eq!(options(fast(&[])), Some(true));
+        assert_eq!(options(fast(&["--no-fast"])), Some(false));
+    }
+
     #[test]
     fn keep_backup_flag() {
         // `true` when nothing passed
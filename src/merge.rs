@@ -15,27 +15,32 @@ use crate::{
     line_based::{
         LINE_BASED_METHOD, line_based_merge, line_based_merge_with_duplicate_signature_detection,
     },
-    resolve_merge, structured_merge, TextualMergeStrategy,
+    resolve_merge,
+    settings::{ChunkLogFormat, ReferenceCheck, SemistructuredSettings},
+    structured::warn_about_references_to_modified,
+    structured_merge,
 };
 
 /// Merge the files textually and then attempt to merge any conflicts
 /// in a structured way (see [`structured_merge`]).
 /// If there are still conflicts and a full merge is requested, a fully structured
-/// merge (independently of the textual merge) is attempted
+/// merge (independently of the textual merge) is attempted.
+///
+/// [`crate::Merger`] offers the same with a builder, and is the preferred interface for libraries.
 #[allow(clippy::too_many_arguments)]
 pub fn line_merge_and_structured_resolution(
-    contents_base: &'static str,
-    contents_left: &'static str,
-    contents_right: &'static str,
-    fname_base: &'static Path,
-    settings: DisplaySettings<'static>,
+    contents_base: &str,
+    contents_left: &str,
+    contents_right: &str,
+    fname_base: &Path,
+    settings: DisplaySettings,
     full_merge: bool,
     attempts_cache: Option<&AttemptsCache>,
-    debug_dir: Option<&'static Path>,
+    debug_dir: Option<&Path>,
     timeout: Duration,
     language: Option<&str>,
-    print_chunks: bool,
-    semistructured: Option<TextualMergeStrategy>,
+    print_chunks: Option<ChunkLogFormat>,
+    semistructured: Option<SemistructuredSettings>,
 ) -> MergeResult {
     let Ok(lang_profile) = LangProfile::find_by_filename_or_name(fname_base, language) else {
         return line_based_merge(contents_base, contents_left, contents_right, &settings);
@@ -87,20 +92,20 @@ pub fn line_merge_and_structured_resolution(
 /// any of them finds a conflict-free merge without any additional issues.
 #[allow(clippy::too_many_arguments)]
 pub fn cascading_merge(
-    contents_base: &'static str,
-    contents_left: &'static str,
-    contents_right: &'static str,
+    contents_base: &str,
+    contents_left: &str,
+    contents_right: &str,
     lang_profile: &'static LangProfile,
-    settings: DisplaySettings<'static>,
+    settings: DisplaySettings,
     full_merge: bool,
-    debug_dir: Option<&'static Path>,
+    debug_dir: Option<&Path>,
     timeout: Duration,
-    print_chunks: bool,
-    semistructured: Option<TextualMergeStrategy>,
+    print_chunks: Option<ChunkLogFormat>,
+    semistructured: Option<SemistructuredSettings>,
 ) -> Vec<MergeResult> {
     // first attempt: try to merge as line-based
     let start = Instant::now();
-    let (parsed_conflicts, line_based_merge) = line_based_merge_with_duplicate_signature_detection(
+    let (parsed_conflicts, mut line_based_merge) = line_based_merge_with_duplicate_signature_detection(
         contents_base,
         contents_left,
         contents_right,
@@ -108,18 +113,76 @@ pub fn cascading_merge(
         lang_profile,
     );
     debug!("line-based merge took {:?}", start.elapsed());
-    if line_based_merge.conflict_count == 0 && !line_based_merge.has_additional_issues {
+    let clean_line_based_merge =
+        line_based_merge.conflict_count == 0 && !line_based_merge.has_additional_issues;
+    // a clean textual merge can still combine a new element with a modification
+    // of an element it references, which only the structured merge checks
+    let reference_check = semistructured
+        .as_ref()
+        .map_or(ReferenceCheck::Off, |semistructured| semistructured.reference_check);
+    if clean_line_based_merge && reference_check == ReferenceCheck::Off {
         return vec![line_based_merge];
     }
 
+    // the merge keeps running in the background after a timeout, so it needs its own copy of the inputs
+    let (contents_base, contents_left, contents_right) = (
+        contents_base.to_owned(),
+        contents_left.to_owned(),
+        contents_right.to_owned(),
+    );
+    let settings = settings.into_owned();
+    let debug_dir = debug_dir.map(Path::to_path_buf);
     let (tx, rx) = oneshot::channel();
 
     thread::spawn(move || {
+        let debug_dir = debug_dir.as_deref();
         let mut merges = Vec::new();
 
+        if clean_line_based_merge {
+            // check the references of the clean merge, which is kept unless they conflict
+            let semistructured =
+                semistructured.expect("references are only checked in semistructured mode");
+            if reference_check == ReferenceCheck::Warn {
+                if let Err(err) = warn_about_references_to_modified(
+                    &contents_base,
+                    &contents_left,
+                    &contents_right,
+                    lang_profile,
+                    semistructured,
+                ) {
+                    debug!("could not check references in the merge: {err}");
+                }
+            } else {
+                match structured_merge(
+                    &contents_base,
+                    &contents_left,
+                    &contents_right,
+                    None,
+                    &settings,
+                    lang_profile,
+                    debug_dir,
+                    print_chunks,
+                    Some(semistructured),
+                ) {
+                    // conflicts unrelated to references, such as those in truncated nodes,
+                    // don't matter since the line-based merge is clean
+                    Ok(checked_merge)
+                        if (checked_merge.semistructured_stats.as_ref())
+                            .is_some_and(|stats| stats.reference_conflicts > 0) =>
+                    {
+                        merges.push(checked_merge);
+                    }
+                    Ok(_) => {}
+                    Err(err) => debug!("could not check references in the merge: {err}"),
+                }
+            }
+            let _ = tx.send(merges);
+            return;
+        }
+
         // second attempt: to solve the conflicts from the line-based merge
         if !line_based_merge.has_additional_issues {
-            let solved_merge = resolve_merge(&parsed_conflicts, &settings, lang_profile, debug_dir, print_chunks, semistructured);
+            let solved_merge = resolve_merge(&parsed_conflicts, &settings, lang_profile, debug_dir, print_chunks, semistructured.clone());
 
             match solved_merge {
                 Ok(recovered_merge) => {
@@ -139,9 +202,9 @@ pub fn cascading_merge(
         if full_merge || line_based_merge.has_additional_issues {
             // third attempt: full-blown structured merge
             let structured_merge = structured_merge(
-                contents_base,
-                contents_left,
-                contents_right,
+                &contents_base,
+                &contents_left,
+                &contents_right,
                 None,
                 &settings,
                 lang_profile,
@@ -172,10 +235,16 @@ pub fn cascading_merge(
         }
     };
 
+    if clean_line_based_merge && !merges.is_empty() {
+        // the references of the line-based merge conflict
+        line_based_merge.has_additional_issues = true;
+    }
     merges.push(line_based_merge);
     merges
 }
 
+// only returned by `select_best_merge`, so boxing the merges wouldn't save anything
+#[allow(clippy::large_enum_variant)]
 enum LineBasedAndBestAre {
     TheSame(MergeResult),
     NotTheSame {
@@ -225,3 +294,70 @@ fn select_best_merge(mut merges: Vec<MergeResult>) -> LineBasedAndBestAre {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::TextualMergeStrategy;
+
+    #[test]
+    fn references_of_clean_line_based_merge() {
+        let base = "class A {\n    int foo() {\n        return 1;\n    }\n}\n";
+        let left = "class A {\n    int foo() {\n        return 2;\n    }\n}\n";
+        let right = "class A {\n    int foo() {\n        return 1;\n    }\n\n    int bar() {\n        return foo();\n    }\n}\n";
+
+        let merge = |reference_check| {
+            line_merge_and_structured_resolution(
+                base,
+                left,
+                right,
+                Path::new("A.java"),
+                DisplaySettings::default(),
+                true,
+                None,
+                None,
+                Duration::from_secs(10),
+                None,
+                None,
+                Some(SemistructuredSettings {
+                    reference_check,
+                    ..SemistructuredSettings::new(TextualMergeStrategy::Diff3)
+                }),
+            )
+        };
+
+        let warned = merge(ReferenceCheck::Warn);
+        assert_eq!(warned.method, LINE_BASED_METHOD);
+        assert_eq!(warned.conflict_count, 0);
+
+        let checked = merge(ReferenceCheck::Conflict);
+        assert_ne!(checked.method, LINE_BASED_METHOD);
+        assert_eq!(checked.conflict_count, 1);
+
+        // the imports added on both sides are ambiguous, which conflicts in the semistructured
+        // merge but has nothing to do with references: the clean line-based merge is kept
+        let base = "import java.io.File;\nimport java.util.Map;\n\nclass Main {\n}\n";
+        let left = "import java.io.File;\nimport java.util.List;\nimport java.util.Map;\n\nclass Main {\n}\n";
+        let right = "import java.io.File;\nimport java.util.Map;\nimport staticutils.List;\n\nclass Main {\n}\n";
+        let unrelated = line_merge_and_structured_resolution(
+            base,
+            left,
+            right,
+            Path::new("Main.java"),
+            DisplaySettings::default(),
+            true,
+            None,
+            None,
+            Duration::from_secs(10),
+            None,
+            None,
+            Some(SemistructuredSettings {
+                reference_check: ReferenceCheck::Conflict,
+                ..SemistructuredSettings::new(TextualMergeStrategy::Diff3)
+            }),
+        );
+        assert_eq!(unrelated.method, LINE_BASED_METHOD);
+        assert_eq!(unrelated.conflict_count, 0);
+    }
+}
@@ -1,6 +1,6 @@
-use std::{path::Path, thread, time::Instant};
+use std::{fs, path::Path, thread, time::Instant};
 
-use log::debug;
+use log::{debug, error};
 
 use crate::{
     ast::AstNode,
@@ -8,11 +8,12 @@ use crate::{
     class_mapping::{ClassMapping, RevNode},
     line_based::line_based_merge_parsed,
     matching::Matching,
+    merge_result::SemistructuredStats,
     merged_tree::MergedTree,
     pcs::Revision,
-    settings::DisplaySettings,
-    textual_merge_strategy::TextualMergeStrategy,
-    tree_builder::TreeBuilder,
+    reference_check::check_references,
+    settings::{DisplaySettings, SemistructuredSettings},
+    tree_builder::{ChunkLog, TreeBuilder, UntruncatedArenas, UntruncatedRevisions},
     tree_matcher::{DetailedMatching, TreeMatcher},
     visualizer::write_matching_to_dotty_file,
 };
@@ -40,34 +41,55 @@ pub fn three_way_merge<'a>(
     auxiliary_matcher: &TreeMatcher,
     settings: &DisplaySettings<'a>,
     debug_dir: Option<&Path>,
-    print_chunks: bool,
-    semistructured: Option<TextualMergeStrategy>,
+    chunk_log: Option<ChunkLog>,
+    semistructured: Option<SemistructuredSettings>,
 ) -> (MergedTree<'a>, ClassMapping<'a>) {
-    // match all pairs of revisions
-    let (base_left_matching, base_right_matching, left_right_matching) = generate_matchings(
+    let (merged_tree, class_mapping, _, _) = three_way_merge_with_stats(
         base,
         left,
         right,
         initial_matchings,
         primary_matcher,
         auxiliary_matcher,
+        settings,
         debug_dir,
+        chunk_log,
+        semistructured,
     );
+    (merged_tree, class_mapping)
+}
 
-    // create a classmapping
-    let mut class_mapping = create_class_mapping(
-        &base_left_matching,
-        &base_right_matching,
-        &left_right_matching,
-    );
-
-    class_mapping.populate_from_ast(Revision::Base, base);
-    class_mapping.populate_from_ast(Revision::Left, left);
-    class_mapping.populate_from_ast(Revision::Right, right);
+/// Same as [`three_way_merge`], also returning statistics about the truncated nodes
+/// and the time spent in each phase of the algorithm, as well as the chunks of the
+/// merged tree if they were requested in the JSON format
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn three_way_merge_with_stats<'a>(
+    base: &'a AstNode<'a>,
+    left: &'a AstNode<'a>,
+    right: &'a AstNode<'a>,
+    initial_matchings: Option<&(Matching<'a>, Matching<'a>)>,
+    primary_matcher: &TreeMatcher,
+    auxiliary_matcher: &TreeMatcher,
+    settings: &DisplaySettings<'a>,
+    debug_dir: Option<&Path>,
+    chunk_log: Option<ChunkLog>,
+    semistructured: Option<SemistructuredSettings>,
+) -> (MergedTree<'a>, ClassMapping<'a>, SemistructuredStats, Vec<String>) {
+    let mut stats = SemistructuredStats::default();
+    let mut json_chunks = Vec::new();
 
-    if semistructured.is_some(){
-        class_mapping.unify_concurrent_additions();
-    }
+    let start = Instant::now();
+    let class_mapping = match_revisions(
+        base,
+        left,
+        right,
+        initial_matchings,
+        primary_matcher,
+        auxiliary_matcher,
+        debug_dir,
+        semistructured.is_some(),
+        &mut stats,
+    );
 
     // convert all the trees to PCS triples
     let (changeset, base_changeset) =
@@ -85,14 +107,87 @@ pub fn three_way_merge<'a>(
         &base_changeset,
         &cleaned_changeset,
         settings,
-        print_chunks,
-        semistructured,
+        chunk_log,
+        semistructured.clone(),
+        &mut stats,
+        &mut json_chunks,
     );
+    stats.tree_building_time = start.elapsed() - stats.matching_time;
 
     // post-process to highlight signature conflicts
+    let start = Instant::now();
     let postprocessed_tree = postprocess_tree(merged_tree, &class_mapping);
 
-    (postprocessed_tree, class_mapping)
+    // check for new elements referencing elements modified on the other side
+    let postprocessed_tree = match semistructured {
+        Some(settings) => {
+            let (checked_tree, reference_conflicts) = check_references(
+                postprocessed_tree,
+                base,
+                left,
+                right,
+                &class_mapping,
+                settings.reference_check,
+            );
+            stats.reference_conflicts = reference_conflicts;
+            checked_tree
+        }
+        None => postprocessed_tree,
+    };
+    stats.postprocessing_time = start.elapsed();
+
+    (postprocessed_tree, class_mapping, stats, json_chunks)
+}
+
+/// Matches all pairs of revisions and creates the class mapping resulting from those matchings.
+/// In semistructured mode, the truncated nodes which were modified on one side are also unified
+/// with their base counterparts, as well as the nodes added on both sides.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn match_revisions<'a>(
+    base: &'a AstNode<'a>,
+    left: &'a AstNode<'a>,
+    right: &'a AstNode<'a>,
+    initial_matchings: Option<&(Matching<'a>, Matching<'a>)>,
+    primary_matcher: &TreeMatcher,
+    auxiliary_matcher: &TreeMatcher,
+    debug_dir: Option<&Path>,
+    semistructured: bool,
+    stats: &mut SemistructuredStats,
+) -> ClassMapping<'a> {
+    // match all pairs of revisions
+    let start = Instant::now();
+    let (base_left_matching, base_right_matching, left_right_matching) = generate_matchings(
+        base,
+        left,
+        right,
+        initial_matchings,
+        primary_matcher,
+        auxiliary_matcher,
+        debug_dir,
+    );
+    stats.matching_time = start.elapsed();
+
+    // create a classmapping
+    let mut class_mapping = create_class_mapping(
+        &base_left_matching,
+        &base_right_matching,
+        &left_right_matching,
+    );
+
+    class_mapping.populate_from_ast(Revision::Base, base);
+    class_mapping.populate_from_ast(Revision::Left, left);
+    class_mapping.populate_from_ast(Revision::Right, right);
+
+    if semistructured {
+        class_mapping.unify_modified_truncated_nodes(Revision::Left);
+        class_mapping.unify_modified_truncated_nodes(Revision::Right);
+        class_mapping.unify_retyped_truncated_nodes(Revision::Left);
+        class_mapping.unify_retyped_truncated_nodes(Revision::Right);
+        class_mapping.unify_renamed_truncated_nodes(Revision::Left);
+        class_mapping.unify_renamed_truncated_nodes(Revision::Right);
+        class_mapping.unify_concurrent_additions();
+    }
+    class_mapping
 }
 
 fn generate_matchings<'a>(
@@ -143,8 +238,16 @@ fn generate_matchings<'a>(
     let left_right_matching = auxiliary_matcher.match_trees(left, right, Some(&composed_matching));
     debug!("matching all three pairs took {:?}", start.elapsed());
 
-    // save the matchings for debugging purposes
+    // save the matchings for debugging purposes, along with the parameters used to compute them
     if let Some(debug_dir) = debug_dir {
+        let parameters = format!("primary: {primary_matcher:?}\nauxiliary: {auxiliary_matcher:?}\n");
+        let path = debug_dir.join("matchers.txt");
+        if let Err(err) = fs::write(&path, parameters) {
+            error!(
+                "Mergiraf: Could not write the parameters of the matchers to {}: {err}",
+                path.display()
+            );
+        }
         thread::scope(|s| {
             s.spawn(|| {
                 write_matching_to_dotty_file(
@@ -300,12 +403,25 @@ fn build_tree<'a>(
     base_changeset: &ChangeSet<'a>,
     cleaned_changeset: &ChangeSet<'a>,
     settings: &DisplaySettings<'a>,
-    print_chunks: bool,
-    semistructured: Option<TextualMergeStrategy>,
+    chunk_log: Option<ChunkLog>,
+    semistructured: Option<SemistructuredSettings>,
+    stats: &mut SemistructuredStats,
+    json_chunks: &mut Vec<String>,
 ) -> MergedTree<'a> {
     let start: Instant = Instant::now();
-    let tree_builder = TreeBuilder::new(cleaned_changeset, base_changeset, class_mapping, settings, print_chunks, semistructured);
-    let merged_tree = tree_builder.build_tree().unwrap_or_else(|_| {
+    let arenas = UntruncatedArenas::default();
+    let untruncated = UntruncatedRevisions::new(&arenas);
+    let tree_builder = TreeBuilder::new(
+        cleaned_changeset,
+        base_changeset,
+        class_mapping,
+        settings,
+        chunk_log,
+        semistructured.clone(),
+        &untruncated,
+    );
+    let merged_tree = tree_builder.build_tree(json_chunks).unwrap_or_else(|err| {
+        debug!("could not build the merged tree, falling back on a line-based merge: {err}");
         let line_based = line_based_merge_parsed(base.source, left.source, right.source, settings);
         MergedTree::LineBasedMerge {
             node: class_mapping.map_to_leader(RevNode::new(Revision::Base, base)),
@@ -313,6 +429,9 @@ fn build_tree<'a>(
         }
     });
     debug!("constructing the merged tree took {:?}", start.elapsed());
+    if semistructured.is_some() {
+        tree_builder.count_truncated_nodes(&merged_tree, stats);
+    }
 
     merged_tree
 }
@@ -322,9 +441,11 @@ fn postprocess_tree<'a>(
     class_mapping: &ClassMapping<'a>,
 ) -> MergedTree<'a> {
     let start: Instant = Instant::now();
-    let postprocessed_tree = merged_tree.post_process_for_duplicate_signatures(class_mapping);
+    let postprocessed_tree = merged_tree
+        .post_process_for_duplicate_signatures(class_mapping)
+        .post_process_for_ambiguous_imports(class_mapping);
     debug!(
-        "post-processing the merged tree for signature conflicts took {:?}",
+        "post-processing the merged tree for signature conflicts and ambiguous imports took {:?}",
         start.elapsed()
     );
 
@@ -334,7 +455,8 @@ fn postprocess_tree<'a>(
 #[cfg(test)]
 mod tests {
     use crate::{
-        settings::DisplaySettings,
+        TextualMergeStrategy, TruncationPolicy,
+        settings::{DisplaySettings, ReferenceCheck},
         test_utils::{ctx, json_matchers},
     };
 
@@ -361,7 +483,7 @@ mod tests {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -391,7 +513,7 @@ mod tests {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -431,7 +553,7 @@ mod tests {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -471,7 +593,7 @@ mod tests {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -511,7 +633,7 @@ mod tests {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -551,7 +673,7 @@ mod tests {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -580,7 +702,7 @@ mod tests {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -588,6 +710,42 @@ mod tests {
         assert_eq!(pretty_printed, "{\"a\": 1, \"b\": 2}");
     }
 
+    #[test]
+    fn commutative_merge_keeps_order() {
+        // run the merge multiple times, as a merge relying on the iteration order
+        // of a hash set would only give a different order from time to time
+        for _ in 0..10 {
+            let ctx = ctx();
+
+            let base = ctx.parse_json("{\"x\": 0}");
+            let left = ctx.parse_json("{\"x\": 0, \"d\": 4, \"c\": 3, \"b\": 2}");
+            let right = ctx.parse_json("{\"x\": 0, \"f\": 6, \"e\": 5}");
+
+            let (primary_matcher, auxiliary_matcher) = json_matchers();
+
+            let settings = DisplaySettings::default();
+
+            let (merged_tree, class_mapping) = three_way_merge(
+                base,
+                left,
+                right,
+                None,
+                &primary_matcher,
+                &auxiliary_matcher,
+                &settings,
+                None,
+                None,
+                None,
+            );
+
+            let pretty_printed = merged_tree.pretty_print(&class_mapping, &settings);
+            assert_eq!(
+                pretty_printed,
+                "{\"x\": 0, \"d\": 4, \"c\": 3, \"b\": 2, \"f\": 6, \"e\": 5}"
+            );
+        }
+    }
+
     #[test]
     fn commutative_conflict_double_delete() {
         let ctx = ctx();
@@ -609,7 +767,7 @@ mod tests {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -638,7 +796,7 @@ mod tests {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -686,7 +844,7 @@ mod tests {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -772,7 +930,7 @@ fn baz() {
             &auxiliary_matcher,
             &settings,
             None,
-            false,
+            None,
             None,
         );
 
@@ -792,4 +950,221 @@ fn baz() {
         let pretty_printed = merged_tree.pretty_print(&class_mapping, &settings);
         assert_eq!(pretty_printed, expected);
     }
+
+    #[test]
+    fn textual_merge_conflicts_are_real_conflicts() {
+        let ctx = ctx();
+
+        let base = ctx.parse_java_semistructured(
+            "class A {\n    void f() {\n        g(0);\n        h();\n    }\n}\n",
+        );
+        let left = ctx.parse_java_semistructured(
+            "class A {\n    void f() {\n        g(1);\n        h();\n    }\n}\n",
+        );
+        let right = ctx.parse_java_semistructured(
+            "class A {\n    void f() {\n        g(2);\n        h();\n    }\n}\n",
+        );
+
+        let (primary_matcher, auxiliary_matcher) = json_matchers();
+
+        let settings = DisplaySettings {
+            conflict_marker_size: Some(9),
+            left_revision_name: Some("ours".into()),
+            base_revision_name: Some("ancestor".into()),
+            right_revision_name: Some("theirs".into()),
+            ..DisplaySettings::default()
+        };
+
+        let (merged_tree, class_mapping) = three_way_merge(
+            base,
+            left,
+            right,
+            None,
+            &primary_matcher,
+            &auxiliary_matcher,
+            &settings,
+            None,
+            None,
+            Some(SemistructuredSettings::new(TextualMergeStrategy::Diff3)),
+        );
+
+        let merged_text = merged_tree.to_merged_text(&class_mapping);
+        assert_eq!(merged_text.count_conflicts(), 1);
+        assert_eq!(
+            merged_text.render(&settings),
+            "\
+class A {
+    void f() {
+<<<<<<<<< ours
+        g(1);
+||||||||| ancestor
+        g(0);
+=========
+        g(2);
+>>>>>>>>> theirs
+        h();
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn nodes_truncated_in_some_revisions_only() {
+        let ctx = ctx();
+        let policy = Some(TruncationPolicy::MinLines(3));
+
+        let base = ctx.parse_with_truncation(
+            "a.java",
+            "class A {\n    int x = 0;\n    void f() {\n        g(0);\n    }\n}\n",
+            policy,
+        );
+        // the method is too short to be truncated in this revision
+        let left = ctx.parse_with_truncation(
+            "a.java",
+            "class A {\n    int x = 0;\n    void f() { g(1); }\n}\n",
+            policy,
+        );
+        let right = ctx.parse_with_truncation(
+            "a.java",
+            "class A {\n    int x = 1;\n    void f() {\n        g(0);\n    }\n}\n",
+            policy,
+        );
+        let base_method = base[0][2][2];
+        let left_method = left[0][2][2];
+        assert!(base_method.is_truncated());
+        assert!(left_method.is_truncation_point() && !left_method.is_truncated());
+
+        let (primary_matcher, auxiliary_matcher) = json_matchers();
+
+        let (merged_tree, class_mapping) = three_way_merge(
+            base,
+            left,
+            right,
+            None,
+            &primary_matcher,
+            &auxiliary_matcher,
+            &DisplaySettings::default(),
+            None,
+            None,
+            Some(SemistructuredSettings {
+                strategy: TextualMergeStrategy::Diff3,
+                truncation_policy: TruncationPolicy::MinLines(3),
+                merge_command: None,
+                reference_check: ReferenceCheck::Off,
+            }),
+        );
+
+        let merged_text = merged_tree.to_merged_text(&class_mapping);
+        assert_eq!(merged_text.count_conflicts(), 0);
+        assert_eq!(
+            merged_text.render(&DisplaySettings::default()),
+            "class A {\n    int x = 1;\n    void f() { g(1); }\n}\n"
+        );
+    }
+
+    #[test]
+    fn external_strategy() {
+        fn merge(command: Option<&str>) -> String {
+            let ctx = ctx();
+            let policy = Some(TruncationPolicy::MinLines(3));
+
+            let base = ctx.parse_with_truncation(
+                "a.java",
+                "class A {\n    void f() {\n        g(0);\n        h();\n        k(0);\n    }\n}\n",
+                policy,
+            );
+            let left = ctx.parse_with_truncation(
+                "a.java",
+                "class A {\n    void f() {\n        g(1);\n        h();\n        k(0);\n    }\n}\n",
+                policy,
+            );
+            let right = ctx.parse_with_truncation(
+                "a.java",
+                "class A {\n    void f() {\n        g(0);\n        h();\n        k(1);\n    }\n}\n",
+                policy,
+            );
+            assert!(base[0][2][1].is_truncated());
+
+            let (primary_matcher, auxiliary_matcher) = json_matchers();
+
+            let (merged_tree, class_mapping) = three_way_merge(
+                base,
+                left,
+                right,
+                None,
+                &primary_matcher,
+                &auxiliary_matcher,
+                &DisplaySettings::default(),
+                None,
+                None,
+                Some(SemistructuredSettings {
+                    strategy: TextualMergeStrategy::External,
+                    truncation_policy: TruncationPolicy::MinLines(3),
+                    merge_command: command.map(Into::into),
+                    reference_check: ReferenceCheck::Off,
+                }),
+            );
+
+            let merged_text = merged_tree.to_merged_text(&class_mapping);
+            assert_eq!(merged_text.count_conflicts(), 0);
+            merged_text.render(&DisplaySettings::default())
+        }
+
+        let merged =
+            "class A {\n    void f() {\n        g(1);\n        h();\n        k(1);\n    }\n}\n";
+
+        // the command leaves the left revision as it is
+        assert_eq!(
+            merge(Some("exit 0")),
+            "class A {\n    void f() {\n        g(1);\n        h();\n        k(0);\n    }\n}\n"
+        );
+
+        // the command fails, so diff3 merges the method instead
+        assert_eq!(merge(Some("exit 129")), merged);
+
+        // without a command, the right revision isn't silently dropped: the tree can't be built,
+        // and the whole file is merged line by line
+        assert_eq!(merge(None), merged);
+    }
+
+    #[test]
+    fn new_element_referencing_modified_element() {
+        fn count_conflicts(reference_check: ReferenceCheck) -> usize {
+            let ctx = ctx();
+
+            let base = ctx.parse_java_semistructured(
+                "class A {\n    int foo() {\n        return 1;\n    }\n}\n",
+            );
+            let left = ctx.parse_java_semistructured(
+                "class A {\n    int foo() {\n        return 2;\n    }\n}\n",
+            );
+            let right = ctx.parse_java_semistructured(
+                "class A {\n    int foo() {\n        return 1;\n    }\n\n    int bar() {\n        return foo();\n    }\n}\n",
+            );
+
+            let (primary_matcher, auxiliary_matcher) = json_matchers();
+
+            let (merged_tree, class_mapping) = three_way_merge(
+                base,
+                left,
+                right,
+                None,
+                &primary_matcher,
+                &auxiliary_matcher,
+                &DisplaySettings::default(),
+                None,
+                None,
+                Some(SemistructuredSettings {
+                    reference_check,
+                    ..SemistructuredSettings::new(TextualMergeStrategy::Diff3)
+                }),
+            );
+            merged_tree.to_merged_text(&class_mapping).count_conflicts()
+        }
+
+        assert_eq!(count_conflicts(ReferenceCheck::Off), 0);
+        assert_eq!(count_conflicts(ReferenceCheck::Warn), 0);
+        assert_eq!(count_conflicts(ReferenceCheck::Conflict), 1);
+    }
 }
@@ -1,3 +1,5 @@
+use std::{fmt::Display, time::Duration};
+
 use crate::attempts::Attempt;
 use log::info;
 
@@ -15,6 +17,62 @@ pub struct MergeResult {
     pub method: &'static str,
     /// Indicates that there are known conflicts which haven't been marked as such (such as duplicate signatures)
     pub has_additional_issues: bool,
+    /// How the truncated nodes were merged, if this is a semistructured merge
+    pub semistructured_stats: Option<SemistructuredStats>,
+    /// The chunks of the structured merge, one JSON object per chunk,
+    /// if they were requested in the JSON format (see [`crate::settings::ChunkLogFormat::Json`])
+    pub chunk_log: Vec<String>,
+}
+
+/// Statistics about a semistructured merge: how its truncated nodes were merged,
+/// and how long each phase of the merge took.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SemistructuredStats {
+    /// Truncated nodes which differ between both sides, merged (textually or structurally) without conflict
+    pub merged_cleanly: usize,
+    /// Truncated nodes which differ between both sides, whose merge has conflicts
+    pub merged_with_conflicts: usize,
+    /// Truncated nodes present in only one side, which did not need merging
+    pub one_sided: usize,
+    /// Truncated nodes identical in both sides (usually because neither side changed them),
+    /// which did not need merging either
+    pub unchanged: usize,
+    /// Conflicts added between elements added on one side and the elements modified on the
+    /// other side which they reference (see [`crate::settings::ReferenceCheck::Conflict`])
+    pub reference_conflicts: usize,
+    /// Time spent parsing the revisions
+    pub parsing_time: Duration,
+    /// Time spent matching the revisions with each other
+    pub matching_time: Duration,
+    /// Time spent building the merged tree, from the matchings
+    pub tree_building_time: Duration,
+    /// Time spent post-processing the merged tree
+    pub postprocessing_time: Duration,
+    /// Time spent rendering the merged tree and checking the output
+    pub rendering_time: Duration,
+}
+
+impl SemistructuredStats {
+    /// The number of truncated nodes in the merged tree
+    pub fn truncated_nodes(&self) -> usize {
+        self.merged_cleanly + self.merged_with_conflicts + self.one_sided + self.unchanged
+    }
+}
+
+impl Display for SemistructuredStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "truncated nodes: {}", self.truncated_nodes())?;
+        writeln!(f, "  merged cleanly: {}", self.merged_cleanly)?;
+        writeln!(f, "  merged with conflicts: {}", self.merged_with_conflicts)?;
+        writeln!(f, "  present in one side only: {}", self.one_sided)?;
+        writeln!(f, "  identical in both sides: {}", self.unchanged)?;
+        writeln!(f, "reference conflicts: {}", self.reference_conflicts)?;
+        writeln!(f, "parsing: {:?}", self.parsing_time)?;
+        writeln!(f, "matching: {:?}", self.matching_time)?;
+        writeln!(f, "tree building: {:?}", self.tree_building_time)?;
+        writeln!(f, "post-processing: {:?}", self.postprocessing_time)?;
+        write!(f, "rendering: {:?}", self.rendering_time)
+    }
 }
 
 impl MergeResult {
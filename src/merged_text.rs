@@ -405,6 +405,8 @@ impl<'a> MergedText<'a> {
             conflict_mass: self.conflict_mass(),
             method,
             has_additional_issues: false,
+            semistructured_stats: None,
+            chunk_log: Vec::new(),
         }
     }
 }
@@ -413,7 +415,7 @@ impl<'a> MergedText<'a> {
 mod tests {
     use super::*;
 
-    fn merged(contents: &str) -> MergeSection {
+    fn merged(contents: &str) -> MergeSection<'_> {
         MergeSection::Merged(contents.into())
     }
 
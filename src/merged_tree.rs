@@ -450,10 +450,46 @@ impl<'a> MergedTree<'a> {
                         }
                     })
             }
-            MergedTree::LineBasedMerge { .. } | MergedTree::TextuallyMerged { .. } => {
+            MergedTree::LineBasedMerge { .. } => {
                 // See above
                 true
             }
+            MergedTree::TextuallyMerged {
+                node,
+                content,
+                has_conflict,
+            } => {
+                // The merged content must be parsed back as a single node of the same type,
+                // with the same source as the textual merge at this revision.
+                let content_at_rev = if *has_conflict {
+                    match ParsedMerge::parse(content, &DisplaySettings::default()) {
+                        Ok(parsed) => Cow::from(parsed.reconstruct_revision(revision)),
+                        Err(_) => return false,
+                    }
+                } else {
+                    Cow::from(content)
+                };
+                if node.grammar_name() != other_node.grammar_name
+                    || content_at_rev.trim_end() != other_node.source.trim_end()
+                {
+                    return false;
+                }
+                // The text alone doesn't tell whether the merge preserved the structure of the
+                // node, so the children it parses to must also be found in the revisions.
+                let Some(child_kinds) = other_node.untruncated_child_kinds() else {
+                    return false;
+                };
+                let revision_child_kinds: Vec<_> = class_mapping
+                    .representatives(node)
+                    .iter()
+                    .filter_map(|rev_node| rev_node.node.untruncated_child_kinds())
+                    .collect();
+                child_kinds.iter().all(|kind| {
+                    revision_child_kinds
+                        .iter()
+                        .any(|kinds| kinds.contains(kind))
+                })
+            }
             MergedTree::Conflict { .. } => {
                 // Conflict is only allowed to appear as a child of another node, in which case
                 // it will be flattened above
@@ -514,17 +550,43 @@ impl<'a> MergedTree<'a> {
         class_mapping: &ClassMapping<'a>,
     ) -> Option<Signature<'b, 'a>> {
         let definition = match self {
+            // truncated nodes don't have any children to extract the signature from
+            MergedTree::ExactTree { node, .. } if node.as_representative().node.is_truncated() => {
+                return node.as_representative().node.signature();
+            }
+            MergedTree::TextuallyMerged { node, .. } => {
+                return Self::merged_signature_of_truncated_node(node, class_mapping);
+            }
             MergedTree::ExactTree { node, .. }
             | MergedTree::MixedTree { node, .. }
             | MergedTree::LineBasedMerge { node, .. } => node.signature_definition(),
-            MergedTree::TextuallyMerged { .. }
-            | MergedTree::Conflict { .. } 
-            | MergedTree::CommutativeChildSeparator { .. } => None,
+            MergedTree::Conflict { .. } | MergedTree::CommutativeChildSeparator { .. } => None,
         }?;
         let signature = definition.extract_signature_from_merged_node(self, class_mapping);
         Some(signature)
     }
 
+    /// The signature of a node whose revisions were merged textually, which is the one
+    /// resulting from a three-way merge of the signatures of its revisions.
+    /// If both sides changed it differently, there is none.
+    fn merged_signature_of_truncated_node<'b>(
+        node: &Leader<'a>,
+        class_mapping: &ClassMapping<'a>,
+    ) -> Option<Signature<'b, 'a>> {
+        let signature_at =
+            |rev| class_mapping.node_at_rev(node, rev).and_then(|rev_node| rev_node.signature());
+        let base = signature_at(Revision::Base);
+        let left = signature_at(Revision::Left);
+        let right = signature_at(Revision::Right);
+        if left == right || right == base {
+            left
+        } else if left == base {
+            right
+        } else {
+            None
+        }
+    }
+
     pub fn short_debug(&self) -> String {
         match self {
             MergedTree::ExactTree { node, revisions, .. } => {
@@ -591,7 +653,9 @@ mod test {
     use super::*;
 
     use crate::{
+        TextualMergeStrategy,
         merge_3dm::three_way_merge,
+        settings::SemistructuredSettings,
         test_utils::{ctx, json_matchers},
     };
 
@@ -612,7 +676,7 @@ mod test {
             &auxiliary_matcher,
             &DisplaySettings::default(),
             None,
-            false,
+            None,
             None,
         );
         assert_eq!(
@@ -629,4 +693,92 @@ Mixed(document:0…6@Base
 )"
         );
     }
+
+    #[test]
+    fn isomorphism_of_textually_merged_nodes() {
+        let ctx = ctx();
+        let base = ctx.parse_java_semistructured(
+            "class A {\n    void f() {\n        g(0);\n        h();\n        k(0);\n    }\n}\n",
+        );
+        let left = ctx.parse_java_semistructured(
+            "class A {\n    void f() {\n        g(1);\n        h();\n        k(0);\n    }\n}\n",
+        );
+        let right = ctx.parse_java_semistructured(
+            "class A {\n    void f() {\n        g(0);\n        h();\n        k(1);\n    }\n}\n",
+        );
+
+        let (primary_matcher, auxiliary_matcher) = json_matchers();
+        let (merged_tree, class_mapping) = three_way_merge(
+            base,
+            left,
+            right,
+            None,
+            &primary_matcher,
+            &auxiliary_matcher,
+            &DisplaySettings::default(),
+            None,
+            None,
+            Some(SemistructuredSettings::new(TextualMergeStrategy::Diff3)),
+        );
+
+        let rendered = ctx.parse_java_semistructured(
+            "class A {\n    void f() {\n        g(1);\n        h();\n        k(1);\n    }\n}\n",
+        );
+        assert!(merged_tree.isomorphic_to_source(rendered, Revision::Base, &class_mapping));
+
+        let broken = ctx.parse_java_semistructured(
+            "class A {\n    void f() {\n        g(1);\n        k(1);\n    }\n}\n",
+        );
+        assert!(!merged_tree.isomorphic_to_source(broken, Revision::Base, &class_mapping));
+    }
+
+    #[test]
+    fn structure_of_textually_merged_nodes() {
+        fn find_method<'a>(root: &'a AstNode<'a>) -> &'a AstNode<'a> {
+            root.dfs()
+                .find(|node| node.grammar_name == "method_declaration")
+                .expect("no method found")
+        }
+
+        let ctx = ctx();
+        let base =
+            ctx.parse_java_semistructured("class A {\n    void f() {\n        g(0);\n    }\n}\n");
+        let left =
+            ctx.parse_java_semistructured("class A {\n    void f() {\n        g(1);\n    }\n}\n");
+        let right =
+            ctx.parse_java_semistructured("class A {\n    int f() {\n        g(0);\n    }\n}\n");
+
+        let (primary_matcher, auxiliary_matcher) = json_matchers();
+        let (_, class_mapping) = three_way_merge(
+            base,
+            left,
+            right,
+            None,
+            &primary_matcher,
+            &auxiliary_matcher,
+            &DisplaySettings::default(),
+            None,
+            None,
+            Some(SemistructuredSettings::new(TextualMergeStrategy::Diff3)),
+        );
+        let leader = class_mapping.map_to_leader(RevNode::new(Revision::Base, find_method(base)));
+        let textually_merged = |content: &str| MergedTree::TextuallyMerged {
+            node: leader,
+            content: content.to_owned(),
+            has_conflict: false,
+        };
+
+        // all the children of the merged method can be found in some revision
+        let rendered =
+            ctx.parse_java_semistructured("class A {\n    int f() {\n        g(1);\n    }\n}\n");
+        let merged = textually_merged("int f() {\n        g(1);\n    }");
+        assert!(merged.isomorphic_to_source(find_method(rendered), Revision::Base, &class_mapping));
+
+        // none of the revisions declares any exception
+        let rendered = ctx.parse_java_semistructured(
+            "class A {\n    int f() throws E {\n        g(1);\n    }\n}\n",
+        );
+        let merged = textually_merged("int f() throws E {\n        g(1);\n    }");
+        assert!(!merged.isomorphic_to_source(find_method(rendered), Revision::Base, &class_mapping));
+    }
 }
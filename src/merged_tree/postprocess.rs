@@ -1,8 +1,9 @@
-use std::iter::zip;
+use std::{fmt::Display, hash::Hash, iter::zip};
 
 use itertools::Itertools;
 use log::debug;
 use regex::Regex;
+use rustc_hash::FxHashMap;
 
 use crate::{
     ast::AstNode,
@@ -23,19 +24,24 @@ impl<'a> MergedTree<'a> {
     ) -> Self {
         match self {
             Self::MixedTree { node, children, .. } => {
-                let recursively_processed = children
+                let recursively_processed: Vec<_> = children
                     .into_iter()
                     .map(|element| element.post_process_for_duplicate_signatures(class_mapping))
                     .collect();
                 let commutative_parent = node.commutative_parent_definition();
                 if let Some(commutative_parent) = commutative_parent {
-                    let highlighted = highlight_duplicate_signatures(
+                    let signatures = recursively_processed
+                        .iter()
+                        .map(|element| element.signature(class_mapping))
+                        .collect();
+                    let highlighted = highlight_duplicates(
                         &node,
-                        recursively_processed,
+                        &recursively_processed,
+                        signatures,
                         class_mapping,
                         commutative_parent,
                     );
-                    Self::new_mixed(node, highlighted)
+                    Self::new_mixed(node, highlighted.unwrap_or(recursively_processed))
                 } else {
                     Self::new_mixed(node, recursively_processed)
                 }
@@ -47,20 +53,170 @@ impl<'a> MergedTree<'a> {
             | Self::TextuallyMerged { .. } => self,
         }
     }
+
+    /// Transforms a merged tree by checking that no two import declarations introduce the same
+    /// simple name from different packages (such as `import a.List;` and `import b.List;`), which
+    /// happens when both sides add an import for the same name. If there are any, they are
+    /// grouped in the same location and a conflict is added there.
+    pub(crate) fn post_process_for_ambiguous_imports(
+        self,
+        class_mapping: &ClassMapping<'a>,
+    ) -> Self {
+        match self {
+            Self::MixedTree { node, children, .. } => {
+                let recursively_processed: Vec<_> = children
+                    .into_iter()
+                    .map(|element| element.post_process_for_ambiguous_imports(class_mapping))
+                    .collect();
+                let imported_names: Vec<_> = recursively_processed
+                    .iter()
+                    .map(|element| element.imported_name(class_mapping))
+                    .collect();
+                if let Some(commutative_parent) = node.commutative_parent_definition()
+                    && imported_names.iter().any(Option::is_some)
+                {
+                    let highlighted = highlight_duplicates(
+                        &node,
+                        &recursively_processed,
+                        imported_names,
+                        class_mapping,
+                        commutative_parent,
+                    );
+                    Self::new_mixed(node, highlighted.unwrap_or(recursively_processed))
+                } else {
+                    Self::new_mixed(node, recursively_processed)
+                }
+            }
+            Self::ExactTree { .. }
+            | Self::Conflict { .. }
+            | Self::LineBasedMerge { .. }
+            | Self::CommutativeChildSeparator { .. }
+            | Self::TextuallyMerged { .. } => self,
+        }
+    }
+
+    /// Transforms a merged tree by grouping elements added on one side with the elements
+    /// modified on the other side which they reference, adding a conflict there.
+    /// The supplied map associates both kinds of elements with the name of the modified element.
+    /// Only elements which are children of the same commutative parent are grouped.
+    /// The number of conflicts added is accumulated in `added_conflicts`.
+    pub(crate) fn post_process_for_references(
+        self,
+        class_mapping: &ClassMapping<'a>,
+        related: &FxHashMap<Leader<'a>, String>,
+        added_conflicts: &mut usize,
+    ) -> Self {
+        match self {
+            Self::MixedTree { node, children, .. } => {
+                let recursively_processed: Vec<_> = children
+                    .into_iter()
+                    .map(|element| {
+                        element.post_process_for_references(class_mapping, related, added_conflicts)
+                    })
+                    .collect();
+                let referenced_names: Vec<_> = recursively_processed
+                    .iter()
+                    .map(|element| match element {
+                        Self::ExactTree { node, .. }
+                        | Self::MixedTree { node, .. }
+                        | Self::LineBasedMerge { node, .. }
+                        | Self::TextuallyMerged { node, .. } => related.get(node).cloned(),
+                        Self::Conflict { .. } | Self::CommutativeChildSeparator { .. } => None,
+                    })
+                    .collect();
+                if let Some(commutative_parent) = node.commutative_parent_definition()
+                    && referenced_names.iter().any(Option::is_some)
+                {
+                    let highlighted = highlight_duplicates(
+                        &node,
+                        &recursively_processed,
+                        referenced_names,
+                        class_mapping,
+                        commutative_parent,
+                    );
+                    if let Some(highlighted) = &highlighted {
+                        let count_conflicts = |elements: &[Self]| {
+                            (elements.iter())
+                                .filter(|element| matches!(element, Self::Conflict { .. }))
+                                .count()
+                        };
+                        *added_conflicts += count_conflicts(highlighted)
+                            .saturating_sub(count_conflicts(&recursively_processed));
+                    }
+                    Self::new_mixed(node, highlighted.unwrap_or(recursively_processed))
+                } else {
+                    Self::new_mixed(node, recursively_processed)
+                }
+            }
+            Self::ExactTree { .. }
+            | Self::Conflict { .. }
+            | Self::LineBasedMerge { .. }
+            | Self::CommutativeChildSeparator { .. }
+            | Self::TextuallyMerged { .. } => self,
+        }
+    }
+
+    /// The simple name introduced by this element, if it is an import declaration
+    fn imported_name(&self, class_mapping: &ClassMapping<'a>) -> Option<String> {
+        match self {
+            Self::ExactTree { node, .. }
+            | Self::MixedTree { node, .. }
+            | Self::TextuallyMerged { node, .. } => {
+                let revisions = class_mapping.revision_set(node);
+                let representative = class_mapping.node_at_rev(node, revisions.any())?;
+                imported_simple_name(representative)
+            }
+            Self::Conflict { .. }
+            | Self::LineBasedMerge { .. }
+            | Self::CommutativeChildSeparator { .. } => None,
+        }
+    }
+}
+
+/// The simple name introduced by an import declaration, for the languages in which
+/// we detect ambiguous imports. Wildcard imports are ignored, as explicit imports take
+/// precedence over them, and so are Java's static imports, which can be overloaded.
+fn imported_simple_name<'a>(node: &'a AstNode<'a>) -> Option<String> {
+    let kotlin = match (node.lang_profile.name, node.grammar_name) {
+        ("Java", "import_declaration") => false,
+        ("Kotlin", "import") => true,
+        _ => return None,
+    };
+    // the tokens of the declaration, recovered by re-parsing it if it is truncated
+    let tokens = node.source_tokens()?;
+    let tokens: Vec<&str> = tokens
+        .iter()
+        .map(|token| token.trim())
+        .filter(|token| !token.is_empty() && !token.starts_with("//") && !token.starts_with("/*"))
+        .collect();
+    let ["import", path @ ..] = &tokens[..] else {
+        return None;
+    };
+    if kotlin {
+        if let Some(alias_position) = path.iter().position(|token| *token == "as") {
+            return path.get(alias_position + 1).map(|alias| (*alias).to_owned());
+        }
+    } else if path.first() == Some(&"static") {
+        return None;
+    }
+    let path = path.strip_suffix(&[";"]).unwrap_or(path);
+    match *path.last()? {
+        "*" => None,
+        name => Some(name.to_owned()),
+    }
 }
 
-/// Checks for duplicate signatures among the children of the given commutative parent.
-fn highlight_duplicate_signatures<'a>(
+/// Checks for duplicate keys (such as signatures) among the children of the given commutative parent.
+/// If there are any, returns the children with the elements with identical keys grouped in the
+/// same location, potentially with a conflict there.
+fn highlight_duplicates<'a, K: Eq + Hash + Display>(
     parent: &Leader<'a>,
-    elements: Vec<MergedTree<'a>>,
+    elements: &[MergedTree<'a>],
+    sigs: Vec<Option<K>>,
     class_mapping: &ClassMapping<'a>,
     commutative_parent: &CommutativeParent,
-) -> Vec<MergedTree<'a>> {
-    // compute signatures and index them
-    let sigs: Vec<_> = elements
-        .iter()
-        .map(|element| element.signature(class_mapping))
-        .collect();
+) -> Option<Vec<MergedTree<'a>>> {
+    // index the keys
     let sig_to_indices = sigs
         .iter()
         .enumerate()
@@ -75,13 +231,13 @@ fn highlight_duplicate_signatures<'a>(
         .for_each(|signature| {
             conflict_found = true;
             debug!(
-                "signature conflict found in {}: {}",
+                "duplicate found in {}: {}",
                 commutative_parent.parent_type(),
                 signature
             );
         });
     if !conflict_found {
-        return elements;
+        return None;
     }
 
     // find an example of a separator among the elements to merge
@@ -115,7 +271,7 @@ fn highlight_duplicate_signatures<'a>(
         sigs.len(),
         "Inconsistent length of signature arrays and elements array"
     );
-    for (idx, (element, sig)) in zip(&elements, &sigs).enumerate().rev() {
+    for (idx, (element, sig)) in zip(elements, &sigs).enumerate().rev() {
         match sig {
             None => {
                 let is_separator = is_separator(element, trimmed_separator);
@@ -209,7 +365,7 @@ fn highlight_duplicate_signatures<'a>(
             }
         }
     }
-    result
+    Some(result)
 }
 
 /// Check if a merged element is a separator of its commutative parent
@@ -252,12 +408,12 @@ fn merge_same_sigs<'a>(
     separator: Option<RevNode<'a>>,
     add_separator: AddSeparator,
 ) -> (Vec<MergedTree<'a>>, bool) {
-    if let &[first, second] = elements {
-        if isomorphic_merged_trees(first, second, class_mapping) {
-            // The two elements don't just have the same signature, they are actually isomorphic!
-            // So let's just deduplicate them.
-            return (vec![first.clone()], true);
-        }
+    if let &[first, second] = elements
+        && isomorphic_merged_trees(first, second, class_mapping)
+    {
+        // The two elements don't just have the same signature, they are actually isomorphic!
+        // So let's just deduplicate them.
+        return (vec![first.clone()], true);
     }
     let base = filter_by_revision(elements, Revision::Base, class_mapping);
     let left = filter_by_revision(elements, Revision::Left, class_mapping);
@@ -364,3 +520,33 @@ fn find_separator<'a>(
         })
         .find(|revnode| revnode.node.source.trim() == trimmed_separator)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::ctx;
+
+    #[test]
+    fn imported_names() {
+        let ctx = ctx();
+        let java = |source| {
+            let tree = ctx.parse_java(source);
+            let semistructured_tree = ctx.parse_java_semistructured(source);
+            assert!(semistructured_tree[0].is_truncated());
+            let name = imported_simple_name(tree[0]);
+            assert_eq!(imported_simple_name(semistructured_tree[0]), name);
+            name
+        };
+        assert_eq!(java("import java.util.List;"), Some("List".to_owned()));
+        assert_eq!(java("import staticutils.List;"), Some("List".to_owned()));
+        assert_eq!(java("import  java . util . /* lists */ List ;"), Some("List".to_owned()));
+        assert_eq!(java("import static java.util.Collections.emptyList;"), None);
+        assert_eq!(java("import java.util.*;"), None);
+        assert_eq!(java("import static java.util.Collections.*;"), None);
+
+        let kotlin = |source| imported_simple_name(ctx.parse_with_truncation("a.kt", source, None)[0]);
+        assert_eq!(kotlin("import java.util.List\n"), Some("List".to_owned()));
+        assert_eq!(kotlin("import java.util.List as JList\n"), Some("JList".to_owned()));
+        assert_eq!(kotlin("import java.util.*\n"), None);
+    }
+}
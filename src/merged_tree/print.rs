@@ -1,17 +1,18 @@
 use core::convert::identity;
 use std::borrow::Cow;
 
+use log::debug;
+
 use crate::{
     ast::AstNode,
     class_mapping::{ClassMapping, Leader, RevNode},
     merged_text::MergedText,
     merged_tree::MergedTree,
+    parsed_merge::{MergedChunk, ParsedMerge},
     pcs::Revision,
+    settings::DisplaySettings,
 };
 
-#[cfg(test)]
-use crate::settings::DisplaySettings;
-
 #[derive(Debug, Clone)]
 enum PreviousSibling<'a> {
     RealNode(Leader<'a>),
@@ -26,6 +27,27 @@ impl<'a> MergedTree<'a> {
         merged_text
     }
 
+    /// Renders the tree as a replacement for the source of its root node, when that node
+    /// is not the root of its file: the whitespace preceding the root node is left out,
+    /// and the following lines are indented relatively to the supplied indentation,
+    /// which is that of the line the root node starts on.
+    pub(crate) fn to_merged_text_in_place(
+        &'a self,
+        class_mapping: &ClassMapping<'a>,
+        indentation: &str,
+    ) -> MergedText<'a> {
+        let mut merged_text = MergedText::new();
+        // an empty separator before the root node prevents any whitespace from being added before it
+        let previous_sibling = PreviousSibling::CommutativeSeparator("");
+        self.pretty_print_recursively(
+            &mut merged_text,
+            class_mapping,
+            Some(&previous_sibling),
+            indentation,
+        );
+        merged_text
+    }
+
     #[cfg(test)]
     /// Pretty-prints the result tree into its final output. Exciting!
     pub fn pretty_print<'u: 'a>(
@@ -161,10 +183,32 @@ impl<'a> MergedTree<'a> {
                     class_mapping,
                 );
 
-                if *has_conflict {
-                    output.push_conflict(Cow::from(""), Cow::from(content.clone()), Cow::from(""));
-                } else {
-                    output.push_merged(Cow::from(content.clone()));
+                if !*has_conflict {
+                    output.push_merged(Cow::from(content.as_str()));
+                    return;
+                }
+                // textual mergers output conflicts with diffy's markers, of the default size
+                match ParsedMerge::parse(content, &DisplaySettings::default()) {
+                    Ok(parsed) => {
+                        for chunk in parsed.chunks {
+                            match chunk {
+                                MergedChunk::Resolved { contents, .. } => {
+                                    output.push_merged(Cow::from(contents));
+                                }
+                                MergedChunk::Conflict {
+                                    left, base, right, ..
+                                } => output.push_conflict(
+                                    Cow::from(base.unwrap_or_default()),
+                                    Cow::from(left.unwrap_or_default()),
+                                    Cow::from(right.unwrap_or_default()),
+                                ),
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        debug!("could not parse the conflicts of a textual merge: {err}");
+                        output.push_conflict(Cow::from(""), Cow::from(content.as_str()), Cow::from(""));
+                    }
                 }
             }
         }
@@ -0,0 +1,212 @@
+//! A builder to merge files from other programs, without going through the command line
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    MergeResult,
+    attempts::AttemptsCache,
+    line_merge_and_structured_resolution,
+    settings::{ChunkLogFormat, DisplaySettings, SemistructuredSettings},
+};
+
+/// How the revisions are merged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Line-based merge followed by a structured resolution of its conflicts, like `mergiraf merge --fast`
+    Fast,
+    /// Like [`Strategy::Fast`], falling back on a fully structured merge if conflicts remain
+    #[default]
+    Structured,
+}
+
+/// Merges three revisions of a file, with the settings of a builder.
+///
+/// The language of the file is determined by [`Merger::language`] if set, and otherwise detected
+/// from [`Merger::file_name`]. If no supported language is found, the revisions are merged line by line.
+///
+/// ```
+/// use mergiraf::Merger;
+///
+/// let base = String::from("{\n  \"a\": 1\n}\n");
+/// let left = String::from("{\n  \"a\": 1,\n  \"b\": 2\n}\n");
+/// let right = String::from("{\n  \"a\": 1,\n  \"c\": 3\n}\n");
+///
+/// let merge = Merger::new().language("json").merge(&base, &left, &right);
+/// assert_eq!(merge.conflict_count, 0);
+/// assert_eq!(merge.contents, "{\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 3\n}\n");
+/// ```
+///
+/// The conflicts which can't be solved are displayed according to the [`DisplaySettings`]:
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use mergiraf::{
+///     Merger,
+///     merger::Strategy,
+///     settings::DisplaySettings,
+/// };
+///
+/// let merger = Merger::new()
+///     .file_name("config.json")
+///     .strategy(Strategy::Fast)
+///     .timeout(Duration::from_secs(2))
+///     .settings(DisplaySettings {
+///         compact: Some(true),
+///         ..Default::default()
+///     });
+///
+/// let merge = merger.merge("{\"a\": 1}\n", "{\"a\": 2}\n", "{\"a\": 3}\n");
+/// assert_eq!(merge.conflict_count, 1);
+/// assert!(merge.contents.contains("<<<<<<< LEFT"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Merger<'a> {
+    language: Option<String>,
+    file_name: Option<PathBuf>,
+    settings: DisplaySettings<'a>,
+    strategy: Strategy,
+    semistructured: Option<SemistructuredSettings>,
+    timeout: Duration,
+    attempts_cache: Option<&'a AttemptsCache>,
+    debug_dir: Option<PathBuf>,
+    print_chunks: Option<ChunkLogFormat>,
+}
+
+impl Default for Merger<'_> {
+    fn default() -> Self {
+        Self {
+            language: None,
+            file_name: None,
+            settings: DisplaySettings::default(),
+            strategy: Strategy::default(),
+            semistructured: None,
+            timeout: Duration::from_secs(10),
+            attempts_cache: None,
+            debug_dir: None,
+            print_chunks: None,
+        }
+    }
+}
+
+impl<'a> Merger<'a> {
+    /// A merger with the default settings, as used by `mergiraf merge`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the language of the file, by name or extension (such as `java` or `rs`)
+    #[must_use]
+    pub fn language(self, language: impl Into<String>) -> Self {
+        Self {
+            language: Some(language.into()),
+            ..self
+        }
+    }
+
+    /// Sets the name of the file, used to detect its language if it is not set explicitly
+    /// and to store the merge in the cache
+    #[must_use]
+    pub fn file_name(self, file_name: impl Into<PathBuf>) -> Self {
+        Self {
+            file_name: Some(file_name.into()),
+            ..self
+        }
+    }
+
+    /// Sets how to display the conflicts
+    #[must_use]
+    pub fn settings(self, settings: DisplaySettings<'a>) -> Self {
+        Self { settings, ..self }
+    }
+
+    /// Sets how to merge the revisions
+    #[must_use]
+    pub fn strategy(self, strategy: Strategy) -> Self {
+        Self { strategy, ..self }
+    }
+
+    /// Merges in semistructured mode, where the nodes at truncation points are merged textually
+    ///
+    /// ```
+    /// use mergiraf::{Merger, TextualMergeStrategy, settings::SemistructuredSettings};
+    ///
+    /// let method = |body: &str| format!("class A {{\n    void f() {{\n{body}    }}\n}}\n");
+    /// let (base, left, right) = (method(""), method("        a();\n"), method("        b();\n"));
+    ///
+    /// let merge = Merger::new()
+    ///     .language("java")
+    ///     .semistructured(SemistructuredSettings::new(TextualMergeStrategy::Diff3))
+    ///     .merge(&base, &left, &right);
+    /// assert_eq!(merge.conflict_count, 1);
+    /// ```
+    #[must_use]
+    pub fn semistructured(self, settings: SemistructuredSettings) -> Self {
+        Self {
+            semistructured: Some(settings),
+            ..self
+        }
+    }
+
+    /// Sets the maximum duration of the structured merge, after which the line-based merge
+    /// is returned. A zero duration lets it run until it completes. Defaults to 10 seconds.
+    #[must_use]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Stores the merges which differ from the line-based merge in a cache, so that they
+    /// can be reviewed with `mergiraf review`
+    #[must_use]
+    pub fn cache(self, attempts_cache: &'a AttemptsCache) -> Self {
+        Self {
+            attempts_cache: Some(attempts_cache),
+            ..self
+        }
+    }
+
+    /// Writes debug files to a directory, to analyze the merge
+    #[must_use]
+    pub fn debug_dir(self, debug_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            debug_dir: Some(debug_dir.into()),
+            ..self
+        }
+    }
+
+    /// Prints the chunks of the merged trees as text in the logs,
+    /// or returns them as JSON objects in [`MergeResult::chunk_log`]
+    #[must_use]
+    pub fn print_chunks(self, format: ChunkLogFormat) -> Self {
+        Self {
+            print_chunks: Some(format),
+            ..self
+        }
+    }
+
+    /// Merges three revisions of the file
+    pub fn merge(
+        &self,
+        contents_base: &str,
+        contents_left: &str,
+        contents_right: &str,
+    ) -> MergeResult {
+        line_merge_and_structured_resolution(
+            contents_base,
+            contents_left,
+            contents_right,
+            self.file_name.as_deref().unwrap_or(Path::new("")),
+            self.settings.clone(),
+            self.strategy == Strategy::Structured,
+            self.attempts_cache,
+            self.debug_dir.as_deref(),
+            self.timeout,
+            self.language.as_deref(),
+            self.print_chunks,
+            self.semistructured.clone(),
+        )
+    }
+}
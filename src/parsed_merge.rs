@@ -456,6 +456,8 @@ impl<'a> ParsedMerge<'a> {
             // the line-based merge might have come from a non-syntax-aware tool,
             // and we cautiously assume that it does have issues
             has_additional_issues: true,
+            semistructured_stats: None,
+            chunk_log: Vec::new(),
         }
     }
 
@@ -495,7 +497,7 @@ mod tests {
     use super::*;
 
     #[track_caller]
-    fn parse(source: &str) -> ParsedMerge {
+    fn parse(source: &str) -> ParsedMerge<'_> {
         ParsedMerge::parse(source, &DisplaySettings::default()).expect("unexpected parse error")
     }
 
@@ -1359,7 +1361,7 @@ rest of file
 
     #[test]
     fn is_empty() {
-        const fn resolved(contents: &str) -> MergedChunk {
+        const fn resolved(contents: &str) -> MergedChunk<'_> {
             MergedChunk::Resolved {
                 contents,
                 offset: 0,
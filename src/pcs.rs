@@ -135,7 +135,10 @@ impl Display for PCS<'_> {
     }
 }
 
-impl<'a> PCSNode<'a> {
+
+impl PCSNode<'_> {
+    /// A compact description of the node, for debugging purposes
+    #[allow(dead_code)]
     pub fn short_debug(&self) -> String {
         match self {
             PCSNode::VirtualRoot => "⊥".to_string(),
@@ -162,7 +165,9 @@ impl<'a> PCSNode<'a> {
     }
 }
 
-impl<'a> PCS<'a> {
+impl PCS<'_> {
+    /// A compact description of the triple, for debugging purposes
+    #[allow(dead_code)]
     pub fn short_debug(&self) -> String {
         format!(
             "PCS(parent={}, pred={}, succ={}, rev={})",
@@ -0,0 +1,171 @@
+//! Detection of elements added on one side which reference elements modified on the other side.
+//!
+//! Such merges are clean, but can be semantically wrong: for instance, a method added on the left
+//! side could call a method whose body was changed on the right side, while relying on its old
+//! behaviour.
+
+use log::warn;
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast::AstNode,
+    class_mapping::{ClassMapping, Leader, RevNode, RevisionNESet},
+    merged_tree::MergedTree,
+    pcs::Revision,
+    settings::ReferenceCheck,
+};
+
+/// An element added on one side, which references an element modified on the other side
+#[derive(Debug, Clone)]
+struct ReferenceToModified<'a> {
+    added: Leader<'a>,
+    added_in: Revision,
+    modified: Leader<'a>,
+    modified_in: Revision,
+    name: String,
+}
+
+/// Warns about elements added on one side which reference elements modified on the other side.
+/// In [`ReferenceCheck::Conflict`] mode, such elements are also grouped with the modified ones
+/// in a conflict, if they are children of the same commutative parent.
+/// Returns the processed tree, along with the number of conflicts added to it.
+pub(crate) fn check_references<'a>(
+    merged_tree: MergedTree<'a>,
+    base: &'a AstNode<'a>,
+    left: &'a AstNode<'a>,
+    right: &'a AstNode<'a>,
+    class_mapping: &ClassMapping<'a>,
+    reference_check: ReferenceCheck,
+) -> (MergedTree<'a>, usize) {
+    if reference_check == ReferenceCheck::Off {
+        return (merged_tree, 0);
+    }
+    let references = find_references_to_modified(base, left, right, class_mapping);
+    log_references(&references);
+    if reference_check == ReferenceCheck::Conflict && !references.is_empty() {
+        let mut related = FxHashMap::default();
+        for reference in references {
+            related.insert(reference.modified, reference.name.clone());
+            related.insert(reference.added, reference.name);
+        }
+        let mut added_conflicts = 0;
+        let merged_tree =
+            merged_tree.post_process_for_references(class_mapping, &related, &mut added_conflicts);
+        (merged_tree, added_conflicts)
+    } else {
+        (merged_tree, 0)
+    }
+}
+
+/// Warns about elements added on one side which reference elements modified on the other side,
+/// without merging the revisions
+pub(crate) fn warn_about_references<'a>(
+    base: &'a AstNode<'a>,
+    left: &'a AstNode<'a>,
+    right: &'a AstNode<'a>,
+    class_mapping: &ClassMapping<'a>,
+) {
+    log_references(&find_references_to_modified(
+        base,
+        left,
+        right,
+        class_mapping,
+    ));
+}
+
+fn log_references(references: &[ReferenceToModified]) {
+    for reference in references {
+        warn!(
+            "{} added in {} references `{}`, which was modified in {}",
+            reference.added.grammar_name(),
+            reference.added_in,
+            reference.name,
+            reference.modified_in,
+        );
+    }
+}
+
+/// Finds all the elements (nodes with a signature) added on one side which mention, in their
+/// source, the name of an element modified on the other side.
+fn find_references_to_modified<'a>(
+    base: &'a AstNode<'a>,
+    left: &'a AstNode<'a>,
+    right: &'a AstNode<'a>,
+    class_mapping: &ClassMapping<'a>,
+) -> Vec<ReferenceToModified<'a>> {
+    let mut modified: Vec<(Leader<'a>, Revision, String)> = Vec::new();
+    for base_node in base.dfs() {
+        // only consider the innermost elements, as a class is modified as soon as one of its
+        // methods is
+        if base_node.signature_definition().is_none()
+            || (base_node.dfs().skip(1)).any(|node| node.signature_definition().is_some())
+        {
+            continue;
+        }
+        let leader = class_mapping.map_to_leader(RevNode::new(Revision::Base, base_node));
+        for revision in [Revision::Left, Revision::Right] {
+            if let Some(side_node) = class_mapping.node_at_rev(&leader, revision)
+                && !base_node.isomorphic_to(side_node)
+                && let Some(signature) = base_node.signature()
+                && let Some(name) = signature.name()
+            {
+                modified.push((leader, revision, name.to_owned()));
+            }
+        }
+    }
+    if modified.is_empty() {
+        return Vec::new();
+    }
+
+    let mut references = Vec::new();
+    for (revision, root) in [(Revision::Left, left), (Revision::Right, right)] {
+        let mut added = Vec::new();
+        collect_added_elements(root, revision, class_mapping, &mut added);
+        for added_node in added {
+            let own_name = added_node
+                .signature()
+                .and_then(|sig| sig.name().map(str::to_owned));
+            for (modified_leader, modified_in, name) in &modified {
+                if *modified_in != revision
+                    && own_name.as_ref() != Some(name)
+                    && mentions(added_node.source, name)
+                {
+                    references.push(ReferenceToModified {
+                        added: class_mapping.map_to_leader(RevNode::new(revision, added_node)),
+                        added_in: revision,
+                        modified: *modified_leader,
+                        modified_in: *modified_in,
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+    }
+    references
+}
+
+/// Collects the outermost elements (nodes with a signature) which only exist in the given revision
+fn collect_added_elements<'a>(
+    node: &'a AstNode<'a>,
+    revision: Revision,
+    class_mapping: &ClassMapping<'a>,
+    added: &mut Vec<&'a AstNode<'a>>,
+) {
+    let leader = class_mapping.map_to_leader(RevNode::new(revision, node));
+    if node.signature_definition().is_some()
+        && class_mapping.revision_set(&leader) == RevisionNESet::singleton(revision)
+    {
+        added.push(node);
+        return;
+    }
+    for child in &node.children {
+        collect_added_elements(child, revision, class_mapping, added);
+    }
+}
+
+/// Whether a source contains an identifier
+fn mentions(source: &str, identifier: &str) -> bool {
+    source
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|word| word == identifier)
+}
@@ -1,6 +1,11 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc};
 
-use crate::parsed_merge::{MergedChunk, ParsedMerge};
+use clap::ValueEnum;
+
+use crate::{
+    TextualMergeStrategy, TruncationPolicy,
+    parsed_merge::{MergedChunk, ParsedMerge},
+};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 /// Parameters controlling how the merged tree should be output.
@@ -100,6 +105,18 @@ impl<'a> DisplaySettings<'a> {
         "=".repeat(self.conflict_marker_size_or_default())
     }
 
+    /// The same settings, owning the names of the revisions
+    pub fn into_owned(self) -> DisplaySettings<'static> {
+        DisplaySettings {
+            left_revision_name: self.left_revision_name.map(|name| Cow::Owned(name.into_owned())),
+            base_revision_name: self.base_revision_name.map(|name| Cow::Owned(name.into_owned())),
+            right_revision_name: self
+                .right_revision_name
+                .map(|name| Cow::Owned(name.into_owned())),
+            ..self
+        }
+    }
+
     pub fn default_compact() -> Self {
         Self {
             compact: Some(true),
@@ -139,3 +156,54 @@ impl Default for DisplaySettings<'_> {
         }
     }
 }
+
+/// Parameters of semistructured merge, where the nodes at truncation points are not parsed
+/// any further and are merged textually instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemistructuredSettings {
+    /// The algorithm used to merge the contents of truncated nodes
+    pub strategy: TextualMergeStrategy,
+    /// Which truncation points to truncate
+    pub truncation_policy: TruncationPolicy,
+    /// The command run by the [`TextualMergeStrategy::External`] strategy
+    pub merge_command: Option<Arc<str>>,
+    /// Whether to check for elements added on one side which reference elements
+    /// modified on the other side
+    pub reference_check: ReferenceCheck,
+}
+
+impl SemistructuredSettings {
+    /// Truncates all truncation points, and merges them with the given strategy
+    pub fn new(strategy: TextualMergeStrategy) -> Self {
+        Self {
+            strategy,
+            truncation_policy: TruncationPolicy::default(),
+            merge_command: None,
+            reference_check: ReferenceCheck::default(),
+        }
+    }
+}
+
+/// What to do when an element (such as a method) added on one side references an element
+/// modified on the other side, as the new element might rely on the old behaviour of the
+/// modified one
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReferenceCheck {
+    /// don't check references
+    #[default]
+    Off,
+    /// log a warning for each such reference
+    Warn,
+    /// log a warning, and also add a conflict grouping both elements when they are siblings
+    Conflict,
+}
+
+/// How to print the chunk-by-chunk log of a structured merge
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ChunkLogFormat {
+    /// human-readable text
+    Text,
+    /// one JSON object per line for each chunk on the standard output, with the merge attempt
+    /// it belongs to and the ids, grammar names and byte ranges of its nodes in each revision
+    Json,
+}
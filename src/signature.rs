@@ -319,6 +319,25 @@ impl Signature<'_, '_> {
                 .collect(),
         )
     }
+
+    /// The name of the element, which is the first node of its first path by convention
+    /// (see [`Self::differs_only_by_name`])
+    pub(crate) fn name(&self) -> Option<&str> {
+        match self.0.first()?.first()? {
+            AstNodeEquiv::Original(node) => Some(node.source),
+            AstNodeEquiv::Tmp(name) => Some(name),
+            AstNodeEquiv::Merged(_) => None,
+        }
+    }
+
+    /// Whether two signatures only differ by their first path, which is the name of the
+    /// element by convention (such as `vec![Field("name")]` for a method, followed by the
+    /// types of its parameters).
+    pub(crate) fn differs_only_by_name(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self.0.first() != other.0.first()
+            && self.0[1..] == other.0[1..]
+    }
 }
 
 impl SignatureDefinition {
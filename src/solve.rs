@@ -8,12 +8,13 @@ use log::{debug, info, warn};
 use crate::{
     DisplaySettings, LangProfile, MergeResult, PARSED_MERGE_DIFF2_DETECTED, ParsedMerge,
     git::{GitTempFile, GitTempFiles, extract_all_revisions_from_git, read_content_from_commits},
-    resolve_merge, structured_merge, TextualMergeStrategy,
+    resolve_merge, settings::{ChunkLogFormat, SemistructuredSettings}, structured_merge,
 };
 
 const FROM_PARSED_ORIGINAL: &str = "from_parsed_original";
 
 /// Cascading merge resolution starting from a user-supplied file with merge conflicts
+#[allow(clippy::too_many_arguments)]
 pub fn resolve_merge_cascading<'a>(
     merge_contents: &'a str,
     fname_base: &Path,
@@ -21,8 +22,8 @@ pub fn resolve_merge_cascading<'a>(
     debug_dir: Option<&Path>,
     working_dir: &Path,
     language: Option<&str>,
-    print_chunks: bool,
-    semistructured: Option<TextualMergeStrategy>,
+    print_chunks: Option<ChunkLogFormat>,
+    semistructured: Option<SemistructuredSettings>,
 ) -> Result<MergeResult, String> {
     let mut solves = Vec::with_capacity(4);
 
@@ -46,7 +47,7 @@ pub fn resolve_merge_cascading<'a>(
         Ok(parsed_merge) => {
             settings.add_revision_names(&parsed_merge);
 
-            match resolve_merge(&parsed_merge, &settings, lang_profile, debug_dir, print_chunks, semistructured) {
+            match resolve_merge(&parsed_merge, &settings, lang_profile, debug_dir, print_chunks, semistructured.clone()) {
                 Ok(solve) if solve.conflict_count == 0 => {
                     info!("Solved all conflicts.");
                     debug!("Structured merge from reconstructed revisions.");
@@ -74,7 +75,7 @@ pub fn resolve_merge_cascading<'a>(
         working_dir,
         lang_profile,
         print_chunks,
-        semistructured,
+        semistructured.clone(),
     ) {
         Ok(structured_merge) if structured_merge.conflict_count == 0 => {
             info!("Solved all conflicts.");
@@ -133,8 +134,8 @@ fn structured_merge_from_git_revisions(
     debug_dir: Option<&Path>,
     working_dir: &Path,
     lang_profile: &LangProfile,
-    print_chunks: bool,
-    semistructured: Option<TextualMergeStrategy>,
+    print_chunks: Option<ChunkLogFormat>,
+    semistructured: Option<SemistructuredSettings>,
 ) -> Result<MergeResult, FallbackMergeError> {
     let GitTempFiles { base, left, right } =
         extract_all_revisions_from_git(working_dir, fname_base)
@@ -172,6 +173,7 @@ fn structured_merge_from_git_revisions(
 /// - `None` if the conflict markers do not contain OIDs
 /// - `Some(Err(err))` in case of structured merge error
 /// - `Some(Ok(merge))` in case of structured merge success
+#[allow(clippy::too_many_arguments)]
 fn structured_merge_from_oid(
     fname_base: &Path,
     settings: &DisplaySettings,
@@ -179,8 +181,8 @@ fn structured_merge_from_oid(
     working_dir: &Path,
     lang_profile: &LangProfile,
     parsed: Option<&ParsedMerge<'_>>,
-    print_chunks: bool,
-    semistructured: Option<TextualMergeStrategy>,
+    print_chunks: Option<ChunkLogFormat>,
+    semistructured: Option<SemistructuredSettings>,
 ) -> Option<Result<MergeResult, String>> {
     parsed
         .and_then(|p| p.extract_conflict_oids())
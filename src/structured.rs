@@ -4,9 +4,14 @@ use log::debug;
 use typed_arena::Arena;
 
 use crate::{
-    MergeResult, Revision, ast::AstNode, lang_profile::LangProfile, merge_3dm::three_way_merge,
-    parsed_merge::ParsedMerge, settings::DisplaySettings, tree_matcher::TreeMatcher,
-    TextualMergeStrategy,
+    MergeResult, Revision, ast::AstNode, lang_profile::LangProfile,
+    merge_3dm::{match_revisions, three_way_merge_with_stats},
+    merge_result::SemistructuredStats,
+    parsed_merge::ParsedMerge,
+    reference_check::warn_about_references,
+    settings::{ChunkLogFormat, DisplaySettings, SemistructuredSettings},
+    tree_builder::ChunkLog,
+    tree_matcher::TreeMatcher,
 };
 
 pub const STRUCTURED_RESOLUTION_METHOD: &str = "structured_resolution";
@@ -22,6 +27,7 @@ pub(crate) const ZDIFF3_DETECTED: &str =
 /// If a debug dir is provided, various intermediate stages of the matching will be
 /// written as files in that directory.
 /// Fails if the language cannot be detected or loaded.
+#[allow(clippy::too_many_arguments)]
 pub fn structured_merge(
     contents_base: &str,
     contents_left: &str,
@@ -30,35 +36,25 @@ pub fn structured_merge(
     settings: &DisplaySettings,
     lang_profile: &LangProfile,
     debug_dir: Option<&Path>,
-    print_chunks: bool,
-    semistructured: Option<TextualMergeStrategy>,
+    print_chunks: Option<ChunkLogFormat>,
+    semistructured: Option<SemistructuredSettings>,
 ) -> Result<MergeResult, String> {
     debug!("[STRUCTURED DEBUG] structured_merge called with semistructured = {:?}", semistructured);
 
     let arena = Arena::new();
     let ref_arena = Arena::new();
 
-    let primary_matcher = TreeMatcher {
-        min_height: 1,
-        sim_threshold: 0.4,
-        max_recovery_size: 100,
-        use_rted: true,
-    };
-    let auxiliary_matcher = TreeMatcher {
-        min_height: 2,
-        sim_threshold: 0.6,
-        max_recovery_size: 100,
-        use_rted: false,
-    };
+    let (primary_matcher, auxiliary_matcher) = tree_matchers(lang_profile);
 
     let start = Instant::now();
 
-    let should_truncate = semistructured.is_some();
+    let truncation_policy = semistructured.as_ref().map(|settings| settings.truncation_policy);
 
-    let tree_base = AstNode::parse(contents_base, lang_profile, &arena, &ref_arena, should_truncate);
-    let tree_left = AstNode::parse(contents_left, lang_profile, &arena, &ref_arena, should_truncate);
-    let tree_right = AstNode::parse(contents_right, lang_profile, &arena, &ref_arena, should_truncate);
-    debug!("parsing all three files took {:?}", start.elapsed());
+    let tree_base = AstNode::parse(contents_base, lang_profile, &arena, &ref_arena, truncation_policy);
+    let tree_left = AstNode::parse(contents_left, lang_profile, &arena, &ref_arena, truncation_policy);
+    let tree_right = AstNode::parse(contents_right, lang_profile, &arena, &ref_arena, truncation_policy);
+    let parsing_time = start.elapsed();
+    debug!("parsing all three files took {parsing_time:?}");
 
     // detect a merge in zdiff3 style
     let (tree_base, tree_left, tree_right) = match (tree_base, tree_left, tree_right) {
@@ -84,7 +80,7 @@ pub fn structured_merge(
         )
     });
 
-    let (result_tree, class_mapping) = three_way_merge(
+    let (result_tree, class_mapping, stats, json_chunks) = three_way_merge_with_stats(
         tree_base,
         tree_left,
         tree_right,
@@ -93,43 +89,47 @@ pub fn structured_merge(
         &auxiliary_matcher,
         settings,
         debug_dir,
-        print_chunks,
-        semistructured,
+        print_chunks.map(|format| ChunkLog {
+            format,
+            attempt: if parsed_merge.is_some() { "resolution" } else { "structured" },
+        }),
+        semistructured.clone(),
     );
     debug!("{result_tree}");
 
+    let start = Instant::now();
     let merged_text = result_tree.to_merged_text(&class_mapping);
 
-    if semistructured.is_none() {
-        // Check that the rendered merge is faithful to the tree
-        let revisions_to_check = if merged_text.count_conflicts() == 0 {
-            [Revision::Base].as_slice()
-        } else {
-            [Revision::Base, Revision::Left, Revision::Right].as_slice()
-        };
-
-        for revision in revisions_to_check {
-            let merged_revision = merged_text.reconstruct_revision(*revision);
-            let arena = Arena::new();
-            let ref_arena = Arena::new();
-            let tree = AstNode::parse(
-                &merged_revision,
-                lang_profile,
-                &arena,
-                &ref_arena,
-                false, //merge tree should never be truncated due to isomorphism checks
+    // Check that the rendered merge is faithful to the tree.
+    // In semistructured mode, the revisions are re-parsed with the same truncation policy,
+    // so that truncated nodes are compared with their sources.
+    let revisions_to_check = if merged_text.count_conflicts() == 0 {
+        [Revision::Base].as_slice()
+    } else {
+        [Revision::Base, Revision::Left, Revision::Right].as_slice()
+    };
+
+    for revision in revisions_to_check {
+        let merged_revision = merged_text.reconstruct_revision(*revision);
+        let arena = Arena::new();
+        let ref_arena = Arena::new();
+        let tree = AstNode::parse(
+            &merged_revision,
+            lang_profile,
+            &arena,
+            &ref_arena,
+            truncation_policy,
+        )
+        .map_err(|err| {
+            format!(
+                "merge discarded because rendered revision {revision} has a parsing error: {err}"
             )
-            .map_err(|err| {
-                format!(
-                    "merge discarded because rendered revision {revision} has a parsing error: {err}"
-                )
-            })?;
-            if !result_tree.isomorphic_to_source(tree, *revision, &class_mapping) {
-                debug!(
-                    "discarding merge because rendered revision {revision} isn't isomorphic to the merged tree"
-                );
-                return Err("merge discarded after isomorphism check".to_owned());
-            }
+        })?;
+        if !result_tree.isomorphic_to_source(tree, *revision, &class_mapping) {
+            debug!(
+                "discarding merge because rendered revision {revision} isn't isomorphic to the merged tree"
+            );
+            return Err("merge discarded after isomorphism check".to_owned());
         }
     }
 
@@ -138,5 +138,79 @@ pub fn structured_merge(
     } else {
         STRUCTURED_RESOLUTION_METHOD
     };
-    Ok(merged_text.into_merge_result(settings, method))
+    let mut merge_result = merged_text.into_merge_result(settings, method);
+    merge_result.chunk_log = json_chunks;
+    if semistructured.is_some() {
+        merge_result.semistructured_stats = Some(SemistructuredStats {
+            parsing_time,
+            rendering_time: start.elapsed(),
+            ..stats
+        });
+    }
+    Ok(merge_result)
+}
+
+/// Checks a merge for elements added on one side which reference elements modified on the
+/// other side, only logging a warning for each of them
+/// (see [`crate::settings::ReferenceCheck::Warn`]).
+/// This only matches the revisions, without merging them.
+/// Fails if the revisions can't be parsed.
+pub(crate) fn warn_about_references_to_modified(
+    contents_base: &str,
+    contents_left: &str,
+    contents_right: &str,
+    lang_profile: &LangProfile,
+    semistructured: SemistructuredSettings,
+) -> Result<(), String> {
+    let arena = Arena::new();
+    let ref_arena = Arena::new();
+    let truncation_policy = Some(semistructured.truncation_policy);
+    let tree_base = AstNode::parse(contents_base, lang_profile, &arena, &ref_arena, truncation_policy)?;
+    let tree_left = AstNode::parse(contents_left, lang_profile, &arena, &ref_arena, truncation_policy)?;
+    let tree_right =
+        AstNode::parse(contents_right, lang_profile, &arena, &ref_arena, truncation_policy)?;
+
+    let (primary_matcher, auxiliary_matcher) = tree_matchers(lang_profile);
+    let class_mapping = match_revisions(
+        tree_base,
+        tree_left,
+        tree_right,
+        None,
+        &primary_matcher,
+        &auxiliary_matcher,
+        None,
+        true,
+        &mut SemistructuredStats::default(),
+    );
+    warn_about_references(tree_base, tree_left, tree_right, &class_mapping);
+    Ok(())
+}
+
+/// The matchers used to match the revisions with each other: the primary one
+/// matches base with each side, the auxiliary one matches left and right.
+/// Their parameters can be adjusted by the language profile.
+pub(crate) fn tree_matchers(lang_profile: &LangProfile) -> (TreeMatcher, TreeMatcher) {
+    let (primary_matcher, auxiliary_matcher) = default_tree_matchers();
+    let overrides = lang_profile.matcher_overrides;
+    let primary_matcher = overrides.primary.apply_to(primary_matcher);
+    let auxiliary_matcher = overrides.auxiliary.apply_to(auxiliary_matcher);
+    debug!("primary matcher for {lang_profile}: {primary_matcher:?}");
+    debug!("auxiliary matcher for {lang_profile}: {auxiliary_matcher:?}");
+    (primary_matcher, auxiliary_matcher)
+}
+
+fn default_tree_matchers() -> (TreeMatcher, TreeMatcher) {
+    let primary_matcher = TreeMatcher {
+        min_height: 1,
+        sim_threshold: 0.4,
+        max_recovery_size: 100,
+        use_rted: true,
+    };
+    let auxiliary_matcher = TreeMatcher {
+        min_height: 2,
+        sim_threshold: 0.6,
+        max_recovery_size: 100,
+        use_rted: false,
+    };
+    (primary_matcher, auxiliary_matcher)
 }
@@ -3,6 +3,7 @@ use rustc_hash::FxHashSet;
 
 use crate::{
     lang_profile::{ChildrenGroup, CommutativeParent, LangProfile},
+    tree_matcher::MatcherOverrides,
     signature::{
         PathStep::{ChildType, Field},
         signature,
@@ -28,6 +29,7 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
         signature("pair", vec![vec![Field("key")]]),
         signature("identifier", vec![vec![]]),
         signature("method_definition", vec![vec![Field("name")]]),
+        signature("function_declaration", vec![vec![Field("name")]]),
         signature("public_field_definition", vec![vec![Field("name")]]),
         signature("property_signature", vec![vec![Field("name")]]),
         signature("property_identifier", vec![vec![]]),
@@ -53,13 +55,25 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
         CommutativeParent::new("record_expression", "{", "; ", "}")
             .restricted_to_groups(&[&["field_expression"]]),
     ];
-    let ocaml_signatures = vec![signature(
-        "field_expression",
-        vec![vec![
-            ChildType("field_path"),
-            ChildType("_lowercase_identifier"),
-        ]],
-    )];
+    let ocaml_signatures = vec![
+        signature(
+            "field_expression",
+            vec![vec![
+                ChildType("field_path"),
+                ChildType("_lowercase_identifier"),
+            ]],
+        ),
+        signature(
+            "value_definition",
+            vec![vec![ChildType("let_binding"), Field("pattern")]],
+        ),
+        signature(
+            "type_definition",
+            vec![vec![ChildType("type_binding"), Field("name")]],
+        ),
+        signature("external", vec![vec![ChildType("_lowercase_identifier")]]),
+        signature("open_module", vec![vec![Field("module")]]),
+    ];
 
     vec![
         LangProfile {
@@ -200,6 +214,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
                 "constructor_declaration",
                 "import_declaration",
             ].into_iter().collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Java properties",
@@ -212,6 +228,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             signatures: vec![signature("property", vec![vec![ChildType("key")]])],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Kotlin",
@@ -269,10 +287,12 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
                 "property_declaration",
                 "primary_constructor",
                 "secondary_constructor",
-                "import_header",
+                "import",
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Rust",
@@ -387,6 +407,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Go",
@@ -433,6 +455,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "go.mod",
@@ -505,6 +529,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             ],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "go.sum",
@@ -531,6 +557,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             ],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "INI",
@@ -546,6 +574,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             signatures: vec![signature("setting", vec![vec![ChildType("setting_name")]])],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Javascript",
@@ -563,17 +593,21 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
                 signature("pair", vec![vec![Field("key")]]),
                 signature("identifier", vec![vec![]]),
                 signature("method_definition", vec![vec![Field("name")]]),
+                signature("function_declaration", vec![vec![Field("name")]]),
                 signature("jsx_attribute", vec![vec![ChildType("identifier")]]),
             ],
             injections: Some(tree_sitter_javascript::INJECTIONS_QUERY),
             truncation_node_kinds: [
                 "method_definition",
-                "public_field_definition",
+                "function_declaration",
+                "field_definition",
                 "assignment_expression",
                 "import_statement",
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "JSON",
@@ -589,6 +623,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             signatures: vec![signature("pair", vec![vec![Field("key")]])],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "YAML",
@@ -601,6 +637,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             signatures: vec![signature("block_mapping_pair", vec![vec![Field("key")]])],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "TOML",
@@ -620,6 +658,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             ],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "HTML",
@@ -638,6 +678,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             )],
             injections: Some(tree_sitter_html::INJECTIONS_QUERY),
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "XML",
@@ -653,6 +695,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             signatures: vec![signature("Attribute", vec![vec![ChildType("Name")]])],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "C/C++",
@@ -697,6 +741,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "C#",
@@ -743,6 +789,7 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
                 signature("delegate_declaration", vec![vec![Field("name")]]),
                 signature("record_declaration", vec![vec![Field("name")]]),
                 signature("field_declaration", vec![vec![Field("name")]]),
+                signature("property_declaration", vec![vec![Field("name")]]),
                 signature(
                     "method_declaration",
                     vec![
@@ -773,11 +820,14 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             truncation_node_kinds: [
                 "method_declaration",
                 "field_declaration",
+                "property_declaration",
                 "constructor_declaration",
                 "using_directive",
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Dart",
@@ -795,9 +845,34 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
                 signature("import_or_export", vec![vec![]]),
                 signature("enum_constant", vec![vec![]]),
                 signature("class_definition", vec![vec![Field("name")]]),
+                signature(
+                    "class_member_definition",
+                    vec![
+                        // methods, getters and setters
+                        vec![ChildType("method_signature"), ChildType("function_signature"), Field("name")],
+                        vec![ChildType("method_signature"), ChildType("getter_signature"), Field("name")],
+                        vec![ChildType("method_signature"), ChildType("setter_signature"), Field("name")],
+                        // constructors
+                        vec![ChildType("declaration"), ChildType("constructor_signature"), Field("name")],
+                        vec![ChildType("method_signature"), ChildType("constructor_signature"), Field("name")],
+                        // fields
+                        vec![
+                            ChildType("declaration"),
+                            ChildType("initialized_identifier_list"),
+                            ChildType("initialized_identifier"),
+                            ChildType("identifier"),
+                        ],
+                    ],
+                ),
             ],
             injections: None,
-            truncation_node_kinds: FxHashSet::default(),
+            truncation_node_kinds: [
+                "class_member_definition", // methods, constructors and fields
+            ]
+            .into_iter()
+            .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Devicetree Source",
@@ -813,6 +888,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             ],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Scala",
@@ -822,9 +899,25 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             language: tree_sitter_scala::LANGUAGE.into(),
             atomic_nodes: vec![],
             commutative_parents: vec![],
-            signatures: vec![],
+            signatures: vec![
+                signature("function_definition", vec![vec![Field("name")]]),
+                signature("function_declaration", vec![vec![Field("name")]]),
+                signature("val_definition", vec![vec![Field("pattern")]]),
+                signature("var_definition", vec![vec![Field("pattern")]]),
+                signature("import_declaration", vec![vec![]]),
+            ],
             injections: None,
-            truncation_node_kinds: FxHashSet::default(),
+            truncation_node_kinds: [
+                "function_definition",
+                "function_declaration", // abstract methods
+                "val_definition",
+                "var_definition",
+                "import_declaration",
+            ]
+            .into_iter()
+            .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Typescript",
@@ -838,11 +931,14 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             injections: None,
             truncation_node_kinds: [
                 "method_definition",
+                "function_declaration",
                 "public_field_definition",
                 "import_statement",
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Typescript (TSX)",
@@ -856,11 +952,14 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             injections: None,
             truncation_node_kinds: [
                 "method_definition",
+                "function_declaration",
                 "public_field_definition",
                 "import_statement",
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Python",
@@ -909,12 +1008,20 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             injections: None,
             truncation_node_kinds: [
                 "function_definition",
-                "expression_statement",
                 "import_statement",
                 "import_from_statement",
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![
+                // assignments to module and class attributes, except for `__all__`,
+                // which is merged as a commutative parent
+                r#"(module (expression_statement (assignment
+   left: (identifier) @variable (#not-eq? @variable "__all__")
+ )) @truncated)"#,
+                "(class_definition body: (block (expression_statement) @truncated))",
+            ],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "PHP",
@@ -964,11 +1071,15 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             injections: Some(tree_sitter_php::INJECTIONS_QUERY),
             truncation_node_kinds: [
                 "method_declaration",
+                "function_definition",
                 "property_declaration",
+                "const_declaration",
                 "namespace_use_declaration",
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Solidity",
@@ -981,9 +1092,40 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
                 CommutativeParent::without_delimiters("source_file", "\n"),
                 CommutativeParent::without_delimiters("contract_body", "\n"),
             ],
-            signatures: vec![],
+            signatures: vec![
+                signature("import_directive", vec![vec![]]),
+                // functions and events can be overloaded
+                signature(
+                    "function_definition",
+                    vec![
+                        vec![Field("name")],
+                        vec![ChildType("parameter"), Field("type")],
+                    ],
+                ),
+                signature(
+                    "event_definition",
+                    vec![
+                        vec![Field("name")],
+                        vec![ChildType("event_parameter"), Field("type")],
+                    ],
+                ),
+                signature("constructor_definition", vec![]), // only one constructor per contract
+                signature("modifier_definition", vec![vec![Field("name")]]),
+                signature("state_variable_declaration", vec![vec![Field("name")]]),
+            ],
             injections: None,
-            truncation_node_kinds: FxHashSet::default(),
+            truncation_node_kinds: [
+                "function_definition",
+                "constructor_definition",
+                "modifier_definition",
+                "event_definition",
+                "state_variable_declaration",
+                "import_directive",
+            ]
+            .into_iter()
+            .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Lua",
@@ -993,9 +1135,26 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             language: tree_sitter_lua::LANGUAGE.into(),
             atomic_nodes: vec![],
             commutative_parents: vec![],
-            signatures: vec![],
+            signatures: vec![
+                signature("function_declaration", vec![vec![Field("name")]]),
+                signature(
+                    "variable_declaration",
+                    vec![vec![
+                        ChildType("assignment_statement"),
+                        ChildType("variable_list"),
+                        Field("name"),
+                    ]],
+                ),
+            ],
             injections: Some(tree_sitter_lua::INJECTIONS_QUERY),
-            truncation_node_kinds: FxHashSet::default(),
+            truncation_node_kinds: [
+                "function_declaration",
+                "variable_declaration", // local variables, including `require` imports
+            ]
+            .into_iter()
+            .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Ruby",
@@ -1011,10 +1170,16 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
                 "method", // Method & Constructor (if name is `initialize`)
                 // Field declaration is an `assignment` to an `instance_variable`.
                 // Truncating all assignments is too broad.
-                // Import (`require`) is a `call` node, also too broad.
             ]
             .into_iter()
             .collect(),
+            truncation_queries: vec![
+                // imports are `call` nodes, which are only truncated at the top level
+                r#"(program (call
+   method: (identifier) @method (#any-of? @method "require" "require_relative")
+ ) @truncated)"#,
+            ],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Elixir",
@@ -1027,6 +1192,17 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             signatures: vec![],
             injections: Some(tree_sitter_elixir::INJECTIONS_QUERY),
             truncation_node_kinds: FxHashSet::default(),
+            // Function definitions and imports are all `call` nodes, as are most other
+            // expressions, so they are identified by the function being called.
+            truncation_queries: vec![
+                r#"(call
+   target: (identifier) @keyword (#any-of? @keyword
+     "def" "defp" "defmacro" "defmacrop" "import" "alias" "require" "use")
+ ) @truncated"#,
+                // module attributes
+                r#"(do_block (unary_operator operator: "@") @truncated)"#,
+            ],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Nix",
@@ -1045,6 +1221,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             ],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "SystemVerilog",
@@ -1057,6 +1235,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             signatures: vec![],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Markdown",
@@ -1086,6 +1266,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             signatures: vec![],
             injections: Some(tree_sitter_md::INJECTION_QUERY_BLOCK),
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "HCL",
@@ -1098,6 +1280,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             signatures: vec![],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "OCaml",
@@ -1109,7 +1293,16 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             commutative_parents: ocaml_commutative_parents.clone(),
             signatures: ocaml_signatures.clone(),
             injections: None,
-            truncation_node_kinds: FxHashSet::default(),
+            truncation_node_kinds: [
+                "value_definition",
+                "type_definition",
+                "external",
+                "open_module",
+            ]
+            .into_iter()
+            .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "OCaml interfaces",
@@ -1122,6 +1315,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             signatures: ocaml_signatures,
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "Haskell",
@@ -1145,7 +1340,7 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
                         "bind",
                         "signature",
                         "data_type",
-                        "type_synonym",
+                        "type_synomym", // sic, as spelled in the grammar
                         "deriving_instance",
                         "kind_signature",
                         "newtype",
@@ -1161,15 +1356,28 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
                 signature("signature", vec![vec![Field("name")]]),
                 signature("name", vec![vec![]]), // in deriving lists
                 signature("kind_signature", vec![vec![Field("name")]]),
-                signature("type_synonym", vec![vec![Field("name")]]),
+                signature("type_synomym", vec![vec![Field("name")]]),
                 signature("variable", vec![vec![]]), // in import/export children
                 signature("bind", vec![vec![Field("name")]]),
                 signature("deriving_instance", vec![vec![]]),
                 signature("data_type", vec![vec![Field("name")]]),
                 signature("newtype", vec![vec![Field("name")]]),
+                signature("import", vec![vec![Field("module")]]),
             ],
             injections: None,
-            truncation_node_kinds: FxHashSet::default(),
+            truncation_node_kinds: [
+                "function", // no signature, as functions can be defined by multiple equations
+                "bind",
+                "signature",
+                "data_type",
+                "newtype",
+                "type_synomym", // sic
+                "import",
+            ]
+            .into_iter()
+            .collect(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
         LangProfile {
             name: "GNU Make",
@@ -1188,6 +1396,8 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
             ],
             injections: None,
             truncation_node_kinds: FxHashSet::default(),
+            truncation_queries: vec![],
+            matcher_overrides: MatcherOverrides::default(),
         },
     ]
 });
@@ -1195,6 +1405,7 @@ pub static SUPPORTED_LANGUAGES: LazyLock<Vec<LangProfile>> = LazyLock::new(|| {
 #[cfg(test)]
 mod test {
     use itertools::Itertools;
+    use tree_sitter::Query;
 
     use super::*;
 
@@ -1228,4 +1439,32 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn truncation_queries_are_valid() {
+        for lang_profile in &*SUPPORTED_LANGUAGES {
+            for query_str in &lang_profile.truncation_queries {
+                let query = Query::new(&lang_profile.language, query_str).unwrap_or_else(|err| {
+                    panic!("Invalid truncation query for language {lang_profile}: {err}")
+                });
+                assert!(
+                    query.capture_index_for_name("truncated").is_some(),
+                    "Truncation query for language {lang_profile} without a '@truncated' capture"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn truncation_node_kinds_exist_in_grammars() {
+        for lang_profile in &*SUPPORTED_LANGUAGES {
+            for kind in &lang_profile.truncation_node_kinds {
+                assert_ne!(
+                    lang_profile.language.id_for_node_kind(kind, true),
+                    0,
+                    "Truncation node kind {kind} is not a named node kind of language {lang_profile}"
+                );
+            }
+        }
+    }
 }
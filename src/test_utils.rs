@@ -1,7 +1,9 @@
 use std::hash::{Hash, Hasher};
 use typed_arena::Arena;
 
-use crate::{ast::AstNode, lang_profile::LangProfile, tree_matcher::TreeMatcher};
+use crate::{
+    TruncationPolicy, ast::AstNode, lang_profile::LangProfile, tree_matcher::TreeMatcher,
+};
 
 /// Provides a set of utilities to help write concise tests
 pub struct TestContext<'a> {
@@ -18,9 +20,20 @@ pub fn ctx<'a>() -> TestContext<'a> {
 
 impl<'a> TestContext<'a> {
     fn parse_internal(&'a self, extension: &str, source: &'a str) -> &'a AstNode<'a> {
+        self.parse_with_truncation(extension, source, None)
+    }
+
+    /// Parses a source in the language detected from the extension, truncating nodes
+    /// according to the supplied policy (as in semistructured merge) if any
+    pub fn parse_with_truncation(
+        &'a self,
+        extension: &str,
+        source: &'a str,
+        truncation_policy: Option<TruncationPolicy>,
+    ) -> &'a AstNode<'a> {
         let lang_profile =
             LangProfile::detect_from_filename(extension).expect("could not load language profile");
-        AstNode::parse(source, lang_profile, &self.arena, &self.ref_arena, false)
+        AstNode::parse(source, lang_profile, &self.arena, &self.ref_arena, truncation_policy)
             .expect("syntax error in source")
     }
 
@@ -44,10 +57,20 @@ impl<'a> TestContext<'a> {
         self.parse_internal("a.java", source)
     }
 
+    /// Parses Java with the truncation of nodes used by semistructured merge
+    pub fn parse_java_semistructured(&'a self, source: &'a str) -> &'a AstNode<'a> {
+        self.parse_with_truncation("a.java", source, Some(TruncationPolicy::All))
+    }
+
     pub fn parse_python(&'a self, source: &'a str) -> &'a AstNode<'a> {
         self.parse_internal("a.py", source)
     }
 
+    /// Parses Python with the truncation of nodes used by semistructured merge
+    pub fn parse_python_semistructured(&'a self, source: &'a str) -> &'a AstNode<'a> {
+        self.parse_with_truncation("a.py", source, Some(TruncationPolicy::All))
+    }
+
     pub fn parse_go(&'a self, source: &'a str) -> &'a AstNode<'a> {
         self.parse_internal("a.go", source)
     }
@@ -1,21 +1,56 @@
-use diffy_imara as diffy;
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    io,
+    path::Path,
+    process::{Child, Command, ExitStatus, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
 
-///Textual merge result could be a conflict or not
+use diffy_imara::{self as diffy, Algorithm, MergeOptions};
+use itertools::Itertools;
+use log::{debug, warn};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    ast::AstNode,
+    textual_merge::{
+        diff3::{Diff3Chunk, diff3},
+        patience::patience_matching,
+    },
+};
+
+mod diff3;
+mod patience;
+
+/// Textual merge result could be a conflict or not
 #[derive(Debug, PartialEq, Eq)]
-pub enum TextualMergeResult{
+pub enum TextualMergeResult {
     Success(String),
     Conflict(String),
 }
 
-pub trait TextualMerger{
+/// A textual merge algorithm used to merge the contents of truncated nodes
+pub trait TextualMerger {
     fn merge(&self, base: &str, left: &str, right: &str) -> TextualMergeResult;
+
+    /// Merges the sources of three revisions of a node. Mergers which can make use of
+    /// the syntax of the nodes can override this, by default it merges their sources.
+    fn merge_nodes<'a>(
+        &self,
+        base: &'a AstNode<'a>,
+        left: &'a AstNode<'a>,
+        right: &'a AstNode<'a>,
+    ) -> TextualMergeResult {
+        self.merge(base.source, left.source, right.source)
+    }
 }
 
-///diffy merge implementation
+/// diffy merge implementation, with its default diff algorithm
 pub struct DiffyMerger;
 impl TextualMerger for DiffyMerger {
     fn merge(&self, base: &str, left: &str, right: &str) -> TextualMergeResult {
-        
         let result = diffy::merge(base, left, right);
 
         match result {
@@ -23,4 +58,752 @@ impl TextualMerger for DiffyMerger {
             Err(conflict_text) => TextualMergeResult::Conflict(conflict_text),
         }
     }
-}
\ No newline at end of file
+}
+
+/// diffy merge, aligning the revisions with the histogram diff algorithm
+/// (like `git merge-file --diff-algorithm=histogram`)
+pub struct HistogramMerger;
+impl TextualMerger for HistogramMerger {
+    fn merge(&self, base: &str, left: &str, right: &str) -> TextualMergeResult {
+        diffy_merge_with_algorithm(Algorithm::Histogram, base, left, right)
+    }
+}
+
+/// diffy merge, aligning the revisions with the Myers diff algorithm
+/// (like `git merge-file --diff-algorithm=myers`)
+pub struct MyersMerger;
+impl TextualMerger for MyersMerger {
+    fn merge(&self, base: &str, left: &str, right: &str) -> TextualMergeResult {
+        diffy_merge_with_algorithm(Algorithm::Myers, base, left, right)
+    }
+}
+
+fn diffy_merge_with_algorithm(
+    algorithm: Algorithm,
+    base: &str,
+    left: &str,
+    right: &str,
+) -> TextualMergeResult {
+    let result = MergeOptions::new()
+        .set_algorithm(algorithm)
+        .merge(base, left, right);
+
+    match result {
+        Ok(merged_text) => TextualMergeResult::Success(merged_text),
+        Err(conflict_text) => TextualMergeResult::Conflict(conflict_text),
+    }
+}
+
+/// Line-based merge, aligning the revisions with the patience diff algorithm
+/// (like `git merge-file --diff-algorithm=patience`).
+///
+/// Only lines which are unique in both revisions are used as anchors, so that
+/// frequent lines (such as lone closing braces) do not produce spurious alignments.
+pub struct PatienceMerger;
+impl TextualMerger for PatienceMerger {
+    fn merge(&self, base: &str, left: &str, right: &str) -> TextualMergeResult {
+        let base_lines: Vec<_> = base.split_inclusive('\n').collect();
+        let left_lines: Vec<_> = left.split_inclusive('\n').collect();
+        let right_lines: Vec<_> = right.split_inclusive('\n').collect();
+
+        let chunks = diff3(
+            &base_lines,
+            &left_lines,
+            &right_lines,
+            &patience_matching(&base_lines, &left_lines),
+            &patience_matching(&base_lines, &right_lines),
+        );
+        render_line_chunks(&chunks)
+    }
+}
+
+/// Token-based merge: the revisions are split into tokens (instead of lines),
+/// which are aligned with the patience diff algorithm. This makes it possible to merge
+/// changes to different parts of the same line.
+///
+/// When merging nodes, the tokens are their tree-sitter leaves. Conflicts are rendered
+/// by falling back on a line-based merge, as token-level conflicts would be hard to read.
+pub struct TokensMerger;
+impl TextualMerger for TokensMerger {
+    fn merge(&self, base: &str, left: &str, right: &str) -> TextualMergeResult {
+        merge_tokens(&tokenize(base), &tokenize(left), &tokenize(right))
+            .unwrap_or_else(|| DiffyMerger.merge(base, left, right))
+    }
+
+    fn merge_nodes<'a>(
+        &self,
+        base: &'a AstNode<'a>,
+        left: &'a AstNode<'a>,
+        right: &'a AstNode<'a>,
+    ) -> TextualMergeResult {
+        let (Some(base_tokens), Some(left_tokens), Some(right_tokens)) = (
+            base.source_tokens(),
+            left.source_tokens(),
+            right.source_tokens(),
+        ) else {
+            return self.merge(base.source, left.source, right.source);
+        };
+        merge_tokens(&base_tokens, &left_tokens, &right_tokens)
+            .unwrap_or_else(|| DiffyMerger.merge(base.source, left.source, right.source))
+    }
+}
+
+/// Line-based merge which ignores changes to the whitespace within lines, such as the ones made
+/// by formatters. Lines which only differ by their whitespace are aligned, and take the formatting
+/// of the side which changed it (the left one, if both did). The lines edited on the other side
+/// are re-indented like the lines preceding them.
+pub struct WhitespaceInsensitiveMerger;
+impl TextualMerger for WhitespaceInsensitiveMerger {
+    fn merge(&self, base: &str, left: &str, right: &str) -> TextualMergeResult {
+        fn split(source: &str) -> Vec<Cow<'_, str>> {
+            source.split_inclusive('\n').map(Cow::Borrowed).collect()
+        }
+        let normalize = |lines: &[Cow<str>]| -> Vec<String> {
+            lines
+                .iter()
+                .map(|line| line.split_whitespace().join(" "))
+                .collect()
+        };
+        let mut base_lines = split(base);
+        let mut left_lines = split(left);
+        let mut right_lines = split(right);
+        let base_to_left = patience_matching(&normalize(&base_lines), &normalize(&left_lines));
+        let base_to_right = patience_matching(&normalize(&base_lines), &normalize(&right_lines));
+
+        let left_map: FxHashMap<_, _> = base_to_left.iter().copied().collect();
+        let right_map: FxHashMap<_, _> = base_to_right.iter().copied().collect();
+        let mut left_anchors = Vec::new();
+        let mut right_anchors = Vec::new();
+        for (base_idx, base_line) in base_lines.iter_mut().enumerate() {
+            let left_idx = left_map.get(&base_idx).copied();
+            let right_idx = right_map.get(&base_idx).copied();
+            let formatted = [
+                left_idx.map(|idx| &left_lines[idx]),
+                right_idx.map(|idx| &right_lines[idx]),
+            ]
+            .into_iter()
+            .flatten()
+            .find(|line| *line != base_line)
+            .unwrap_or(base_line)
+            .clone();
+            for (idx, lines, anchors) in [
+                (left_idx, &mut left_lines, &mut left_anchors),
+                (right_idx, &mut right_lines, &mut right_anchors),
+            ] {
+                if let Some(idx) = idx {
+                    anchors.push((
+                        idx,
+                        indentation(&lines[idx]).to_owned(),
+                        indentation(&formatted).to_owned(),
+                    ));
+                    lines[idx] = formatted.clone();
+                }
+            }
+            *base_line = formatted;
+        }
+        reindent_unaligned_lines(&mut left_lines, &left_anchors);
+        reindent_unaligned_lines(&mut right_lines, &right_anchors);
+
+        fn as_strs<'l>(lines: &'l [Cow<str>]) -> Vec<&'l str> {
+            lines.iter().map(AsRef::as_ref).collect()
+        }
+        let chunks = diff3(
+            &as_strs(&base_lines),
+            &as_strs(&left_lines),
+            &as_strs(&right_lines),
+            &base_to_left,
+            &base_to_right,
+        );
+        render_line_chunks(&chunks)
+    }
+}
+
+/// The leading spaces and tabs of a line
+fn indentation(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Re-indents the lines which are not aligned with the base, like the closest aligned line which
+/// had the same indentation (preferably a preceding one). Failing that, the change of indentation
+/// of the closest preceding aligned line is applied to them. The anchors are triples of the index
+/// of an aligned line, its original indentation and its new one.
+fn reindent_unaligned_lines(lines: &mut [Cow<str>], anchors: &[(usize, String, String)]) {
+    let mut next_anchor = 0;
+    for (idx, line) in lines.iter_mut().enumerate() {
+        if anchors
+            .get(next_anchor)
+            .is_some_and(|(anchor_idx, _, _)| *anchor_idx == idx)
+        {
+            next_anchor += 1;
+            continue;
+        }
+        let (preceding, following) = anchors.split_at(next_anchor);
+        let indent = indentation(line);
+        let reindented = if let Some((_, _, new)) = (preceding.iter().rev())
+            .chain(following)
+            .find(|(_, old, _)| old == indent)
+        {
+            format!("{new}{}", &line[indent.len()..])
+        } else if let Some((_, old, new)) = preceding.last()
+            && let Some(rest) = line.strip_prefix(old.as_str())
+        {
+            format!("{new}{rest}")
+        } else {
+            continue;
+        };
+        *line = Cow::Owned(reindented);
+    }
+}
+
+/// Merge delegated to an external command, in the fashion of Git's merge drivers: the `%O`,
+/// `%A` and `%B` placeholders in the command are replaced by temporary files holding the base,
+/// left and right revisions. The command is run by the shell (`sh`, or `cmd` on Windows) and is
+/// expected to write the merged contents (with conflict markers, if any) to the `%A` file, and to
+/// exit with a status between 1 and 128 if the merge conflicts. Any other status means that the
+/// command failed.
+///
+/// If the command cannot be run, fails or runs for longer than [`EXTERNAL_MERGE_TIMEOUT`],
+/// the revisions are merged with diffy instead.
+pub struct ExternalMerger {
+    command: String,
+    timeout: Duration,
+}
+
+/// How long an external merge command can run before it is killed
+pub const EXTERNAL_MERGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl ExternalMerger {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            timeout: EXTERNAL_MERGE_TIMEOUT,
+        }
+    }
+
+    fn run(&self, base: &str, left: &str, right: &str) -> Result<TextualMergeResult, String> {
+        let dir = tempfile::tempdir()
+            .map_err(|err| format!("could not create a temporary directory: {err}"))?;
+        let write_revision = |name: &str, contents: &str| {
+            let path = dir.path().join(name);
+            fs::write(&path, contents)
+                .map_err(|err| format!("could not write {}: {err}", path.display()))?;
+            Ok::<_, String>(path)
+        };
+        let base_path = write_revision("base", base)?;
+        let left_path = write_revision("left", left)?;
+        let right_path = write_revision("right", right)?;
+        // the error output goes to a file rather than a pipe, which could fill up while
+        // the command is waited for
+        let stderr_path = dir.path().join("stderr");
+        let stderr = File::create(&stderr_path)
+            .map_err(|err| format!("could not write {}: {err}", stderr_path.display()))?;
+
+        let command = substitute_placeholders(&self.command, &base_path, &left_path, &right_path);
+        debug!("running external merge command: {command}");
+        let mut child = shell_command(&command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(stderr)
+            .spawn()
+            .map_err(|err| format!("could not run `{command}`: {err}"))?;
+        let status = wait_with_timeout(&mut child, self.timeout)
+            .map_err(|err| format!("could not run `{command}`: {err}"))?
+            .ok_or_else(|| format!("`{command}` timed out after {:?}", self.timeout))?;
+        if let Ok(stderr) = fs::read_to_string(&stderr_path)
+            && !stderr.is_empty()
+        {
+            debug!("{stderr}");
+        }
+
+        let merged = fs::read_to_string(&left_path)
+            .map_err(|err| format!("could not read the output of `{command}`: {err}"))?;
+        match status.code() {
+            Some(0) => Ok(TextualMergeResult::Success(merged)),
+            Some(1..=128) => Ok(TextualMergeResult::Conflict(merged)),
+            _ => Err(format!("`{command}` failed with {status}")),
+        }
+    }
+}
+
+/// Waits for a child process to exit, killing it if it runs for longer than the timeout,
+/// in which case `None` is returned
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+impl TextualMerger for ExternalMerger {
+    fn merge(&self, base: &str, left: &str, right: &str) -> TextualMergeResult {
+        self.run(base, left, right).unwrap_or_else(|err| {
+            warn!("external merge failed, falling back on diff3: {err}");
+            DiffyMerger.merge(base, left, right)
+        })
+    }
+}
+
+/// Runs a command line with `sh`
+#[cfg(not(windows))]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+/// Runs a command line with `cmd`. The command is passed verbatim, since `cmd` does not follow
+/// the escaping rules that [`Command::arg`] applies: with `/S`, it only strips the outer quotes.
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    let mut shell = Command::new("cmd");
+    shell.args(["/S", "/C"]).raw_arg(format!("\"{command}\""));
+    shell
+}
+
+/// Quotes a path for the shell run by [`shell_command`]
+#[cfg(not(windows))]
+fn quote_path(path: &Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', r"'\''"))
+}
+
+/// Quotes a path for the shell run by [`shell_command`]. Paths can't contain double
+/// quotes on Windows, so they don't need escaping.
+#[cfg(windows)]
+fn quote_path(path: &Path) -> String {
+    format!("\"{}\"", path.display())
+}
+
+/// Replaces the `%O`, `%A` and `%B` placeholders of a merge command by the quoted paths
+/// of the base, left and right revisions. `%%` stands for a literal `%`.
+fn substitute_placeholders(command: &str, base: &Path, left: &Path, right: &Path) -> String {
+    let mut substituted = String::new();
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            substituted.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('O') => substituted.push_str(&quote_path(base)),
+            Some('A') => substituted.push_str(&quote_path(left)),
+            Some('B') => substituted.push_str(&quote_path(right)),
+            Some('%') => substituted.push('%'),
+            Some(other) => {
+                substituted.push('%');
+                substituted.push(other);
+            }
+            None => substituted.push('%'),
+        }
+    }
+    substituted
+}
+
+/// Merges three sequences of tokens, returning `None` if there is a conflict
+fn merge_tokens(base: &[&str], left: &[&str], right: &[&str]) -> Option<TextualMergeResult> {
+    let chunks = diff3(
+        base,
+        left,
+        right,
+        &patience_matching(base, left),
+        &patience_matching(base, right),
+    );
+    let mut merged = String::new();
+    for chunk in chunks {
+        match chunk {
+            Diff3Chunk::Merged(tokens) => merged.extend(tokens),
+            Diff3Chunk::Conflict { .. } => return None,
+        }
+    }
+    Some(TextualMergeResult::Success(merged))
+}
+
+/// Splits a source into tokens without knowledge of its syntax:
+/// words (made of alphanumeric characters and underscores), runs of whitespace,
+/// and individual punctuation characters.
+fn tokenize(source: &str) -> Vec<&str> {
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    enum CharClass {
+        Word,
+        Whitespace,
+        Punctuation,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            CharClass::Word
+        } else if c.is_whitespace() {
+            CharClass::Whitespace
+        } else {
+            CharClass::Punctuation
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut token_start = 0;
+    let mut previous_class = None;
+    for (idx, c) in source.char_indices() {
+        let current_class = class(c);
+        if previous_class.is_some_and(|previous| {
+            previous != current_class || current_class == CharClass::Punctuation
+        }) {
+            tokens.push(&source[token_start..idx]);
+            token_start = idx;
+        }
+        previous_class = Some(current_class);
+    }
+    if token_start < source.len() {
+        tokens.push(&source[token_start..]);
+    }
+    tokens
+}
+
+/// Renders the result of a line-based three-way merge with conflict markers,
+/// in the same format as diffy
+fn render_line_chunks(chunks: &[Diff3Chunk]) -> TextualMergeResult {
+    let marker_size = 7;
+    let mut output = String::new();
+    let mut has_conflict = false;
+    for (idx, chunk) in chunks.iter().enumerate() {
+        match chunk {
+            Diff3Chunk::Merged(lines) => output.extend(lines.iter().copied()),
+            Diff3Chunk::Conflict { base, left, right } => {
+                has_conflict = true;
+                let is_last = idx + 1 == chunks.len();
+                let mut push_side = |marker: String, lines: &[&str]| {
+                    output.push_str(&marker);
+                    output.push('\n');
+                    output.extend(lines.iter().copied());
+                    if !output.ends_with('\n') {
+                        output.push('\n');
+                    }
+                };
+                push_side(format!("{} ours", "<".repeat(marker_size)), left);
+                push_side(format!("{} original", "|".repeat(marker_size)), base);
+                push_side("=".repeat(marker_size), right);
+                output.push_str(&">".repeat(marker_size));
+                output.push_str(" theirs");
+                let ends_with_newline = left.last().is_none_or(|line| line.ends_with('\n'));
+                if !is_last || ends_with_newline {
+                    output.push('\n');
+                }
+            }
+        }
+    }
+    if has_conflict {
+        TextualMergeResult::Conflict(output)
+    } else {
+        TextualMergeResult::Success(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::ctx;
+
+    use super::*;
+
+    const BASE: &str = "class A {\n    void a() {\n        foo();\n    }\n}";
+    const LEFT: &str = "class A {\n    void a() {\n        foo();\n    }\n\n    void b() {\n        bar();\n    }\n}";
+    const RIGHT: &str = "class A {\n    void a() {\n        foo(1);\n    }\n}";
+    const EXPECTED: &str = "class A {\n    void a() {\n        foo(1);\n    }\n\n    void b() {\n        bar();\n    }\n}";
+
+    #[test]
+    fn all_mergers_merge_non_conflicting_changes() {
+        let mergers: [&dyn TextualMerger; 6] = [
+            &DiffyMerger,
+            &HistogramMerger,
+            &MyersMerger,
+            &PatienceMerger,
+            &TokensMerger,
+            &WhitespaceInsensitiveMerger,
+        ];
+        for merger in mergers {
+            assert_eq!(
+                merger.merge(BASE, LEFT, RIGHT),
+                TextualMergeResult::Success(EXPECTED.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn whitespace_insensitive_merge_of_reformatted_code() {
+        let base = "\
+void a() {
+  if (x) {
+    foo();
+    bar();
+  }
+}";
+        // reformatted
+        let left = "\
+void a() {
+    if (x) {
+        foo();
+        bar();
+    }
+}";
+        // edited
+        let right = "\
+void a() {
+  if (x) {
+    foo(1);
+    bar();
+    baz();
+  }
+}";
+        let expected = "\
+void a() {
+    if (x) {
+        foo(1);
+        bar();
+        baz();
+    }
+}";
+        assert!(matches!(
+            DiffyMerger.merge(base, left, right),
+            TextualMergeResult::Conflict(_)
+        ));
+        assert_eq!(
+            WhitespaceInsensitiveMerger.merge(base, left, right),
+            TextualMergeResult::Success(expected.to_string())
+        );
+        assert_eq!(
+            WhitespaceInsensitiveMerger.merge(base, right, left),
+            TextualMergeResult::Success(expected.to_string())
+        );
+    }
+
+    #[test]
+    fn whitespace_insensitive_conflict() {
+        let base = "a\n  b\nc\n";
+        let left = "a\n    x\nc\n";
+        let right = "a\n    y\nc\n";
+        assert_eq!(
+            WhitespaceInsensitiveMerger.merge(base, left, right),
+            DiffyMerger.merge(base, left, right)
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn external_merger() {
+        let merger = ExternalMerger::new("cat %B > %A");
+        assert_eq!(
+            merger.merge("a\n", "b\n", "c\n"),
+            TextualMergeResult::Success("c\n".to_string())
+        );
+
+        // the exit code signals conflicts
+        let merger = ExternalMerger::new(
+            "git merge-file -p %A %O %B > %A.merged; s=$?; mv %A.merged %A; exit $s",
+        );
+        assert_eq!(
+            merger.merge(BASE, LEFT, RIGHT),
+            TextualMergeResult::Success(EXPECTED.to_string())
+        );
+        assert!(matches!(
+            merger.merge("a\n", "b\n", "c\n"),
+            TextualMergeResult::Conflict(merged) if merged.contains("=======")
+        ));
+
+        // commands which fail are replaced by diffy
+        let merger = ExternalMerger::new("exit 129");
+        assert_eq!(
+            merger.merge("a\n", "b\n", "c\n"),
+            DiffyMerger.merge("a\n", "b\n", "c\n")
+        );
+
+        // and so are those which crash
+        let merger = ExternalMerger::new("echo partial > %A; kill -SEGV $$");
+        assert_eq!(
+            merger.merge("a\n", "b\n", "c\n"),
+            DiffyMerger.merge("a\n", "b\n", "c\n")
+        );
+
+        // or which hang
+        let merger = ExternalMerger {
+            timeout: Duration::from_millis(100),
+            ..ExternalMerger::new("echo partial > %A; sleep 10")
+        };
+        let start = Instant::now();
+        assert_eq!(
+            merger.merge("a\n", "b\n", "c\n"),
+            DiffyMerger.merge("a\n", "b\n", "c\n")
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn crashed_external_merger() {
+        // crash statuses such as 0xC0000005 are negative
+        let merger = ExternalMerger::new("echo partial > %A & exit -1073741819");
+        assert_eq!(
+            merger.merge("a\n", "b\n", "c\n"),
+            DiffyMerger.merge("a\n", "b\n", "c\n")
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn placeholder_substitution() {
+        let command = substitute_placeholders(
+            "merge %O %A %B 100%% %L",
+            Path::new("/tmp/base"),
+            Path::new("/tmp/it's"),
+            Path::new("/tmp/right"),
+        );
+        assert_eq!(
+            command,
+            r"merge '/tmp/base' '/tmp/it'\''s' '/tmp/right' 100% %L"
+        );
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn placeholder_substitution() {
+        let command = substitute_placeholders(
+            "merge %O %A %B 100%% %L",
+            Path::new(r"C:\tmp\base"),
+            Path::new(r"C:\tmp\it's"),
+            Path::new(r"C:\tmp\right"),
+        );
+        assert_eq!(
+            command,
+            r#"merge "C:\tmp\base" "C:\tmp\it's" "C:\tmp\right" 100% %L"#
+        );
+    }
+
+    #[test]
+    fn patience_conflict_matches_diffy_format() {
+        let base = "a\nb\nc";
+        let left = "a\nx\nc";
+        let right = "a\ny\nc";
+        assert_eq!(
+            PatienceMerger.merge(base, left, right),
+            DiffyMerger.merge(base, left, right)
+        );
+        let left = "a\nb\nx";
+        let right = "a\nb\ny";
+        assert_eq!(
+            PatienceMerger.merge(base, left, right),
+            DiffyMerger.merge(base, left, right)
+        );
+    }
+
+    #[test]
+    fn patience_does_not_align_braces() {
+        let base = "\
+void a() {
+    if (x) {
+        foo();
+    }
+}
+";
+        let left = "\
+void a() {
+    if (x) {
+        foo();
+    }
+}
+
+void b() {
+    if (y) {
+        bar();
+    }
+}
+";
+        let right = "\
+void a() {
+    if (x) {
+        foo();
+        baz();
+    }
+}
+";
+        let expected = "\
+void a() {
+    if (x) {
+        foo();
+        baz();
+    }
+}
+
+void b() {
+    if (y) {
+        bar();
+    }
+}
+";
+        assert_eq!(
+            PatienceMerger.merge(base, left, right),
+            TextualMergeResult::Success(expected.to_string())
+        );
+    }
+
+    #[test]
+    fn tokenize_source() {
+        assert_eq!(
+            tokenize("for(int i = 0;i<10; i++)"),
+            vec![
+                "for", "(", "int", " ", "i", " ", "=", " ", "0", ";", "i", "<", "10", ";", " ",
+                "i", "+", "+", ")"
+            ]
+        );
+    }
+
+    #[test]
+    fn tokens_merge_changes_on_the_same_line() {
+        let base = "for (int i = 0; i < 10; i++) {\n}";
+        let left = "for (int i = 1; i < 10; i++) {\n}";
+        let right = "for (int i = 0; i < 11; i++) {\n}";
+        let expected = "for (int i = 1; i < 11; i++) {\n}";
+        assert!(matches!(
+            DiffyMerger.merge(base, left, right),
+            TextualMergeResult::Conflict(_)
+        ));
+        assert_eq!(
+            TokensMerger.merge(base, left, right),
+            TextualMergeResult::Success(expected.to_string())
+        );
+    }
+
+    #[test]
+    fn tokens_conflict_falls_back_on_lines() {
+        let base = "int x = 0;";
+        let left = "int x = 1;";
+        let right = "int x = 2;";
+        assert_eq!(
+            TokensMerger.merge(base, left, right),
+            DiffyMerger.merge(base, left, right)
+        );
+    }
+
+    #[test]
+    fn tokens_merge_truncated_nodes() {
+        let ctx = ctx();
+        let parse = |source| {
+            let root = ctx.parse_java_semistructured(source);
+            root.dfs()
+                .find(|node| node.grammar_name == "method_declaration")
+                .expect("no method found")
+        };
+        let base = parse("class A {\n  void f() {\n    g(a, b);\n  }\n}\n");
+        let left = parse("class A {\n  void f() {\n    g(x, b);\n  }\n}\n");
+        let right = parse("class A {\n  void f() {\n    g(a, y);\n  }\n}\n");
+        assert!(base.is_truncated());
+        assert_eq!(
+            TokensMerger.merge_nodes(base, left, right),
+            TextualMergeResult::Success("void f() {\n    g(x, y);\n  }".to_string())
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! A generic three-way merge over sequences of tokens, parametrized by the
+//! two-way matching used to align each side with the base.
+
+/// A matching between two sequences, as a list of pairs of indices `(base_idx, side_idx)`
+/// which is strictly increasing in both components.
+pub(crate) type Matching = Vec<(usize, usize)>;
+
+/// A region of the three-way merge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Diff3Chunk<'t> {
+    /// Tokens which could be merged without conflict
+    Merged(Vec<&'t str>),
+    /// Tokens which were changed differently by both sides
+    Conflict {
+        base: Vec<&'t str>,
+        left: Vec<&'t str>,
+        right: Vec<&'t str>,
+    },
+}
+
+/// Merges three sequences of tokens, using the supplied two-way matchings
+/// between the base and each side. The matchings are only required to be
+/// increasing and to only pair identical tokens.
+///
+/// Consecutive merged tokens are grouped in a single [`Diff3Chunk::Merged`] chunk.
+pub(crate) fn diff3<'t>(
+    base: &[&'t str],
+    left: &[&'t str],
+    right: &[&'t str],
+    base_to_left: &Matching,
+    base_to_right: &Matching,
+) -> Vec<Diff3Chunk<'t>> {
+    let to_map = |matching: &Matching| {
+        let mut map = vec![None; base.len()];
+        for (base_idx, side_idx) in matching {
+            map[*base_idx] = Some(*side_idx);
+        }
+        map
+    };
+    let left_map = to_map(base_to_left);
+    let right_map = to_map(base_to_right);
+
+    let mut chunks = Vec::new();
+    let push_merged = |chunks: &mut Vec<Diff3Chunk<'t>>, tokens: &[&'t str]| {
+        if tokens.is_empty() {
+            return;
+        }
+        if let Some(Diff3Chunk::Merged(previous)) = chunks.last_mut() {
+            previous.extend_from_slice(tokens);
+        } else {
+            chunks.push(Diff3Chunk::Merged(tokens.to_vec()));
+        }
+    };
+
+    let (mut base_pos, mut left_pos, mut right_pos) = (0, 0, 0);
+    loop {
+        // consume the stable chunk starting at the current positions, if any
+        let stable_len = (base_pos..base.len())
+            .take_while(|idx| {
+                left_map[*idx] == Some(left_pos + idx - base_pos)
+                    && right_map[*idx] == Some(right_pos + idx - base_pos)
+            })
+            .count();
+        if stable_len > 0 {
+            push_merged(&mut chunks, &base[base_pos..base_pos + stable_len]);
+            base_pos += stable_len;
+            left_pos += stable_len;
+            right_pos += stable_len;
+            continue;
+        }
+
+        // find the next base token which is matched on both sides,
+        // the tokens until then form an unstable chunk
+        let next_stable =
+            (base_pos..base.len()).find_map(|idx| Some((idx, left_map[idx]?, right_map[idx]?)));
+        let (base_end, left_end, right_end) =
+            next_stable.unwrap_or((base.len(), left.len(), right.len()));
+
+        let base_chunk = &base[base_pos..base_end];
+        let left_chunk = &left[left_pos..left_end];
+        let right_chunk = &right[right_pos..right_end];
+        if left_chunk == base_chunk || left_chunk == right_chunk {
+            push_merged(&mut chunks, right_chunk);
+        } else if right_chunk == base_chunk {
+            push_merged(&mut chunks, left_chunk);
+        } else {
+            chunks.push(Diff3Chunk::Conflict {
+                base: base_chunk.to_vec(),
+                left: left_chunk.to_vec(),
+                right: right_chunk.to_vec(),
+            });
+        }
+
+        if next_stable.is_none() {
+            break;
+        }
+        (base_pos, left_pos, right_pos) = (base_end, left_end, right_end);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_sides() {
+        let tokens = ["a", "b", "c"];
+        let identity: Matching = (0..3).map(|i| (i, i)).collect();
+        let chunks = diff3(&tokens, &tokens, &tokens, &identity, &identity);
+        assert_eq!(chunks, vec![Diff3Chunk::Merged(vec!["a", "b", "c"])]);
+    }
+
+    #[test]
+    fn non_overlapping_changes() {
+        let base = ["a", "b", "c"];
+        let left = ["a", "x", "b", "c"];
+        let right = ["a", "b"];
+        let chunks = diff3(
+            &base,
+            &left,
+            &right,
+            &vec![(0, 0), (1, 2), (2, 3)],
+            &vec![(0, 0), (1, 1)],
+        );
+        assert_eq!(chunks, vec![Diff3Chunk::Merged(vec!["a", "x", "b"])]);
+    }
+
+    #[test]
+    fn overlapping_changes() {
+        let base = ["a", "b", "c"];
+        let left = ["a", "x", "c"];
+        let right = ["a", "y", "c"];
+        let chunks = diff3(
+            &base,
+            &left,
+            &right,
+            &vec![(0, 0), (2, 2)],
+            &vec![(0, 0), (2, 2)],
+        );
+        assert_eq!(
+            chunks,
+            vec![
+                Diff3Chunk::Merged(vec!["a"]),
+                Diff3Chunk::Conflict {
+                    base: vec!["b"],
+                    left: vec!["x"],
+                    right: vec!["y"],
+                },
+                Diff3Chunk::Merged(vec!["c"]),
+            ]
+        );
+    }
+}
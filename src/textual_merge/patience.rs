@@ -0,0 +1,218 @@
+//! Patience diff: aligns two sequences using the tokens which occur exactly once
+//! in both of them as anchors, recursively.
+
+use std::hash::Hash;
+
+use rustc_hash::FxHashMap;
+
+use super::diff3::Matching;
+
+/// Above this number of cells, regions without unique anchors are left unmatched
+/// instead of being aligned by computing their longest common subsequence.
+const MAX_LCS_CELLS: usize = 100_000;
+
+/// Computes a matching between two sequences using the patience diff algorithm
+pub(crate) fn patience_matching<T: Eq + Hash>(left: &[T], right: &[T]) -> Matching {
+    let mut matching = Vec::new();
+    match_region(left, right, 0, 0, &mut matching);
+    matching
+}
+
+fn match_region<T: Eq + Hash>(
+    left: &[T],
+    right: &[T],
+    left_offset: usize,
+    right_offset: usize,
+    matching: &mut Matching,
+) {
+    // strip the common prefix and suffix
+    let prefix_len = left.iter().zip(right).take_while(|(l, r)| l == r).count();
+    let suffix_len = left[prefix_len..]
+        .iter()
+        .rev()
+        .zip(right[prefix_len..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    matching.extend((0..prefix_len).map(|i| (left_offset + i, right_offset + i)));
+
+    let left_middle = &left[prefix_len..left.len() - suffix_len];
+    let right_middle = &right[prefix_len..right.len() - suffix_len];
+    let middle_left_offset = left_offset + prefix_len;
+    let middle_right_offset = right_offset + prefix_len;
+
+    if !left_middle.is_empty() && !right_middle.is_empty() {
+        let anchors = unique_anchors(left_middle, right_middle);
+        if anchors.is_empty() {
+            match_by_lcs(
+                left_middle,
+                right_middle,
+                middle_left_offset,
+                middle_right_offset,
+                matching,
+            );
+        } else {
+            let (mut left_start, mut right_start) = (0, 0);
+            for (left_idx, right_idx) in anchors {
+                match_region(
+                    &left_middle[left_start..left_idx],
+                    &right_middle[right_start..right_idx],
+                    middle_left_offset + left_start,
+                    middle_right_offset + right_start,
+                    matching,
+                );
+                matching.push((
+                    middle_left_offset + left_idx,
+                    middle_right_offset + right_idx,
+                ));
+                (left_start, right_start) = (left_idx + 1, right_idx + 1);
+            }
+            match_region(
+                &left_middle[left_start..],
+                &right_middle[right_start..],
+                middle_left_offset + left_start,
+                middle_right_offset + right_start,
+                matching,
+            );
+        }
+    }
+
+    let left_suffix_start = left_offset + left.len() - suffix_len;
+    let right_suffix_start = right_offset + right.len() - suffix_len;
+    matching.extend((0..suffix_len).map(|i| (left_suffix_start + i, right_suffix_start + i)));
+}
+
+/// Finds the tokens occurring exactly once on each side, and returns the longest
+/// increasing sequence of their positions.
+fn unique_anchors<T: Eq + Hash>(left: &[T], right: &[T]) -> Vec<(usize, usize)> {
+    // for each token, its number of occurrences and last position on each side
+    let mut occurrences: FxHashMap<&T, (usize, usize, usize, usize)> = FxHashMap::default();
+    for (idx, token) in left.iter().enumerate() {
+        let entry = occurrences.entry(token).or_default();
+        entry.0 += 1;
+        entry.1 = idx;
+    }
+    for (idx, token) in right.iter().enumerate() {
+        if let Some(entry) = occurrences.get_mut(token) {
+            entry.2 += 1;
+            entry.3 = idx;
+        }
+    }
+    let mut candidates: Vec<(usize, usize)> = occurrences
+        .into_values()
+        .filter(|(left_count, _, right_count, _)| *left_count == 1 && *right_count == 1)
+        .map(|(_, left_idx, _, right_idx)| (left_idx, right_idx))
+        .collect();
+    candidates.sort_unstable();
+    longest_increasing_subsequence(&candidates)
+}
+
+/// Patience sorting: among pairs sorted by their first component,
+/// find the longest subsequence whose second components are increasing.
+fn longest_increasing_subsequence(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // index (in `pairs`) of the top of each pile
+    let mut pile_tops: Vec<usize> = Vec::new();
+    // for each pair, the index of the top of the previous pile when it was placed
+    let mut back_pointers: Vec<Option<usize>> = Vec::with_capacity(pairs.len());
+    for (idx, (_, right_idx)) in pairs.iter().enumerate() {
+        let pile = pile_tops.partition_point(|top| pairs[*top].1 < *right_idx);
+        back_pointers.push(pile.checked_sub(1).map(|previous| pile_tops[previous]));
+        if pile == pile_tops.len() {
+            pile_tops.push(idx);
+        } else {
+            pile_tops[pile] = idx;
+        }
+    }
+
+    let mut result = Vec::with_capacity(pile_tops.len());
+    let mut cursor = pile_tops.last().copied();
+    while let Some(idx) = cursor {
+        result.push(pairs[idx]);
+        cursor = back_pointers[idx];
+    }
+    result.reverse();
+    result
+}
+
+/// Fallback for regions without unique tokens: matches them according to
+/// their longest common subsequence, unless they are too big.
+fn match_by_lcs<T: Eq>(
+    left: &[T],
+    right: &[T],
+    left_offset: usize,
+    right_offset: usize,
+    matching: &mut Matching,
+) {
+    let width = right.len() + 1;
+    if (left.len() + 1).saturating_mul(width) > MAX_LCS_CELLS {
+        return;
+    }
+    // lengths[i * width + j] is the length of the LCS of left[i..] and right[j..]
+    let mut lengths = vec![0_usize; (left.len() + 1) * width];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            lengths[i * width + j] = if left[i] == right[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if left[i] == right[j] {
+            matching.push((left_offset + i, right_offset + j));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical() {
+        let tokens = ["a", "b", "c"];
+        assert_eq!(
+            patience_matching(&tokens, &tokens),
+            vec![(0, 0), (1, 1), (2, 2)]
+        );
+    }
+
+    #[test]
+    fn unique_lines_as_anchors() {
+        let left = ["{", "a", "}", "{", "b", "}"];
+        let right = ["{", "b", "}"];
+        // the unique line `b` is used as anchor, and the braces around it
+        // are only matched as common prefix and suffix
+        assert_eq!(
+            patience_matching(&left, &right),
+            vec![(0, 0), (4, 1), (5, 2)]
+        );
+    }
+
+    #[test]
+    fn lcs_fallback() {
+        // no token is unique on both sides
+        let left = ["x", "a", "x", "a", "y"];
+        let right = ["a", "x", "a", "x", "z"];
+        assert_eq!(
+            patience_matching(&left, &right),
+            vec![(1, 0), (2, 1), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn lis() {
+        let pairs = [(0, 3), (1, 0), (2, 1), (3, 4), (4, 2)];
+        assert_eq!(
+            longest_increasing_subsequence(&pairs),
+            vec![(1, 0), (2, 1), (4, 2)]
+        );
+    }
+}
@@ -1,8 +1,44 @@
 use clap::ValueEnum;
 
+use crate::textual_merge::{
+    DiffyMerger, ExternalMerger, HistogramMerger, MyersMerger, PatienceMerger, TextualMerger,
+    TokensMerger, WhitespaceInsensitiveMerger,
+};
+
 /// Choose between the possible unstructured merge strategies
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
 pub enum TextualMergeStrategy {
-    /// semistructured merge options
-    Diff3, 
-}
\ No newline at end of file
+    /// diffy's three-way merge, with its default diff algorithm
+    Diff3,
+    /// three-way merge based on the histogram diff algorithm
+    Histogram,
+    /// three-way merge based on the Myers diff algorithm
+    Myers,
+    /// three-way merge based on the patience diff algorithm
+    Patience,
+    /// three-way merge of the tokens (tree-sitter leaves) of nodes, instead of their lines
+    Tokens,
+    /// line-based merge ignoring changes to whitespace, keeping the formatting of the side
+    /// which changed it
+    WhitespaceInsensitive,
+    /// merge with a user-supplied command, like a Git merge driver (see `--merge-command`)
+    External,
+}
+
+impl TextualMergeStrategy {
+    /// The textual merger implementing this strategy. The external strategy runs the
+    /// supplied command (see [`ExternalMerger`]), and fails if there is none.
+    pub fn merger(self, command: Option<&str>) -> Result<Box<dyn TextualMerger>, String> {
+        Ok(match self {
+            Self::Diff3 => Box::new(DiffyMerger),
+            Self::Histogram => Box::new(HistogramMerger),
+            Self::Myers => Box::new(MyersMerger),
+            Self::Patience => Box::new(PatienceMerger),
+            Self::Tokens => Box::new(TokensMerger),
+            Self::WhitespaceInsensitive => Box::new(WhitespaceInsensitiveMerger),
+            Self::External => Box::new(ExternalMerger::new(
+                command.ok_or("the external merge strategy requires a merge command")?,
+            )),
+        })
+    }
+}
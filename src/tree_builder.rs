@@ -1,22 +1,29 @@
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::hash::Hash;
 
 use either::Either;
 use itertools::Itertools;
 use log::{debug, info};
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde_json::{Value, json};
+use typed_arena::Arena;
 
 use crate::{
     ast::AstNode,
     changeset::ChangeSet,
     class_mapping::{ClassMapping, Leader, RevNode, RevisionNESet, RevisionSet},
     lang_profile::CommutativeParent,
+    merge_3dm::three_way_merge,
     merged_tree::MergedTree,
     multimap::MultiMap,
+    parsed_merge::ParsedMerge,
     pcs::{PCSNode, Revision},
-    settings::DisplaySettings,
-    textual_merge::{DiffyMerger, TextualMergeResult, TextualMerger},
-    TextualMergeStrategy,
+    merge_result::SemistructuredStats,
+    settings::{ChunkLogFormat, DisplaySettings, SemistructuredSettings},
+    structured::tree_matchers,
+    textual_merge::TextualMergeResult,
 };
 
 //Three structs for debug log
@@ -39,6 +46,34 @@ impl<'a> ChunkData<'a> {
     pub fn is_empty(&self) -> bool {
         self.left_nodes.is_empty() && self.base_nodes.is_empty() && self.right_nodes.is_empty()
     }
+
+    /// Adds the representatives of a class leader in the supplied revisions to this chunk
+    fn push_leader(
+        &mut self,
+        leader: &Leader<'a>,
+        revisions: RevisionSet,
+        class_mapping: &ClassMapping<'a>,
+    ) {
+        for (revision, nodes) in [
+            (Revision::Left, &mut self.left_nodes),
+            (Revision::Base, &mut self.base_nodes),
+            (Revision::Right, &mut self.right_nodes),
+        ] {
+            if revisions.contains(revision)
+                && let Some(node) = class_mapping.node_at_rev(leader, revision)
+            {
+                nodes.push(node);
+            }
+        }
+    }
+}
+
+/// How to print the chunk log of a merge, if requested
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkLog {
+    pub format: ChunkLogFormat,
+    /// The merge attempt the chunks belong to, such as `resolution` or `structured`
+    pub attempt: &'static str,
 }
 
 // Estado para manter o log durante a recursão
@@ -77,7 +112,7 @@ fn format_node_list_detailed(nodes: &Vec<&AstNode>, is_unstable: bool) -> String
             }
             format!("{}: '{}'", n.grammar_name, content)
         } else {
-            format!("{}", n.grammar_name)
+            n.grammar_name.to_string()
         }
     }).take(MAX_NODES_TO_SHOW).collect();
     let mut summary = format!("[{}]", descriptions.join(", "));
@@ -88,6 +123,88 @@ fn format_node_list_detailed(nodes: &Vec<&AstNode>, is_unstable: bool) -> String
     format!("{} nodes {} {}", nodes.len(), range, summary)
 }
 
+/// Prints the chunk log in a human-readable form
+fn print_chunk_log(log: &[MergeChunk], attempt: &str) {
+    info!("\n--- MERGIRAF CHUNK DEBUG LOG ({attempt}) ---");
+    info!("===========================================================");
+    
+    let mut chunk_counter = 0;
+    
+    for chunk in log {
+        match chunk {
+            MergeChunk::Stable(data) => {
+                chunk_counter += 1;
+                info!("-- stable chunk #{} --", chunk_counter);
+                info!("    Left (L):  {}", format_node_list_detailed(&data.left_nodes, false));
+                info!("    Base (B):  {}", format_node_list_detailed(&data.base_nodes, false));
+                info!("    Right (R): {}", format_node_list_detailed(&data.right_nodes, false));
+            }
+            MergeChunk::Unstable(data) => {
+                chunk_counter += 1;
+                info!("-- unstable chunk #{} --", chunk_counter);
+                info!("    Left (L):  {}", format_node_list_detailed(&data.left_nodes, true));
+                info!("    Base (B):  {}", format_node_list_detailed(&data.base_nodes, true));
+                info!("    Right (R): {}", format_node_list_detailed(&data.right_nodes, true));
+            }
+            MergeChunk::CommutativeResolutionStart { parent_kind } => {
+                info!("---> START: UNORDERED MERGE CONFLICT RESOLUTION FOR '{}'...", parent_kind);
+            }
+            MergeChunk::CommutativeResolutionEnd { parent_kind } => {
+                info!("<--- END: UNORDERED MERGE CONFLICT RESOLUTION FOR '{}'.", parent_kind);
+                info!("-----------------------------------------------------------");
+            }
+        }
+        info!("-----------------------------------------------------------");
+    }
+    info!("--- END MERGIRAF CHUNK DEBUG LOG ---\n");
+}
+
+/// Renders each chunk of the log as a JSON object, tagged with the merge attempt it belongs to.
+/// Unstable chunks solved by commutative merging carry the grammar name of their commutative parent.
+fn chunk_log_as_json(log: &[MergeChunk], attempt: &str) -> Vec<Value> {
+    let mut lines = Vec::new();
+    let mut number = 0;
+    for (index, chunk) in log.iter().enumerate() {
+        let (kind, data) = match chunk {
+            MergeChunk::Stable(data) => ("stable", data),
+            MergeChunk::Unstable(data) => ("unstable", data),
+            MergeChunk::CommutativeResolutionStart { .. }
+            | MergeChunk::CommutativeResolutionEnd { .. } => continue,
+        };
+        number += 1;
+        let commutative_parent = match log.get(index + 1) {
+            Some(MergeChunk::CommutativeResolutionStart { parent_kind }) if kind == "unstable" => {
+                Some(parent_kind)
+            }
+            _ => None,
+        };
+        lines.push(json!({
+            "attempt": attempt,
+            "chunk": number,
+            "kind": kind,
+            "left": nodes_to_json(&data.left_nodes),
+            "base": nodes_to_json(&data.base_nodes),
+            "right": nodes_to_json(&data.right_nodes),
+            "commutative_parent": commutative_parent,
+        }));
+    }
+    lines
+}
+
+/// Renders a list of nodes as a JSON array of objects holding their id, grammar name and byte range
+fn nodes_to_json(nodes: &[&AstNode]) -> Value {
+    nodes
+        .iter()
+        .map(|node| {
+            json!({
+                "id": node.id,
+                "grammar_name": node.grammar_name,
+                "byte_range": [node.byte_range.start, node.byte_range.end],
+            })
+        })
+        .collect()
+}
+
 /// An internal structure to map a parent and a predecessor to a possible successor in each revision
 struct SuccessorMap<'a> {
     multimap: HashMap<PCSNode<'a>, MultiMap<PCSNode<'a>, (Revision, PCSNode<'a>)>>,
@@ -115,15 +232,60 @@ impl<'a> SuccessorMap<'a> {
     }
 }
 
+/// Storage for the untruncated parses of the revisions and the structured merges of truncated
+/// nodes made from them, which need to outlive the tree builder.
+#[derive(Default)]
+pub struct UntruncatedArenas<'u> {
+    nodes: Arena<AstNode<'u>>,
+    refs: Arena<&'u AstNode<'u>>,
+    merges: Arena<(MergedTree<'u>, ClassMapping<'u>)>,
+}
+
+/// The revisions being merged, parsed without truncation the first time a truncated node
+/// needs to be merged structurally, and reused for all the following ones.
+pub struct UntruncatedRevisions<'u> {
+    arenas: &'u UntruncatedArenas<'u>,
+    roots: RefCell<FxHashMap<Revision, Option<&'u AstNode<'u>>>>,
+}
+
+impl<'u> UntruncatedRevisions<'u> {
+    /// Creates an empty cache, whose parses will be allocated in the supplied arenas
+    pub fn new(arenas: &'u UntruncatedArenas<'u>) -> Self {
+        Self {
+            arenas,
+            roots: RefCell::default(),
+        }
+    }
+
+    /// Finds the counterpart of a node of the supplied revision in its untruncated parse
+    fn find<'n: 'u>(&self, node: &'n AstNode<'n>, revision: Revision) -> Option<&'u AstNode<'u>> {
+        let root = (*self.roots.borrow_mut().entry(revision).or_insert_with(|| {
+            let root = node.root();
+            let (arena, ref_arena) = (&self.arenas.nodes, &self.arenas.refs);
+            AstNode::parse(root.source, root.lang_profile, arena, ref_arena, None)
+                .inspect_err(|err| debug!("could not parse {revision} without truncation: {err}"))
+                .ok()
+        }))?;
+        let start = node.byte_range.start;
+        let mut current = root;
+        while current.byte_range.start != start || current.grammar_name != node.grammar_name {
+            current = *(current.children.iter())
+                .find(|child| child.byte_range.start <= start && start < child.byte_range.end)?;
+        }
+        Some(current)
+    }
+}
+
 /// Algorithm to build back a tree from a changeset, holding the associated static state.
-pub struct TreeBuilder<'a, 'b> {
+pub struct TreeBuilder<'a, 'b, 'u> {
     // index the set of PCS triples by parent
     merged_successors: SuccessorMap<'a>,
     base_successors: SuccessorMap<'a>,
     class_mapping: &'b ClassMapping<'a>,
     settings: &'b DisplaySettings<'a>,
-    print_chunks: bool,
-    semistructured_strategy: Option<TextualMergeStrategy>,
+    chunk_log: Option<ChunkLog>,
+    semistructured: Option<SemistructuredSettings>,
+    untruncated: &'b UntruncatedRevisions<'u>,
 }
 
 /// Variable state, keeping track of visited nodes to avoid looping
@@ -141,28 +303,31 @@ impl VisitingState<'_> {
 
 type SuccessorsCursor<'a> = FxHashSet<(Revision, PCSNode<'a>)>;
 
-impl<'a, 'b> TreeBuilder<'a, 'b> {
+impl<'a: 'u, 'b, 'u> TreeBuilder<'a, 'b, 'u> {
     /// Create a tree builder from PCS triples, the class mapping and language-specific settings
     pub fn new(
         merged_changeset: &ChangeSet<'a>,
         base_changeset: &ChangeSet<'a>,
         class_mapping: &'b ClassMapping<'a>,
         settings: &'b DisplaySettings<'a>,
-        print_chunks: bool,
-        semistructured_strategy: Option<TextualMergeStrategy>,
+        chunk_log: Option<ChunkLog>,
+        semistructured: Option<SemistructuredSettings>,
+        untruncated: &'b UntruncatedRevisions<'u>,
     ) -> Self {
         TreeBuilder {
             merged_successors: SuccessorMap::new(merged_changeset),
             base_successors: SuccessorMap::new(base_changeset),
             class_mapping,
             settings,
-            print_chunks,
-            semistructured_strategy,
+            chunk_log,
+            semistructured,
+            untruncated,
         }
     }
 
-    /// Build the merged tree
-    pub fn build_tree(&self) -> Result<MergedTree<'a>, String> {
+    /// Build the merged tree. If the chunk log was requested in the JSON format,
+    /// its chunks are added to the supplied vector, one JSON object per chunk.
+    pub fn build_tree(&self, json_chunks: &mut Vec<String>) -> Result<MergedTree<'a>, String> {
         let mut visiting_state = VisitingState {
             // keep track of all nodes that have been deleted on one side and modified on the other
             deleted_and_modified: HashSet::new(),
@@ -170,46 +335,22 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
             visited_nodes: HashSet::new(),
         };
 
-        let mut log_state = if self.print_chunks { Some(LogState::default()) } else { None };
+        let mut log_state = self.chunk_log.map(|_| LogState::default());
 
         // recursively build the tree by starting from the virtual root
         let merged_tree = self.build_subtree(PCSNode::VirtualRoot, &mut visiting_state, &mut log_state)?;
 
         debug!("{merged_tree}");
 
-        if let Some(final_log_state) = log_state {
-            info!("\n--- MERGIRAF CHUNK DEBUG LOG ---");
-            info!("===========================================================");
-            
-            let mut chunk_counter = 0;
-            
-            for chunk in final_log_state.log.iter() {
-                match chunk {
-                    MergeChunk::Stable(data) => {
-                        chunk_counter += 1;
-                        info!("-- stable chunk #{} --", chunk_counter);
-                        info!("    Left (L):  {}", format_node_list_detailed(&data.left_nodes, false));
-                        info!("    Base (B):  {}", format_node_list_detailed(&data.base_nodes, false));
-                        info!("    Right (R): {}", format_node_list_detailed(&data.right_nodes, false));
-                    }
-                    MergeChunk::Unstable(data) => {
-                        chunk_counter += 1;
-                        info!("-- unstable chunk #{} --", chunk_counter);
-                        info!("    Left (L):  {}", format_node_list_detailed(&data.left_nodes, true));
-                        info!("    Base (B):  {}", format_node_list_detailed(&data.base_nodes, true));
-                        info!("    Right (R): {}", format_node_list_detailed(&data.right_nodes, true));
-                    }
-                    MergeChunk::CommutativeResolutionStart { parent_kind } => {
-                        info!("---> START: UNORDERED MERGE CONFLICT RESOLUTION FOR '{}'...", parent_kind);
-                    }
-                    MergeChunk::CommutativeResolutionEnd { parent_kind } => {
-                        info!("<--- END: UNORDERED MERGE CONFLICT RESOLUTION FOR '{}'.", parent_kind);
-                        info!("-----------------------------------------------------------");
-                    }
-                }
-                info!("-----------------------------------------------------------");
+        if let (Some(final_log_state), Some(chunk_log)) = (log_state, self.chunk_log) {
+            match chunk_log.format {
+                ChunkLogFormat::Json => json_chunks.extend(
+                    chunk_log_as_json(&final_log_state.log, chunk_log.attempt)
+                        .iter()
+                        .map(Value::to_string),
+                ),
+                ChunkLogFormat::Text => print_chunk_log(&final_log_state.log, chunk_log.attempt),
             }
-            info!("--- END MERGIRAF CHUNK DEBUG LOG ---\n");
         }
 
         let deleted_and_modified = visiting_state.deleted_and_modified;
@@ -245,6 +386,63 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
         ))
     }
 
+    /// Counts how the truncated nodes of a tree built by this builder were merged
+    pub fn count_truncated_nodes(
+        &self,
+        merged_tree: &MergedTree<'a>,
+        stats: &mut SemistructuredStats,
+    ) {
+        let count_in = |nodes: &[&'a AstNode<'a>]| {
+            nodes
+                .iter()
+                .flat_map(|node| node.dfs())
+                .filter(|node| node.is_truncated())
+                .count()
+        };
+        match merged_tree {
+            MergedTree::ExactTree {
+                node, revisions, ..
+            } => {
+                let representative = self
+                    .class_mapping
+                    .node_at_rev(node, revisions.any())
+                    .expect("Revision set for ExactTree inconsistent with class mapping");
+                let count = count_in(&[representative]);
+                if revisions.contains(Revision::Left) && revisions.contains(Revision::Right) {
+                    stats.unchanged += count;
+                } else {
+                    stats.one_sided += count;
+                }
+            }
+            MergedTree::MixedTree { children, .. } => {
+                for child in children {
+                    self.count_truncated_nodes(child, stats);
+                }
+            }
+            MergedTree::Conflict { left, right, .. } => {
+                stats.merged_with_conflicts += count_in(left).max(count_in(right));
+            }
+            MergedTree::TextuallyMerged {
+                node, has_conflict, ..
+            } => {
+                let source_at = |revision| {
+                    self.class_mapping
+                        .node_at_rev(node, revision)
+                        .map(|node| node.source)
+                };
+                if source_at(Revision::Left) == source_at(Revision::Right) {
+                    // truncated nodes are merged textually even if no side changed them
+                    stats.unchanged += 1;
+                } else if *has_conflict {
+                    stats.merged_with_conflicts += 1;
+                } else {
+                    stats.merged_cleanly += 1;
+                }
+            }
+            MergedTree::LineBasedMerge { .. } | MergedTree::CommutativeChildSeparator { .. } => {}
+        }
+    }
+
     /// Recursive function to build the merged subtree rooted in a node,
     /// checking if it has already been visited to avoid looping.
     fn build_subtree(
@@ -254,98 +452,12 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
         log_state: &mut Option<LogState<'a>>,
     ) -> Result<MergedTree<'a>, String> {
 
-        if let (Some(textual_merger), PCSNode::Node {node: leader, .. }) = (self.semistructured_strategy, node) {
-            if leader.lang_profile().truncation_node_kinds.contains(leader.grammar_name()) {
-                let revisions = self.class_mapping.revision_set(&leader);
-                //node in both left and right
-                if revisions.contains(Revision::Left) && revisions.contains(Revision::Right){
-                    let left_node = self.class_mapping.node_at_rev(&leader, Revision::Left)
-                        .ok_or_else(|| "Truncated note not found on Left revision".to_string())?;
-                    let right_node = self.class_mapping.node_at_rev(&leader, Revision::Right)
-                        .ok_or_else(|| "Truncated note not found on Right revision".to_string())?;
-                    
-                    if let Some(base_node) = self.class_mapping.node_at_rev(&leader, Revision::Base) {
-                        //node in base
-                        let merger: Box<dyn TextualMerger> = match textual_merger {
-                            TextualMergeStrategy::Diff3 => Box::new(DiffyMerger),
-                            //add other diff strategies here
-                        };
-                        let text_result = merger.merge(base_node.source, left_node.source, right_node.source);
-        
-                        let merged_node = match text_result {
-                            TextualMergeResult::Success(content) => MergedTree::TextuallyMerged {
-                                node: leader,
-                                content,
-                                has_conflict: false,
-                            },
-                            TextualMergeResult::Conflict(content) => MergedTree::TextuallyMerged {
-                                node: leader,
-                                content,
-                                has_conflict: true,
-                            },
-                        };
-        
-                        return Ok(merged_node);
-                    } else {
-                        //node not in base
-                        if left_node.source == right_node.source {
-                            return Ok(MergedTree::TextuallyMerged{
-                                node: leader,
-                                content: left_node.source.to_string(),
-                                has_conflict: false,
-                            });
-                        } else {
-                            let marker_size = self.settings.conflict_marker_size_or_default();
-                            let left_marker = "<".repeat(marker_size);
-                            let right_marker = ">".repeat(marker_size);
-                            let base_marker = "|".repeat(marker_size);
-                            let separator_marker = "=".repeat(marker_size);
-
-                            let left_name = self.settings.left_revision_name.as_deref().unwrap_or("left");
-                            let base_name = self.settings.base_revision_name.as_deref().unwrap_or("base");
-                            let right_name = self.settings.right_revision_name.as_deref().unwrap_or("right");
-
-                            let conflict_content = format!(
-                                "{left_marker} {left_name}\n{left_content}\n{base_marker} {base_name}\n{separator_marker}\n{right_content}\n{right_marker} {right_name}",
-                                left_marker = left_marker,
-                                left_name = left_name,
-                                left_content = left_node.source,
-                                base_marker = base_marker,
-                                base_name = base_name,
-                                separator_marker = separator_marker,
-                                right_content = right_node.source,
-                                right_marker = right_marker,
-                                right_name = right_name,
-                            );
-
-                            return Ok(MergedTree::TextuallyMerged{
-                                node: leader,
-                                content: conflict_content,
-                                has_conflict: true,
-                            });
-                        }
-                    }
-                } else {
-                    //node only in left or right
-                    let rev = if let Some(_) = self.class_mapping.node_at_rev(&leader, Revision::Left) {
-                        Revision::Left
-                    } else if let Some(_) = self.class_mapping.node_at_rev(&leader, Revision::Right) {
-                        Revision::Right
-                    } else {
-                        debug!(
-                            "[WARN] Truncated node {:?} has revision set {:?} but no mapped nodes.",
-                            leader.grammar_name(),
-                            revisions
-                        );
-                        return Ok(MergedTree::TextuallyMerged {
-                            node: leader,
-                            content: String::new(),
-                            has_conflict: false,
-                        });
-                    };
-                    let node_to_add = self.class_mapping.node_at_rev(&leader, rev).unwrap();
-                }
-            }
+        if let (Some(settings), PCSNode::Node { node: leader, .. }) =
+            (&self.semistructured, node)
+            && self.class_mapping.is_truncated_in_any_revision(&leader)
+            && let Some(merged) = self.textually_merge_truncated_node(leader, settings)?
+        {
+            return Ok(merged);
         }
 
         if let PCSNode::Node { node, .. } = node {
@@ -362,6 +474,174 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
         result
     }
 
+    /// Merges a truncated node (see [`LangProfile::truncation_node_kinds`]) textually,
+    /// using the supplied settings. Returns `None` if the node is not present in both
+    /// the left and right revisions, in which case it can be built like any other node.
+    ///
+    /// [`LangProfile::truncation_node_kinds`]: crate::lang_profile::LangProfile::truncation_node_kinds
+    fn textually_merge_truncated_node(
+        &self,
+        leader: Leader<'a>,
+        settings: &SemistructuredSettings,
+    ) -> Result<Option<MergedTree<'a>>, String> {
+        let (Some(left_node), Some(right_node)) = (
+            self.class_mapping.node_at_rev(&leader, Revision::Left),
+            self.class_mapping.node_at_rev(&leader, Revision::Right),
+        ) else {
+            return Ok(None);
+        };
+
+        let Some(base_node) = self.class_mapping.node_at_rev(&leader, Revision::Base) else {
+            // the node was added on both sides
+            if left_node.source == right_node.source {
+                return Ok(Some(MergedTree::TextuallyMerged {
+                    node: leader,
+                    content: left_node.source.to_string(),
+                    has_conflict: false,
+                }));
+            }
+            return Ok(Some(MergedTree::Conflict {
+                base: vec![],
+                left: vec![left_node],
+                right: vec![right_node],
+            }));
+        };
+
+        let merger = settings.strategy.merger(settings.merge_command.as_deref())?;
+        let merged_node = match merger.merge_nodes(base_node, left_node, right_node) {
+            TextualMergeResult::Success(content) => MergedTree::TextuallyMerged {
+                node: leader,
+                content,
+                has_conflict: false,
+            },
+            TextualMergeResult::Conflict(content) => {
+                let (content, has_conflict) = self
+                    .structurally_merge_truncated_node(base_node, left_node, right_node, &content)
+                    .unwrap_or((content, true));
+                MergedTree::TextuallyMerged {
+                    node: leader,
+                    content,
+                    has_conflict,
+                }
+            }
+        };
+        Ok(Some(merged_node))
+    }
+
+    /// Fallback for truncated nodes whose textual merge conflicts: find the three revisions
+    /// of the node in the untruncated parses and merge them with the regular 3DM pipeline.
+    /// The rendered structured merge (and whether it has conflicts) is only returned if it has
+    /// fewer conflicts than the textual one and if it passes the same checks as
+    /// [`crate::structured::structured_merge`]: each revision reconstructed from it must parse
+    /// back to a node of the same kind, isomorphic to the merged tree.
+    fn structurally_merge_truncated_node(
+        &self,
+        base: &'a AstNode<'a>,
+        left: &'a AstNode<'a>,
+        right: &'a AstNode<'a>,
+        textual_merge: &str,
+    ) -> Option<(String, bool)> {
+        let settings = DisplaySettings::default();
+        let textual_conflicts = ParsedMerge::parse(textual_merge, &settings)
+            .map_or(1, |parsed| parsed.conflict_count());
+
+        let untruncated = [
+            (base, Revision::Base),
+            (left, Revision::Left),
+            (right, Revision::Right),
+        ]
+        .map(|(node, revision)| self.untruncated.find(node, revision));
+        let [Some(base), Some(left), Some(right)] = untruncated else {
+            debug!("could not find truncated node {left} in the untruncated revisions");
+            return None;
+        };
+
+        let (primary_matcher, auxiliary_matcher) = tree_matchers(left.lang_profile);
+        let merge = three_way_merge(
+            base,
+            left,
+            right,
+            None,
+            &primary_matcher,
+            &auxiliary_matcher,
+            &settings,
+            None,
+            None,
+            None,
+        );
+        // rendering the merge borrows it for as long as the untruncated parses it is made of live
+        let (merged_tree, class_mapping) = &*self.untruncated.arenas.merges.alloc(merge);
+        let indentation = (left.preceding_indentation())
+            .or(left.ancestor_indentation())
+            .unwrap_or("");
+        let merged_text = merged_tree.to_merged_text_in_place(class_mapping, indentation);
+        let structured_conflicts = merged_text.count_conflicts();
+        debug!(
+            "merging truncated node {left} structurally gives {structured_conflicts} conflicts, against {textual_conflicts} textually"
+        );
+        if structured_conflicts >= textual_conflicts {
+            return None;
+        }
+
+        // The matchers see less context in the subtrees than in the whole files, so they can
+        // match nodes added on both sides which the regular pipeline would keep apart, making
+        // the merge combine them (such as two different modifiers added to a method).
+        // Since those additions conflict in the regular pipeline, so must they here.
+        let merges_concurrent_additions = left.dfs().any(|node| {
+            let leader = class_mapping.map_to_leader(RevNode::new(Revision::Left, node));
+            !class_mapping.revision_set(&leader).set().contains(Revision::Base)
+                && class_mapping
+                    .node_at_rev(&leader, Revision::Right)
+                    .is_some_and(|right_node| right_node.hash != node.hash)
+        });
+        if merges_concurrent_additions {
+            debug!(
+                "discarding structured merge of truncated node {left} because it combines nodes added on both sides"
+            );
+            return None;
+        }
+
+        let revisions_to_check = if structured_conflicts == 0 {
+            [Revision::Base].as_slice()
+        } else {
+            [Revision::Base, Revision::Left, Revision::Right].as_slice()
+        };
+        for revision in revisions_to_check {
+            let rendered = merged_text.reconstruct_revision(*revision);
+            if !Self::reparses_to_merged_tree(left, &rendered, merged_tree, *revision, class_mapping)
+            {
+                debug!(
+                    "discarding structured merge of truncated node {left} because rendered revision {revision} isn't isomorphic to the merged tree"
+                );
+                return None;
+            }
+        }
+        Some((merged_text.render(&settings), structured_conflicts > 0))
+    }
+
+    /// Checks that a rendered revision of a structurally merged node parses back to a node
+    /// of the same kind which is isomorphic to the merged tree, once spliced into the file
+    /// in place of the supplied (untruncated) node. Only the spliced region is re-parsed.
+    fn reparses_to_merged_tree<'n, 't>(
+        node: &'n AstNode<'n>,
+        rendered: &str,
+        merged_tree: &'t MergedTree<'t>,
+        revision: Revision,
+        class_mapping: &ClassMapping<'t>,
+    ) -> bool {
+        let root = node.root();
+        let range = &node.byte_range;
+        let source = format!(
+            "{}{rendered}{}",
+            &root.source[..range.start],
+            &root.source[range.end..]
+        );
+        let arena = Arena::new();
+        let ref_arena = Arena::new();
+        root.parse_replaced_range(range.clone(), &source, node.grammar_name, &arena, &ref_arena)
+            .is_some_and(|reparsed| merged_tree.isomorphic_to_source(reparsed, revision, class_mapping))
+    }
+
     // Main recursive function to build the merged subtree from a node
     // (without loop checking)
     fn build_subtree_from_changeset(
@@ -376,8 +656,10 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
             revisions,
             node: leader,
         } = node
+            && revisions.is_full()
+            && self.class_mapping.is_isomorphic_in_all_revisions(&leader)
         {
-            if revisions.is_full() && self.class_mapping.is_isomorphic_in_all_revisions(&leader) {
+            {
                 // If one of the sides is doing a reformatting, make sure we pick this side for pretty printing,
                 // so that we preserve the new formatting.
                 let final_revisions = if self.class_mapping.is_reformatting(&leader, Revision::Left)
@@ -418,10 +700,10 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
                 1 => {
                     // only a single successor, great
 
-                    if let Some(ls) = log_state.as_mut(){
-                        if !ls.current_unstable.is_empty(){
-                            ls.log.push(MergeChunk::Unstable(std::mem::take(&mut ls.current_unstable)));
-                        }
+                    if let Some(ls) = log_state.as_mut()
+                        && !ls.current_unstable.is_empty()
+                    {
+                        ls.log.push(MergeChunk::Unstable(std::mem::take(&mut ls.current_unstable)));
                     }
 
                     let (_, current_child) = cursor
@@ -439,22 +721,11 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
                         return line_diff;
                     }
 
-                    if let (Some(ls), PCSNode::Node{ node: leader, revisions }) = (log_state.as_mut(), current_child) {
-                        if revisions.contains(Revision::Left) {
-                            if let Some(n) = self.class_mapping.node_at_rev(&leader, Revision::Left) {
-                                ls.current_stable.left_nodes.push(n);
-                            }
-                        }
-                        if revisions.contains(Revision::Base) {
-                            if let Some(n) = self.class_mapping.node_at_rev(&leader, Revision::Base) {
-                                ls.current_stable.base_nodes.push(n);
-                            }
-                        }
-                        if revisions.contains(Revision::Right) {
-                            if let Some(n) = self.class_mapping.node_at_rev(&leader, Revision::Right) {
-                                ls.current_stable.right_nodes.push(n);
-                            }
-                        }
+                    if let (Some(ls), PCSNode::Node { node: leader, revisions }) =
+                        (log_state.as_mut(), current_child)
+                    {
+                        ls.current_stable
+                            .push_leader(&leader, revisions.set(), self.class_mapping);
                     }
 
                     let subtree = self.build_subtree(current_child, visiting_state, log_state);
@@ -471,10 +742,10 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
                     cursor = children_map.get(&predecessor);
                 }
                 2 => {
-                    if let Some(ls) = log_state.as_mut(){
-                        if !ls.current_stable.is_empty() {
-                            ls.log.push(MergeChunk::Stable(std::mem::take(&mut ls.current_stable)));
-                        }
+                    if let Some(ls) = log_state.as_mut()
+                        && !ls.current_stable.is_empty()
+                    {
+                        ls.log.push(MergeChunk::Stable(std::mem::take(&mut ls.current_stable)));
                     }
 
                     let Ok((next_cursor, conflict)) = self.build_conflict(
@@ -840,8 +1111,7 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
                     && trimmed != trimmed_right_delim
             })
             .map(move |n| self.class_mapping.map_to_leader(RevNode::new(revision, n)))
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
+            .unique()
     }
 
     /// Collects examples of separators with the surrounding whitespace
@@ -968,38 +1238,22 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
             .map(|(revnode, _)| revnode)
             .collect();
 
-        let merged_set: HashSet<_> = left_leaders
+        // keep the order of the left side, followed by the elements added on the right side
+        let merged_list: Vec<_> = left_seq
             .iter()
             .filter(|n| !right_removed_and_not_modified.contains(n))
+            .chain(
+                right_seq
+                    .iter()
+                    .filter(|n| !base_leaders.contains(n) && !left_leaders.contains(n)),
+            )
             .copied()
-            .chain(right_leaders.difference(&base_leaders).copied())
             .collect();
 
-        let merged: Vec<_> = if self.semistructured_strategy.is_some() {
-
-            let to_pos_map = |seq: &Vec<Leader<'a>>| -> HashMap<Leader<'a>, usize> {
-                let mut m = HashMap::with_capacity(seq.len());
-                for (i, l) in seq.iter().enumerate() {
-                    m.entry(*l).or_insert(i);
-                }
-                m
-            };
-
-            let pos_r = to_pos_map(&right_seq);
-            let pos_l = to_pos_map(&left_seq);
-            let pos_b = to_pos_map(&base_seq);
-
-            let mut v: Vec<_> = merged_set.iter().copied().collect();
-
-            v.sort_by_key(|l| {(
-                pos_r.get(l).copied().unwrap_or(usize::MAX),
-                pos_l.get(l).copied().unwrap_or(usize::MAX),
-                pos_b.get(l).copied().unwrap_or(usize::MAX),
-            )});
-
-            v
+        let merged: Vec<_> = if self.semistructured.is_some() {
+            order_commutative_children(&base_seq, &left_seq, &right_seq, &merged_list)
         } else {
-            merged_set.into_iter().collect()
+            merged_list
         };
 
         debug!("{pad}merged leaders: {}", merged.iter().format(", "));
@@ -1082,22 +1336,11 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
         if let Some(left_delim) = left_delim {
 
             if let Some(ls) = log_state.as_mut() {
-                let revs = self.class_mapping.revision_set(&left_delim);
-                if revs.contains(Revision::Left) {
-                    if let Some(n) = self.class_mapping.node_at_rev(&left_delim, Revision::Left) {
-                        ls.current_stable.left_nodes.push(n);
-                    }
-                }
-                if revs.contains(Revision::Base) {
-                    if let Some(n) = self.class_mapping.node_at_rev(&left_delim, Revision::Base) {
-                        ls.current_stable.base_nodes.push(n);
-                    }
-                }
-                if revs.contains(Revision::Right) {
-                    if let Some(n) = self.class_mapping.node_at_rev(&left_delim, Revision::Right) {
-                        ls.current_stable.right_nodes.push(n);
-                    }
-                }
+                ls.current_stable.push_leader(
+                    &left_delim,
+                    self.class_mapping.revision_set(&left_delim).set(),
+                    self.class_mapping,
+                );
             }
 
 
@@ -1110,7 +1353,7 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
 
         let mut first = !starts_with_separator;
 
-        for (_revnode, merged_tree) in merged_pairs {
+        for (leader, merged_tree) in merged_pairs {
             if first {
                 first = false;
             } else {
@@ -1119,50 +1362,20 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
 
             //updated logging for semistructured
             if let Some(ls) = log_state.as_mut() {
-                let push_leader_into_current_stable = |ls: &mut LogState<'a>, leader: Leader<'a>, class_mapping: &ClassMapping<'a>| {
-                    let revs = class_mapping.revision_set(&leader);
-                    if revs.contains(Revision::Left) {
-                        if let Some(n) = class_mapping.node_at_rev(&leader, Revision::Left) {
-                            ls.current_stable.left_nodes.push(n);
-                        }
-                    }
-                    if revs.contains(Revision::Base) {
-                        if let Some(n) = class_mapping.node_at_rev(&leader, Revision::Base) {
-                            ls.current_stable.base_nodes.push(n);
-                        }
-                    }
-                    if revs.contains(Revision::Right) {
-                        if let Some(n) = class_mapping.node_at_rev(&leader, Revision::Right) {
-                            ls.current_stable.right_nodes.push(n);
-                        }
-                    }
-                };
-
                 match &merged_tree {
-                    MergedTree::TextuallyMerged { node: leader, has_conflict: true, .. } => {
+                    MergedTree::TextuallyMerged { has_conflict: true, .. }
+                    | MergedTree::Conflict { .. } => {
                         debug!("[TB DEBUG] Entering TextuallyMerged Log");
                         if !ls.current_stable.is_empty() {
                             ls.log.push(MergeChunk::Stable(std::mem::take(&mut ls.current_stable)));
                         }
 
                         let mut conflict_chunk_data = ChunkData::default();
-                        let revs = self.class_mapping.revision_set(leader);
-
-                        if revs.contains(Revision::Left) {
-                            if let Some(n) = self.class_mapping.node_at_rev(leader, Revision::Left) {
-                                conflict_chunk_data.left_nodes.push(n);
-                            }
-                        }
-                        if revs.contains(Revision::Base) {
-                            if let Some(n) = self.class_mapping.node_at_rev(leader, Revision::Base) {
-                                conflict_chunk_data.base_nodes.push(n);
-                            }
-                        }
-                        if revs.contains(Revision::Right) {
-                            if let Some(n) = self.class_mapping.node_at_rev(leader, Revision::Right) {
-                                conflict_chunk_data.right_nodes.push(n);
-                            }
-                        }
+                        conflict_chunk_data.push_leader(
+                            &leader,
+                            self.class_mapping.revision_set(&leader).set(),
+                            self.class_mapping,
+                        );
                         ls.log.push(MergeChunk::Unstable(conflict_chunk_data));
                     }
                     
@@ -1170,10 +1383,14 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
                     | MergedTree::MixedTree { node, .. }
                     | MergedTree::LineBasedMerge { node, .. }
                     | MergedTree::TextuallyMerged { node, has_conflict: false, .. } => {
-                        push_leader_into_current_stable(ls, *node, self.class_mapping);
+                        ls.current_stable.push_leader(
+                            node,
+                            self.class_mapping.revision_set(node).set(),
+                            self.class_mapping,
+                        );
                     }
 
-                    MergedTree::CommutativeChildSeparator { .. } | MergedTree::Conflict { .. } => {}
+                    MergedTree::CommutativeChildSeparator { .. } => {}
                 }
             }
 
@@ -1186,22 +1403,11 @@ impl<'a, 'b> TreeBuilder<'a, 'b> {
 
         if let Some(right_delim) = right_delim {
             if let Some(ls) = log_state.as_mut() {
-                let revs = self.class_mapping.revision_set(&right_delim);
-                if revs.contains(Revision::Left) {
-                    if let Some(n) = self.class_mapping.node_at_rev(&right_delim, Revision::Left) {
-                        ls.current_stable.left_nodes.push(n);
-                    }
-                }
-                if revs.contains(Revision::Base) {
-                    if let Some(n) = self.class_mapping.node_at_rev(&right_delim, Revision::Base) {
-                        ls.current_stable.base_nodes.push(n);
-                    }
-                }
-                if revs.contains(Revision::Right) {
-                    if let Some(n) = self.class_mapping.node_at_rev(&right_delim, Revision::Right) {
-                        ls.current_stable.right_nodes.push(n);
-                    }
-                }
+                ls.current_stable.push_leader(
+                    &right_delim,
+                    self.class_mapping.revision_set(&right_delim).set(),
+                    self.class_mapping,
+                );
             }
     
             with_separators.push(MergedTree::new_exact(
@@ -1441,6 +1647,62 @@ fn fmt_set<S>(s: &HashSet<(Revision, PCSNode<'_>), S>) -> impl Display {
         .format_with(", ", |(r, n), f| f(&format_args!("({r},{n})")))
 }
 
+/// Orders the merged children of a commutative parent, in semistructured mode.
+///
+/// The order of one revision is kept: the right one if only the right side reordered elements
+/// of the base revision, the left one otherwise (which preserves the order of the base revision
+/// unless both sides reordered elements). Each element only present on the other side is
+/// inserted after its closest preceding neighbour in that revision, and after any elements added
+/// on the left side at the same place.
+fn order_commutative_children<T: Copy + Eq + Hash>(
+    base: &[T],
+    left: &[T],
+    right: &[T],
+    merged: &[T],
+) -> Vec<T> {
+    let merged_set: HashSet<T> = merged.iter().copied().collect();
+    let base_set: HashSet<T> = base.iter().copied().collect();
+    let reordered = |side: &[T]| {
+        let side_set: HashSet<&T> = side.iter().collect();
+        let kept_from_base = base.iter().filter(|element| side_set.contains(element));
+        !kept_from_base.eq(side.iter().filter(|element| base_set.contains(element)))
+    };
+    let (kept, other, kept_is_left) = if reordered(right) && !reordered(left) {
+        (right, left, false)
+    } else {
+        (left, right, true)
+    };
+    let kept_set: HashSet<T> = kept.iter().copied().collect();
+    let other_set: HashSet<T> = other.iter().copied().collect();
+    let added_on_left =
+        |element: &T| kept_is_left && !base_set.contains(element) && !other_set.contains(element);
+
+    // the elements to insert, indexed by the element of the kept revision they follow
+    let mut insertions: HashMap<Option<T>, Vec<T>> = HashMap::new();
+    let mut neighbour = None;
+    for element in other {
+        if kept_set.contains(element) {
+            if merged_set.contains(element) {
+                neighbour = Some(*element);
+            }
+        } else if merged_set.contains(element) {
+            insertions.entry(neighbour).or_default().push(*element);
+        }
+    }
+
+    let mut result = Vec::with_capacity(merged.len());
+    let mut pending = insertions.remove(&None).unwrap_or_default();
+    for element in kept.iter().filter(|element| merged_set.contains(element)) {
+        if !added_on_left(element) {
+            result.append(&mut pending);
+        }
+        result.push(*element);
+        pending.extend(insertions.remove(&Some(*element)).unwrap_or_default());
+    }
+    result.append(&mut pending);
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test_utils::ctx;
@@ -1463,10 +1725,19 @@ mod tests {
             let merged_changeset = &changeset;
             let base_changeset = &changeset;
             let class_mapping = &class_mapping;
+            let arenas = UntruncatedArenas::default();
+            let untruncated = UntruncatedRevisions::new(&arenas);
             // build the necessary context for the tree-gathering algorithm
-            let tree_gatherer =
-                TreeBuilder::new(merged_changeset, base_changeset, class_mapping, &settings, false, None);
-            tree_gatherer.build_tree()
+            let tree_gatherer = TreeBuilder::new(
+                merged_changeset,
+                base_changeset,
+                class_mapping,
+                &settings,
+                None,
+                None,
+                &untruncated,
+            );
+            tree_gatherer.build_tree(&mut Vec::new())
         };
 
         assert_eq!(
@@ -1495,10 +1766,19 @@ mod tests {
             let merged_changeset = &changeset;
             let base_changeset = &changeset;
             let class_mapping = &class_mapping;
+            let arenas = UntruncatedArenas::default();
+            let untruncated = UntruncatedRevisions::new(&arenas);
             // build the necessary context for the tree-gathering algorithm
-            let tree_gatherer =
-                TreeBuilder::new(merged_changeset, base_changeset, class_mapping, &settings, false, None);
-            tree_gatherer.build_tree()
+            let tree_gatherer = TreeBuilder::new(
+                merged_changeset,
+                base_changeset,
+                class_mapping,
+                &settings,
+                None,
+                None,
+                &untruncated,
+            );
+            tree_gatherer.build_tree(&mut Vec::new())
         }
         .expect("a successful merge was expected");
 
@@ -1511,4 +1791,120 @@ mod tests {
             &class_mapping
         ));
     }
+
+    #[test]
+    fn chunk_log_as_json_lines() {
+        let ctx = ctx();
+
+        let base = ctx.parse_json("[1, 2]");
+        let left = ctx.parse_json("[1, 2, \"a\\\"b\"]");
+        let right = ctx.parse_json("[1, 2, 3]");
+        let (base_array, left_array, right_array) = (base[0], left[0], right[0]);
+
+        let log = [
+            MergeChunk::Stable(ChunkData {
+                left_nodes: vec![left_array[0]],
+                base_nodes: vec![base_array[0]],
+                right_nodes: vec![right_array[0]],
+            }),
+            MergeChunk::Unstable(ChunkData {
+                left_nodes: vec![left_array[5]],
+                base_nodes: vec![],
+                right_nodes: vec![right_array[5]],
+            }),
+            MergeChunk::CommutativeResolutionStart {
+                parent_kind: "array",
+            },
+            MergeChunk::CommutativeResolutionEnd {
+                parent_kind: "array",
+            },
+        ];
+
+        let lines = chunk_log_as_json(&log, "structured");
+        assert_eq!(
+            lines,
+            [
+                json!({
+                    "attempt": "structured",
+                    "chunk": 1,
+                    "kind": "stable",
+                    "left": [{"id": left_array[0].id, "grammar_name": "[", "byte_range": [0, 1]}],
+                    "base": [{"id": base_array[0].id, "grammar_name": "[", "byte_range": [0, 1]}],
+                    "right": [{"id": right_array[0].id, "grammar_name": "[", "byte_range": [0, 1]}],
+                    "commutative_parent": null,
+                }),
+                json!({
+                    "attempt": "structured",
+                    "chunk": 2,
+                    "kind": "unstable",
+                    "left": [{"id": left_array[5].id, "grammar_name": "string", "byte_range": [7, 13]}],
+                    "base": [],
+                    "right": [{"id": right_array[5].id, "grammar_name": "number", "byte_range": [7, 8]}],
+                    "commutative_parent": "array",
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn order_commutative_children_keeps_insertions_in_place() {
+        let base = ['a', 'b', 'c'];
+        let left = ['a', 'x', 'b', 'c'];
+        let right = ['a', 'b', 'c', 'y'];
+        let merged = ['a', 'x', 'b', 'c', 'y'];
+        assert_eq!(
+            order_commutative_children(&base, &left, &right, &merged),
+            ['a', 'x', 'b', 'c', 'y']
+        );
+
+        // insertions at the same place: left first, then right
+        let left = ['x', 'a', 'b', 'c'];
+        let right = ['y', 'z', 'a', 'b', 'c'];
+        let merged = ['x', 'a', 'b', 'c', 'y', 'z'];
+        assert_eq!(
+            order_commutative_children(&base, &left, &right, &merged),
+            ['x', 'y', 'z', 'a', 'b', 'c']
+        );
+
+        // the neighbour of a right insertion was removed on the left
+        let left = ['a', 'c'];
+        let right = ['a', 'b', 'y', 'c'];
+        let merged = ['a', 'c', 'y'];
+        assert_eq!(
+            order_commutative_children(&base, &left, &right, &merged),
+            ['a', 'y', 'c']
+        );
+    }
+
+    #[test]
+    fn order_commutative_children_keeps_reorderings() {
+        let base = ['a', 'b', 'c'];
+
+        // only the right side reordered elements
+        let left = ['a', 'x', 'b', 'c'];
+        let right = ['c', 'a', 'b', 'y'];
+        let merged = ['a', 'x', 'b', 'c', 'y'];
+        assert_eq!(
+            order_commutative_children(&base, &left, &right, &merged),
+            ['c', 'a', 'x', 'b', 'y']
+        );
+
+        // insertions at the same place are still ordered left first
+        let left = ['x', 'a', 'b', 'c'];
+        let right = ['y', 'c', 'a', 'b'];
+        let merged = ['x', 'a', 'b', 'c', 'y'];
+        assert_eq!(
+            order_commutative_children(&base, &left, &right, &merged),
+            ['x', 'y', 'c', 'a', 'b']
+        );
+
+        // both sides reordered elements
+        let left = ['b', 'a', 'c'];
+        let right = ['c', 'a', 'b'];
+        let merged = ['b', 'a', 'c'];
+        assert_eq!(
+            order_commutative_children(&base, &left, &right, &merged),
+            ['b', 'a', 'c']
+        );
+    }
 }
@@ -14,7 +14,7 @@ use crate::{ast::AstNode, matching::Matching, multimap::MultiMap, signature::Sig
 mod priority_list;
 use priority_list::PriorityList;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TreeMatcher {
     /// The minimum height of subtrees to match in the top-down phase
     pub min_height: i32,
@@ -26,6 +26,59 @@ pub struct TreeMatcher {
     pub max_recovery_size: i32,
 }
 
+/// Parameters overriding those of a [`TreeMatcher`], unset ones being left unchanged
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TreeMatcherOverrides {
+    pub min_height: Option<i32>,
+    pub sim_threshold: Option<f32>,
+    pub use_rted: Option<bool>,
+    pub max_recovery_size: Option<i32>,
+}
+
+impl TreeMatcherOverrides {
+    /// Fills the parameters unset in `self` with those of `fallback`
+    #[must_use]
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            min_height: self.min_height.or(fallback.min_height),
+            sim_threshold: self.sim_threshold.or(fallback.sim_threshold),
+            use_rted: self.use_rted.or(fallback.use_rted),
+            max_recovery_size: self.max_recovery_size.or(fallback.max_recovery_size),
+        }
+    }
+
+    /// The matcher with the parameters set in `self` replaced
+    pub fn apply_to(self, matcher: TreeMatcher) -> TreeMatcher {
+        TreeMatcher {
+            min_height: self.min_height.unwrap_or(matcher.min_height),
+            sim_threshold: self.sim_threshold.unwrap_or(matcher.sim_threshold),
+            use_rted: self.use_rted.unwrap_or(matcher.use_rted),
+            max_recovery_size: self.max_recovery_size.unwrap_or(matcher.max_recovery_size),
+        }
+    }
+}
+
+/// Overrides of the parameters of both matchers used in a merge (see [`crate::structured::tree_matchers`]),
+/// such as for a particular language
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MatcherOverrides {
+    /// Overrides for the matcher of base with each side
+    pub primary: TreeMatcherOverrides,
+    /// Overrides for the matcher of left with right
+    pub auxiliary: TreeMatcherOverrides,
+}
+
+impl MatcherOverrides {
+    /// Fills the parameters unset in `self` with those of `fallback`
+    #[must_use]
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            primary: self.primary.or(fallback.primary),
+            auxiliary: self.auxiliary.or(fallback.auxiliary),
+        }
+    }
+}
+
 /// A matching which keeps track of how each link was inferred, for visualization purposes
 #[derive(Default)]
 pub struct DetailedMatching<'src> {
@@ -500,6 +553,36 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn apply_overrides() {
+        let matcher = TreeMatcher {
+            min_height: 1,
+            sim_threshold: 0.4,
+            use_rted: true,
+            max_recovery_size: 100,
+        };
+        let overrides = TreeMatcherOverrides {
+            sim_threshold: Some(0.6),
+            ..Default::default()
+        }
+        .or(TreeMatcherOverrides {
+            sim_threshold: Some(0.8),
+            use_rted: Some(false),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            overrides.apply_to(matcher),
+            TreeMatcher {
+                min_height: 1,
+                sim_threshold: 0.6,
+                use_rted: false,
+                max_recovery_size: 100,
+            }
+        );
+        assert_eq!(TreeMatcherOverrides::default().apply_to(matcher), matcher);
+    }
+
     #[test]
     fn small_sample() {
         let ctx = ctx();
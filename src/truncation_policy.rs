@@ -0,0 +1,84 @@
+use std::{fmt::Display, str::FromStr};
+
+/// Choose which truncation points (see [`LangProfile::truncation_node_kinds`]) are actually
+/// truncated in semistructured merge, depending on their size.
+///
+/// Truncating a node makes merging it faster, but less precise: small nodes (such as one-line
+/// field declarations) are generally better merged structurally.
+///
+/// [`LangProfile::truncation_node_kinds`]: crate::lang_profile::LangProfile::truncation_node_kinds
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum TruncationPolicy {
+    /// truncate all truncation points, whatever their size
+    #[default]
+    All,
+    /// only truncate nodes spanning at least this number of lines
+    MinLines(usize),
+    /// only truncate nodes made of at least this number of syntax nodes
+    MinNodes(usize),
+}
+
+impl TruncationPolicy {
+    /// Whether a node at a truncation point should be truncated according to this policy
+    pub(crate) fn should_truncate(self, node: &tree_sitter::Node) -> bool {
+        match self {
+            Self::All => true,
+            Self::MinLines(min_lines) => {
+                node.end_position().row - node.start_position().row + 1 >= min_lines
+            }
+            Self::MinNodes(min_nodes) => node.descendant_count() >= min_nodes,
+        }
+    }
+}
+
+impl FromStr for TruncationPolicy {
+    type Err = String;
+
+    /// Parses a policy from `all`, `lines:<N>` or `nodes:<N>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "all" {
+            return Ok(Self::All);
+        }
+        let parse_threshold = |threshold: &str| {
+            threshold
+                .parse()
+                .map_err(|err| format!("invalid truncation threshold '{threshold}': {err}"))
+        };
+        match s.split_once(':') {
+            Some(("lines", threshold)) => parse_threshold(threshold).map(Self::MinLines),
+            Some(("nodes", threshold)) => parse_threshold(threshold).map(Self::MinNodes),
+            _ => Err(format!(
+                "invalid truncation policy '{s}', expected 'all', 'lines:<N>' or 'nodes:<N>'"
+            )),
+        }
+    }
+}
+
+impl Display for TruncationPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::All => write!(f, "all"),
+            Self::MinLines(min_lines) => write!(f, "lines:{min_lines}"),
+            Self::MinNodes(min_nodes) => write!(f, "nodes:{min_nodes}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        for policy in [
+            TruncationPolicy::All,
+            TruncationPolicy::MinLines(5),
+            TruncationPolicy::MinNodes(100),
+        ] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+        assert!("lines".parse::<TruncationPolicy>().is_err());
+        assert!("lines:many".parse::<TruncationPolicy>().is_err());
+        assert!("tokens:3".parse::<TruncationPolicy>().is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::process::Command;
+
+use mergiraf::DISABLING_ENV_VAR;
+use serde_json::Value;
+
+#[test]
+fn json_chunk_log_on_stdout() {
+    let dir = tempfile::tempdir().expect("failed to create the temp dir");
+    let repo_dir = dir.path();
+    fs::write(repo_dir.join("Base.json"), "{\n  \"a\": 1\n}\n").unwrap();
+    fs::write(
+        repo_dir.join("Left.json"),
+        "{\n  \"a\": 1,\n  \"b\": 2\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        repo_dir.join("Right.json"),
+        "{\n  \"a\": 1,\n  \"c\": 3\n}\n",
+    )
+    .unwrap();
+
+    let merge = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_mergiraf"))
+            .current_dir(repo_dir)
+            .args([
+                "merge",
+                "Base.json",
+                "Left.json",
+                "Right.json",
+                "--print-chunks=json",
+            ])
+            .args(args)
+            // in case Mergiraf is disabled in the environment running the tests
+            .env_remove(DISABLING_ENV_VAR)
+            .output()
+            .expect("failed to run mergiraf")
+    };
+
+    // the chunks can't be mixed with the merged file
+    let output = merge(&[]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let output = merge(&["-o", "Merged.json"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        fs::read_to_string(repo_dir.join("Merged.json")).unwrap(),
+        "{\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 3\n}\n"
+    );
+
+    // the conflict of the line-based merge is solved by a structured resolution
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let chunks: Vec<Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line should be a JSON object"))
+        .collect();
+    assert!(!chunks.is_empty());
+    assert!(chunks.iter().all(|chunk| chunk["attempt"] == "resolution"));
+    assert!(
+        chunks.iter().any(|chunk| {
+            chunk["kind"] == "unstable" && chunk["commutative_parent"] == "object"
+        })
+    );
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use mergiraf::DISABLING_ENV_VAR;
+use mergiraf::config::CONFIG_FILE_NAME;
+
+mod common;
+use common::run_git;
+
+/// Merges conflicting revisions of a JSON file with the `mergiraf` binary, like Git does
+/// from the root of the repository, and returns the length of the conflict markers
+fn conflict_marker_size(repo_dir: &Path, path_name: &str, args: &[&str]) -> usize {
+    fs::write(repo_dir.join("Base.json"), "{\"a\": 1}\n").unwrap();
+    fs::write(repo_dir.join("Left.json"), "{\"a\": 2}\n").unwrap();
+    fs::write(repo_dir.join("Right.json"), "{\"a\": 3}\n").unwrap();
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_mergiraf"));
+    command
+        .current_dir(repo_dir)
+        .args([
+            "merge",
+            "Base.json",
+            "Left.json",
+            "Right.json",
+            "-p",
+            path_name,
+        ])
+        .args(args)
+        // in case Mergiraf is disabled in the environment running the tests
+        .env_remove(DISABLING_ENV_VAR);
+    let output = command.output().expect("failed to run mergiraf");
+    assert_eq!(output.status.code(), Some(1), "expected conflicts");
+
+    let contents = String::from_utf8(output.stdout).unwrap();
+    let marker = contents
+        .lines()
+        .find(|line| line.starts_with('<'))
+        .expect("no conflict marker in the merge output");
+    marker.chars().take_while(|c| *c == '<').count()
+}
+
+#[test]
+fn precedence_of_the_configuration_file() {
+    let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
+    let repo_dir = repo_dir.path();
+    run_git(&["init", "."], repo_dir);
+
+    // without configuration file
+    assert_eq!(conflict_marker_size(repo_dir, "file.json", &[]), 7);
+
+    fs::write(
+        repo_dir.join(CONFIG_FILE_NAME),
+        r#"
+conflict-marker-size = 9
+
+[languages.json]
+conflict-marker-size = 10
+
+[files."special/*.json"]
+conflict-marker-size = 11
+"#,
+    )
+    .unwrap();
+
+    // language overrides take precedence over the defaults
+    assert_eq!(conflict_marker_size(repo_dir, "file.json", &[]), 10);
+    assert_eq!(
+        conflict_marker_size(repo_dir, "file.json", &["--language=yaml"]),
+        9
+    );
+    // file overrides take precedence over the language overrides
+    assert_eq!(conflict_marker_size(repo_dir, "special/file.json", &[]), 11);
+    // command-line flags take precedence over the configuration file
+    assert_eq!(
+        conflict_marker_size(repo_dir, "special/file.json", &["-l", "8"]),
+        8
+    );
+}
+
+#[test]
+fn invalid_configuration_file_is_ignored() {
+    let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
+    let repo_dir = repo_dir.path();
+    run_git(&["init", "."], repo_dir);
+    fs::write(
+        repo_dir.join(CONFIG_FILE_NAME),
+        "conflict-marker-size = \"big\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(conflict_marker_size(repo_dir, "file.json", &[]), 7);
+}
+
+#[test]
+fn language_profile_defined_in_the_configuration_file() {
+    let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
+    let repo_dir = repo_dir.path();
+    run_git(&["init", "."], repo_dir);
+    fs::write(
+        repo_dir.join(CONFIG_FILE_NAME),
+        r#"
+[profiles.Registry]
+grammar = "JSON"
+extensions = ["reg"]
+commutative-parents = [{ type = "array", left-delim = "[", separator = ", ", right-delim = "]" }]
+"#,
+    )
+    .unwrap();
+    fs::write(repo_dir.join("Base.reg"), "[1]\n").unwrap();
+    fs::write(repo_dir.join("Left.reg"), "[1, 2]\n").unwrap();
+    fs::write(repo_dir.join("Right.reg"), "[1, 3]\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mergiraf"))
+        .current_dir(repo_dir)
+        .args([
+            "merge",
+            "Base.reg",
+            "Left.reg",
+            "Right.reg",
+            "-p",
+            "list.reg",
+        ])
+        .env_remove(DISABLING_ENV_VAR)
+        .output()
+        .expect("failed to run mergiraf");
+    assert_eq!(output.status.code(), Some(0), "expected a clean merge");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "[1, 2, 3]\n");
+}
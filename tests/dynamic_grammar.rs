@@ -0,0 +1,122 @@
+#![cfg(unix)]
+
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use mergiraf::DISABLING_ENV_VAR;
+use mergiraf::config::CONFIG_FILE_NAME;
+use mergiraf::custom_langs;
+use mergiraf::dynamic_grammar::{GRAMMAR_PATH_ENV_VAR, load_grammar};
+use mergiraf::lang_profile::LangProfile;
+
+mod common;
+use common::run_git;
+
+/// Compiles the INI grammar of `tests/grammars` into a shared library in `dir`
+fn compile_ini_grammar(dir: &Path) -> PathBuf {
+    let source_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/grammars/ini");
+    let library = dir.join(format!("{DLL_PREFIX}tree-sitter-ini{DLL_SUFFIX}"));
+    let compiler = cc::Build::new()
+        .cargo_metadata(false)
+        .cargo_warnings(false)
+        .target(env!("TARGET"))
+        .host(env!("TARGET"))
+        .opt_level(0)
+        .pic(true)
+        .include(&source_dir)
+        .get_compiler();
+    let status = compiler
+        .to_command()
+        .arg("-shared")
+        .arg(source_dir.join("parser.c"))
+        .arg("-o")
+        .arg(&library)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "failed to compile the grammar");
+    library
+}
+
+#[test]
+fn find_profile_with_loaded_grammar() {
+    let dir = tempfile::tempdir().expect("failed to create the temp dir");
+    let library = compile_ini_grammar(dir.path());
+    let language = load_grammar(&library, "tree_sitter_ini").expect("failed to load the grammar");
+
+    custom_langs::register(LangProfile {
+        name: "Loaded INI",
+        extensions: vec!["loadedini"],
+        language,
+        ..LangProfile::find_by_name("INI").unwrap().clone()
+    });
+    let lang_profile = LangProfile::find_by_filename_or_name("settings.loadedini", None)
+        .expect("the registered profile was not found");
+    assert_eq!(lang_profile.name, "Loaded INI");
+
+    assert!(
+        load_grammar(&library, "tree_sitter_dsl")
+            .unwrap_err()
+            .starts_with("Could not find the grammar `tree_sitter_dsl`")
+    );
+}
+
+#[test]
+fn merge_with_grammar_from_the_configuration_file() {
+    let grammar_dir = tempfile::tempdir().expect("failed to create the temp dir");
+    compile_ini_grammar(grammar_dir.path());
+    let repo_dir = tempfile::tempdir().expect("failed to create the temp dir");
+    let repo_dir = repo_dir.path();
+    run_git(&["init", "."], repo_dir);
+    fs::write(
+        repo_dir.join(CONFIG_FILE_NAME),
+        format!(
+            r#"
+[profiles.Settings]
+grammar = {{ library = "{DLL_PREFIX}tree-sitter-ini{DLL_SUFFIX}", symbol = "tree_sitter_ini" }}
+extensions = ["settings"]
+commutative-parents = [{{ type = "section", separator = "\n", groups = [["setting"]] }}]
+signatures = [{{ type = "setting", paths = [[{{ child-type = "setting_name" }}]] }}]
+"#
+        ),
+    )
+    .unwrap();
+    fs::write(repo_dir.join("Base.settings"), "[main]\na = 1\n").unwrap();
+    fs::write(repo_dir.join("Left.settings"), "[main]\na = 1\nb = 2\n").unwrap();
+    fs::write(repo_dir.join("Right.settings"), "[main]\na = 1\nc = 3\n").unwrap();
+
+    let merge = |grammar_path: Option<&Path>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_mergiraf"));
+        command
+            .current_dir(repo_dir)
+            .args([
+                "merge",
+                "Base.settings",
+                "Left.settings",
+                "Right.settings",
+                "-p",
+                "app.settings",
+            ])
+            .env_remove(DISABLING_ENV_VAR)
+            .env_remove(GRAMMAR_PATH_ENV_VAR);
+        if let Some(grammar_path) = grammar_path {
+            command.env(GRAMMAR_PATH_ENV_VAR, grammar_path);
+        }
+        let output = command.output().expect("failed to run mergiraf");
+        (
+            output.status.code(),
+            String::from_utf8(output.stdout).unwrap(),
+        )
+    };
+
+    assert_eq!(
+        merge(Some(grammar_dir.path())),
+        (Some(0), "[main]\na = 1\nb = 2\nc = 3\n".to_owned())
+    );
+    // the grammar is not loaded from directories which are not in the grammar path,
+    // so the configuration file is ignored
+    let (code, contents) = merge(None);
+    assert_eq!(code, Some(1));
+    assert!(contents.contains("<<<<<<<"));
+}
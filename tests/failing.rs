@@ -4,7 +4,7 @@ use std::time::Duration;
 
 use diffy_imara::{PatchFormatter, create_patch};
 use mergiraf::settings::DisplaySettings;
-use mergiraf::{PathBufExt, line_merge_and_structured_resolution};
+use mergiraf::line_merge_and_structured_resolution;
 use rstest::rstest;
 
 mod common;
@@ -42,7 +42,6 @@ enum FailingTestResult {
 #[rstest]
 fn integration_failing(#[files("examples/*/failing/*")] test_dir: PathBuf) {
     let suffix = detect_test_suffix(&test_dir);
-    #[expect(unstable_name_collisions)]
     let fname_base = test_dir.join(format!("Base{suffix}")).leak();
     let contents_base = fs::read_to_string(&fname_base)
         .expect("Unable to read left file")
@@ -74,7 +73,7 @@ fn integration_failing(#[files("examples/*/failing/*")] test_dir: PathBuf) {
         None,
         Duration::from_millis(0),
         language_override_for_test(&test_dir),
-        false,
+        None,
         None,
     );
 
@@ -142,7 +141,7 @@ please examine the new output and update ExpectedCurrently{suffix} if it looks o
         None,
         Duration::from_millis(0),
         None,
-        false,
+        None,
         None,
     );
 
@@ -96,7 +96,7 @@ fn oid_fallback_extracts_revisions_and_solves() {
         None,
         not_repo.path(),
         None,
-        false,
+        None,
         None,
     )
     .unwrap();
@@ -110,7 +110,7 @@ fn oid_fallback_extracts_revisions_and_solves() {
         None,
         repo_dir,
         None,
-        false,
+        None,
         None,
     )
     .unwrap();
@@ -31,7 +31,7 @@ fn not_in_repo() {
         None,
         repo_dir,
         None,
-        false,
+        None,
         None,
     );
     assert!(
@@ -68,7 +68,7 @@ fn not_in_index() {
         None,
         repo_dir,
         None,
-        false,
+        None,
         None,
     );
     assert!(
@@ -119,7 +119,7 @@ fn committed_conflict() {
         None,
         repo_dir,
         None,
-        false,
+        None,
         None,
     );
     assert!(
@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use diffy_imara::{PatchFormatter, create_patch};
+use mergiraf::TextualMergeStrategy;
+use mergiraf::line_merge_and_structured_resolution;
+use mergiraf::settings::{DisplaySettings, SemistructuredSettings};
+use rstest::rstest;
+
+mod common;
+use common::detect_test_suffix;
+
+use crate::common::language_override_for_test;
+
+fn merge_in_semistructured_mode(
+    test_dir: &Path,
+    fname_base: &'static Path,
+    contents_base: &'static str,
+    contents_left: &'static str,
+    contents_right: &'static str,
+    compact: bool,
+) -> String {
+    let settings = DisplaySettings {
+        compact: Some(compact),
+        ..Default::default()
+    };
+
+    let merge_result = line_merge_and_structured_resolution(
+        contents_base,
+        contents_left,
+        contents_right,
+        fname_base,
+        settings,
+        true,
+        None,
+        None,
+        Duration::from_millis(0),
+        language_override_for_test(test_dir),
+        None,
+        Some(SemistructuredSettings::new(TextualMergeStrategy::Diff3)),
+    );
+    merge_result.contents
+}
+
+fn print_diff(expected: &str, actual: &str) {
+    let patch = create_patch(expected, actual);
+    let f = PatchFormatter::new().with_color();
+    print!("{}", f.fmt_patch(&patch));
+}
+
+/// Semistructured test cases either have:
+/// - an `Expected.{ext}` file (and optionally `ExpectedCompact.{ext}`), which the merge output
+///   must be identical to
+/// - `ExpectedCurrently.{ext}` and `ExpectedIdeally.{ext}` files, like the test cases in
+///   `examples/*/failing`: the output must stay identical to `ExpectedCurrently.{ext}` until it
+///   becomes identical to `ExpectedIdeally.{ext}`, at which point the test case should be updated.
+fn run_test_from_dir(test_dir: &Path) {
+    let suffix = detect_test_suffix(test_dir);
+    let fname_base = test_dir.join(format!("Base{suffix}")).leak();
+    let contents_base = fs::read_to_string(&fname_base)
+        .expect("Unable to read base file")
+        .leak();
+    let fname_left = test_dir.join(format!("Left{suffix}"));
+    let contents_left = fs::read_to_string(fname_left)
+        .expect("Unable to read left file")
+        .leak();
+    let fname_right = test_dir.join(format!("Right{suffix}"));
+    let contents_right = fs::read_to_string(fname_right)
+        .expect("Unable to read right file")
+        .leak();
+
+    let merge = |compact| {
+        merge_in_semistructured_mode(
+            test_dir,
+            fname_base,
+            contents_base,
+            contents_left,
+            contents_right,
+            compact,
+        )
+    };
+
+    if let Ok(contents_expected) = fs::read_to_string(test_dir.join(format!("Expected{suffix}"))) {
+        let actual = merge(false);
+        if contents_expected != actual {
+            print_diff(&contents_expected, &actual);
+            panic!("test failed: outputs differ for {}", test_dir.display());
+        }
+
+        // only run the following part if the file exists
+        let fname_expected_compact = test_dir.join(format!("ExpectedCompact{suffix}"));
+        if let Ok(contents_expected_compact) = fs::read_to_string(fname_expected_compact) {
+            let actual_compact = merge(true);
+            if contents_expected_compact != actual_compact {
+                print_diff(&contents_expected_compact, &actual_compact);
+                panic!(
+                    "test failed: compact outputs differ for {}",
+                    test_dir.display()
+                );
+            }
+        }
+        return;
+    }
+
+    let contents_expected_currently =
+        fs::read_to_string(test_dir.join(format!("ExpectedCurrently{suffix}")))
+            .expect("Unable to read expected or expected-currently file");
+    let contents_expected_ideally =
+        fs::read_to_string(test_dir.join(format!("ExpectedIdeally{suffix}")))
+            .expect("Unable to read expected-ideally file");
+
+    let actual = merge(false);
+    if actual == contents_expected_ideally {
+        // if you find yourself seeing this message:
+        // 1. rename `ExpectedIdeally.<extension>` to `Expected.<extension>`
+        // 2. delete `ExpectedCurrently.<extension>`
+        panic!(
+            "test for {} failed to fail -- it works now!",
+            test_dir.display()
+        );
+    } else if actual != contents_expected_currently {
+        print_diff(&contents_expected_currently, &actual);
+        panic!(
+            "\
+test for {} failed, but output differs from what we currently expect
+please examine the new output and update ExpectedCurrently{suffix} if it looks okay",
+            test_dir.display(),
+        );
+    }
+}
+
+/// End-to-end tests for the "mergiraf merge --semistructured" command
+#[rstest]
+fn integration_semistructured(#[files("examples/*/semistructured/*")] path: PathBuf) {
+    run_test_from_dir(&path);
+}
@@ -5,13 +5,12 @@ use std::time::Duration;
 use diffy_imara::{PatchFormatter, create_patch};
 use mergiraf::line_based::line_based_merge;
 use mergiraf::settings::DisplaySettings;
-use mergiraf::{PathBufExt, line_merge_and_structured_resolution};
+use mergiraf::line_merge_and_structured_resolution;
 
 #[test]
 fn timeout_support() {
     let test_dir = Path::new("examples/java/working/move_and_modify_conflict");
     let ext = "java";
-    #[expect(unstable_name_collisions)]
     let fname_base = test_dir.join(format!("Base.{ext}")).leak();
     let contents_base = fs::read_to_string(&fname_base)
         .expect("Unable to read left file")
@@ -39,7 +38,7 @@ fn timeout_support() {
         None,
         Duration::from_millis(1), // very small timeout: structured merging should never be that fast
         None,
-        false,
+        None,
         None,
     );
 
@@ -4,7 +4,7 @@ use std::time::Duration;
 
 use diffy_imara::{PatchFormatter, create_patch};
 use mergiraf::settings::DisplaySettings;
-use mergiraf::{PathBufExt, line_merge_and_structured_resolution};
+use mergiraf::line_merge_and_structured_resolution;
 use rstest::rstest;
 
 mod common;
@@ -37,7 +37,7 @@ fn compare_against_merge(
         None,
         Duration::from_millis(0),
         language_override_for_test(test_dir),
-        false,
+        None,
         None,
     );
 
@@ -54,7 +54,6 @@ fn compare_against_merge(
 
 fn run_test_from_dir(test_dir: &Path) {
     let suffix = detect_test_suffix(test_dir);
-    #[expect(unstable_name_collisions)]
     let fname_base = test_dir.join(format!("Base{suffix}")).leak();
     let contents_base = fs::read_to_string(&fname_base)
         .expect("Unable to read left file")